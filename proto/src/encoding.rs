@@ -1,8 +1,11 @@
-use std::io::{Write, Error};
+use std::{borrow::Cow, io::{Write, Error}};
 
 pub const BEGIN_FRAME_BYTE: u8 = crate::Frame::BEGIN_FRAME_BYTE;
 pub const END_FRAME_BYTE: u8 = crate::Frame::END_FRAME_BYTE;
 pub const ESCAPE_BYTE: u8 = 0x1B;
+/// software flow control bytes
+pub const XON_BYTE: u8 = 0x11;
+pub const XOFF_BYTE: u8 = 0x13;
 
 pub const ESCAPE_TABLE: &[(u8, [u8; 2])] = &[
     (ESCAPE_BYTE, [ESCAPE_BYTE, 0x41]),
@@ -10,6 +13,35 @@ pub const ESCAPE_TABLE: &[(u8, [u8; 2])] = &[
     (END_FRAME_BYTE, [ESCAPE_BYTE, 0x43]),
 ];
 
+/// Extension of the wire format for links using XON/XOFF flow control, whose drivers would swallow these bytes
+///
+/// Always decoded, but frames are encoded with it only by [`escape_flow_control`]. Firmware built without the
+/// extension (`PROTO_ESCAPE_FLOW_CONTROL` in proto_cpp) rejects these sequences as invalid, so it's used only
+/// on links with XON/XOFF, that couldn't carry the bytes anyway.
+pub const FLOW_CONTROL_ESCAPE_TABLE: &[(u8, [u8; 2])] = &[
+    (XON_BYTE, [ESCAPE_BYTE, 0x44]),
+    (XOFF_BYTE, [ESCAPE_BYTE, 0x45]),
+];
+
+/// escapes flow control bytes in serialized frames, see [`FLOW_CONTROL_ESCAPE_TABLE`]
+///
+/// Escape sequences never contain them, so they can be replaced in the whole wire data at once.
+pub fn escape_flow_control(wire: &[u8]) -> Cow<'_, [u8]> {
+    if !wire.iter().any(|b| *b == XON_BYTE || *b == XOFF_BYTE) {
+        return Cow::Borrowed(wire);
+    }
+
+    let mut out = Vec::with_capacity(wire.len() + 8);
+    for b in wire {
+        match FLOW_CONTROL_ESCAPE_TABLE.iter().find(|(d, _)| d == b) {
+            Some((_, e)) => out.extend(e),
+            None => out.push(*b),
+        }
+    }
+
+    Cow::Owned(out)
+}
+
 
 #[derive(Debug, thiserror::Error)]
 pub enum DecodeError {
@@ -46,23 +78,14 @@ where
 
     fn decode(&mut self, data: &[u8]) -> Result<usize, DecodeError> {
         let mut written = 0;
-        let mut windows = data.windows(2);
 
-        while let Some(window) = windows.next() {
+        // escape sequence may end the data, e.g. escaped CRC byte
+        while written < data.len() {
+            let window = &data[written..data.len().min(written + 2)];
             let (consumed, byte) = decode(window)?;
 
             self.write_all(std::slice::from_ref(&byte))?;
             written += consumed;
-
-            (0..consumed.saturating_sub(1))
-                .for_each(|_| { windows.next(); })
-        }
-
-        if let Some(b) = data.last() {
-            let (consumed, byte) = decode(std::slice::from_ref(b))?;
-
-            self.write_all(std::slice::from_ref(&byte))?;
-            written += consumed;
         }
 
         Ok(written)
@@ -82,6 +105,7 @@ fn decode(window: &[u8]) -> Result<(usize, u8), DecodeError> {
     if window[0] == ESCAPE_BYTE {
         if window.len() > 1 {
             ESCAPE_TABLE.iter()
+                .chain(FLOW_CONTROL_ESCAPE_TABLE)
                 .find_map(|(d, e)| (e[1] == window[1]).then_some((2usize, *d)))
                 .ok_or(DecodeError::InvalidEscapeSequence([window[0], window[1]]))
         } else {
@@ -0,0 +1,128 @@
+use crate::{DeserializeError, Frame};
+
+/// Streaming frame assembler, takes raw bytes as they arrive from the wire
+/// and yields frames once their end byte is seen
+#[derive(Debug, Clone)]
+pub struct FrameBuilder {
+    buf: Vec<u8>,
+    /// frames longer than this (in wire format) are dropped mid-assembly
+    max_len: usize,
+    stats: DecoderStats,
+}
+
+/// What happened to bytes that didn't end up in a completed frame, counted since `FrameBuilder` was created
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecoderStats {
+    /// bytes outside of frames, received while no begin byte was seen
+    pub skipped_bytes: u64,
+    /// unfinished frames abandoned, because begin byte of another one arrived
+    pub resyncs: u64,
+    /// unfinished frames dropped for being longer than the limit
+    pub overflows: u64,
+}
+
+impl FrameBuilder {
+    /// default limit of frame length (in wire format), longer frames are dropped mid-assembly
+    pub const FRAME_MAX_LEN: usize = 1280;
+
+    pub fn new() -> Self {
+        Self::with_max_len(Self::FRAME_MAX_LEN)
+    }
+
+    /// builder dropping frames longer than `max_len` bytes (in wire format)
+    pub fn with_max_len(max_len: usize) -> Self {
+        Self {
+            buf: Vec::with_capacity(1512),
+            max_len,
+            stats: DecoderStats::default(),
+        }
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// changes the limit, unfinished frame already longer than it is dropped with the next byte
+    pub fn set_max_len(&mut self, max_len: usize) {
+        self.max_len = max_len;
+    }
+
+    /// bytes of unfinished frame, waiting for its end byte
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn stats(&self) -> DecoderStats {
+        self.stats
+    }
+
+    /// pushes whole buffer, returning every frame that was completed by it
+    /// (including ones that failed to deserialize)
+    pub fn push_buf(&mut self, buf: &[u8]) -> Vec<Result<Frame, DeserializeError>> {
+        buf.iter()
+            .filter_map(|b| self.push_byte(*b))
+            .collect()
+    }
+
+    /// same as `push_buf`, but every frame comes with its wire bytes
+    /// (useful to show what exactly was received when frame is invalid)
+    pub fn push_buf_raw(&mut self, buf: &[u8]) -> Vec<(Vec<u8>, Result<Frame, DeserializeError>)> {
+        buf.iter()
+            .filter_map(|b| self.push(*b, |raw| (raw.to_vec(), Frame::deserialize(raw))))
+            .collect()
+    }
+
+    /// pushes single byte, returns `Some` if this byte ended a frame
+    pub fn push_byte(&mut self, byte: u8) -> Option<Result<Frame, DeserializeError>> {
+        self.push(byte, Frame::deserialize)
+    }
+
+    /// pushes single byte, `complete` is called with wire bytes of the frame this byte ended
+    fn push<T>(&mut self, byte: u8, complete: impl FnOnce(&[u8]) -> T) -> Option<T> {
+        match byte {
+            Frame::BEGIN_FRAME_BYTE => {
+                if !self.buf.is_empty() {
+                    self.stats.resyncs += 1;
+                }
+
+                self.buf.clear();
+                self.buf.push(byte);
+
+                None
+            },
+            Frame::END_FRAME_BYTE => {
+                if !self.buf.is_empty() {
+                    self.buf.push(byte);
+
+                    let result = complete(&self.buf);
+                    self.buf.clear();
+
+                    Some(result)
+                } else {
+                    self.stats.skipped_bytes += 1;
+                    None
+                }
+            },
+            _ => {
+                if self.buf.is_empty() {
+                    self.stats.skipped_bytes += 1;
+                } else {
+                    self.buf.push(byte);
+                }
+
+                if self.buf.len() >= self.max_len {
+                    self.stats.overflows += 1;
+                    self.buf.clear();
+                }
+
+                None
+            }
+        }
+    }
+}
+
+impl Default for FrameBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -5,7 +5,24 @@ use std::io::{Write, self, Cursor, Read};
 use crc::{Crc, CRC_32_MPEG_2};
 use encoding::{DecodeError, Encoding};
 
-mod encoding;
+pub mod encoding;
+mod frame_builder;
+pub mod transfer;
+
+pub use frame_builder::{DecoderStats, FrameBuilder};
+
+/// CRC-32/MPEG-2 of `bytes` zero padded to a multiple of 4 bytes, as frames are checked
+///
+/// STM32 CRC unit takes whole 32 bit words, so firmware pads the hashed fields the same way.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let crc = Crc::<u32>::new(&CRC_32_MPEG_2);
+    let mut hasher = crc.digest();
+
+    hasher.update(bytes);
+    hasher.update(&[0; 4][..(4 - bytes.len() % 4) % 4]);
+
+    hasher.finalize()
+}
 
 #[derive(Debug, thiserror::Error)]
 pub enum SerializeError {
@@ -34,6 +51,20 @@ pub enum DeserializeError {
     DecodeError(#[from] DecodeError),
 }
 
+impl DeserializeError {
+    /// short description without values, to group errors by
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DeserializeError::InvalidFrameBeginByte => "invalid start byte",
+            DeserializeError::InvalidFrameEndByte => "invalid end byte",
+            DeserializeError::UnexpectedEOF => "truncated",
+            DeserializeError::ExpectedFrameEnd(_) => "longer than declared",
+            DeserializeError::CRC32MissMatch { .. } => "CRC mismatch",
+            DeserializeError::DecodeError(_) => "invalid escape sequence",
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 #[error("command is too long ({0:} bytes)")]
 pub struct CommandTooLongError(usize);
@@ -77,7 +108,8 @@ impl Frame {
             Ok(())
         })?;
 
-        out.write_all(&self.calculate_crc32()?.to_be_bytes())?;
+        // escaped like the rest, it may contain any byte, proto_cpp escapes it the same way
+        out.encode(&self.calculate_crc32()?.to_be_bytes())?;
         out.write_all(&[Self::END_FRAME_BYTE])?;
 
         Ok(out)
@@ -128,8 +160,8 @@ impl Frame {
         // but `data` is original data (not sliced), so its length is +2
         let position = cursor.position() as usize;
         if position != cursor.into_inner().len() {
-            // we should have exhausted all data by this point 
-            unreachable!()
+            // more data than declared length accounts for
+            return Err(DeserializeError::ExpectedFrameEnd(position));
         }
 
         let frame = Frame {
@@ -199,7 +231,7 @@ impl Frame {
 
 #[cfg(test)]
 mod tests {
-    use crate::Frame;
+    use crate::{DeserializeError, Frame, FrameBuilder, encoding::{self, ESCAPE_BYTE, XOFF_BYTE, XON_BYTE}};
 
     #[test]
     fn serialize_deserialize() {
@@ -222,6 +254,76 @@ mod tests {
         assert_eq!(frame, Frame::deserialize(&serialized).unwrap());
     }
 
+    #[test]
+    fn trailing_bytes() {
+        let frame = Frame {
+            sender: 1,
+            receiver: 2,
+            data: b"ping".to_vec(),
+        };
+
+        // received with garbage before the end byte, e.g. from a noisy line
+        let mut serialized = frame.serialize().unwrap();
+        serialized.insert(serialized.len() - 1, 0x55);
+
+        assert!(matches!(Frame::deserialize(&serialized), Err(DeserializeError::ExpectedFrameEnd(12))));
+    }
+
+    #[test]
+    fn crc_escaped() {
+        // like proto_cpp does, delimiters in CRC would split the frame otherwise
+        let frame = (0..=u16::MAX)
+            .map(|i| Frame { sender: 1, receiver: 2, data: i.to_be_bytes().to_vec() })
+            .find(|frame| frame.calculate_crc32().unwrap().to_be_bytes().contains(&Frame::END_FRAME_BYTE))
+            .unwrap();
+
+        let serialized = frame.serialize().unwrap();
+        assert_eq!(serialized.iter().filter(|b| **b == Frame::END_FRAME_BYTE).count(), 1);
+        assert!(serialized.contains(&ESCAPE_BYTE));
+        assert_eq!(frame, Frame::deserialize(&serialized).unwrap());
+    }
+
+    #[test]
+    fn escaped_last_crc_byte() {
+        let frame = (0..=u16::MAX)
+            .map(|i| Frame { sender: 1, receiver: 2, data: i.to_be_bytes().to_vec() })
+            .find(|frame| frame.calculate_crc32().unwrap() & 0xFF == Frame::END_FRAME_BYTE as u32)
+            .unwrap();
+
+        let serialized = frame.serialize().unwrap();
+        assert_eq!(serialized[serialized.len() - 3..], [ESCAPE_BYTE, 0x43, Frame::END_FRAME_BYTE]);
+        assert_eq!(frame, Frame::deserialize(&serialized).unwrap());
+    }
+
+    #[test]
+    fn flow_control_bytes_escaped() {
+        let frame = Frame {
+            sender: XON_BYTE,
+            receiver: XOFF_BYTE,
+            data: vec![XON_BYTE, 0x00, XOFF_BYTE, XOFF_BYTE],
+        };
+
+        // only on request, firmware without the extension doesn't know these escapes
+        let serialized = frame.serialize().unwrap();
+        assert!(serialized.contains(&XON_BYTE));
+        assert_eq!(frame, Frame::deserialize(&serialized).unwrap());
+
+        let escaped = encoding::escape_flow_control(&serialized);
+        assert!(!escaped.contains(&XON_BYTE));
+        assert!(!escaped.contains(&XOFF_BYTE));
+        assert_eq!(frame, Frame::deserialize(&escaped).unwrap());
+
+        // CRC may contain them too
+        let frame = (0..=u16::MAX)
+            .map(|i| Frame { sender: 1, receiver: 2, data: i.to_be_bytes().to_vec() })
+            .find(|frame| frame.calculate_crc32().unwrap().to_be_bytes().contains(&XON_BYTE))
+            .unwrap();
+
+        let escaped = encoding::escape_flow_control(&frame.serialize().unwrap()).into_owned();
+        assert!(!escaped.contains(&XON_BYTE));
+        assert_eq!(frame, Frame::deserialize(&escaped).unwrap());
+    }
+
     #[test]
     fn serialized_len() {
         let frame = Frame {
@@ -242,4 +344,112 @@ mod tests {
         assert_eq!(frame.serialized_len(), frame.serialize().unwrap().len());
         assert_eq!(frame.serialized_len(), 20);
     }
+
+    #[test]
+    fn crc32_padding() {
+        for len in 0..9 {
+            let frame = Frame {
+                sender: 7,
+                receiver: 42,
+                data: (0..len).collect(),
+            };
+
+            let mut hashed = vec![frame.sender, frame.receiver];
+            hashed.extend((len as u16).to_be_bytes());
+            hashed.extend(&frame.data);
+
+            assert_eq!(frame.calculate_crc32().unwrap(), crate::crc32(&hashed));
+        }
+    }
+
+    #[test]
+    fn frame_builder() {
+        let frame = Frame {
+            sender: 1,
+            receiver: 2,
+            data: b"(nested)".to_vec(),
+        };
+
+        let mut wire = b"garbage)".to_vec();
+        wire.extend(frame.serialize().unwrap());
+        wire.extend(b"(\x00)");
+        wire.extend(frame.serialize().unwrap());
+
+        let mut builder = FrameBuilder::new();
+        let (first, rest) = wire.split_at(wire.len() / 2);
+
+        let mut results = builder.push_buf(first);
+        results.extend(builder.push_buf(rest));
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &frame);
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap(), &frame);
+    }
+
+    #[test]
+    fn frame_builder_stats() {
+        let frame = Frame {
+            sender: 1,
+            receiver: 2,
+            data: b"data".to_vec(),
+        };
+        let serialized = frame.serialize().unwrap();
+
+        let mut builder = FrameBuilder::new();
+
+        // garbage, frame interrupted by another one, and start of the third
+        builder.push_buf(b"ab)");
+        builder.push_buf(&serialized[..5]);
+        builder.push_buf(&serialized);
+        builder.push_buf(&serialized[..3]);
+
+        let stats = builder.stats();
+        assert_eq!(stats.skipped_bytes, 3);
+        assert_eq!(stats.resyncs, 1);
+        assert_eq!(stats.overflows, 0);
+        assert_eq!(builder.buffered(), 3);
+
+        builder.push_buf(&[0; FrameBuilder::FRAME_MAX_LEN]);
+        assert_eq!(builder.stats().overflows, 1);
+        assert_eq!(builder.buffered(), 0);
+    }
+
+    #[test]
+    fn frame_builder_max_len() {
+        let frame = Frame {
+            sender: 1,
+            receiver: 2,
+            data: vec![7; 20],
+        };
+        let serialized = frame.serialize().unwrap();
+
+        let mut builder = FrameBuilder::with_max_len(serialized.len() - 1);
+        assert!(builder.push_buf(&serialized).is_empty());
+        assert_eq!(builder.stats().overflows, 1);
+
+        builder.set_max_len(serialized.len());
+        assert_eq!(builder.push_buf(&serialized)[0].as_ref().unwrap(), &frame);
+    }
+
+    #[test]
+    fn frame_builder_raw() {
+        let frame = Frame {
+            sender: 1,
+            receiver: 2,
+            data: b"(nested)".to_vec(),
+        };
+
+        let serialized = frame.serialize().unwrap();
+        let mut wire = b"(\x00)".to_vec();
+        wire.extend(&serialized);
+
+        let results = FrameBuilder::new().push_buf_raw(&wire);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, b"(\x00)");
+        assert!(results[0].1.is_err());
+        assert_eq!(results[1].0, serialized);
+        assert_eq!(results[1].1.as_ref().unwrap(), &frame);
+    }
 }
@@ -0,0 +1,189 @@
+//! File transfer carried in frame payloads
+//!
+//! Sender opens a transfer with [`Message::Start`] telling what the file is for, sends the file in [`Message::Chunk`]s and closes it with
+//! [`Message::End`]. Receiver answers `Start` and every `Chunk` with [`Message::Ack`] holding offset of the
+//! first byte it doesn't have yet, and `End` with [`Message::Verified`] once it checked [`crate::crc32`] of
+//! the whole file.
+//!
+//! Sender sends again whatever wasn't acknowledged in time, always from the acknowledged offset, so lost
+//! chunks are repeated. Receiver keeping what it got of an interrupted transfer acknowledges `Start` of the
+//! same file (size and CRC) with where it stopped, and the transfer is resumed from there.
+//!
+//! Firmware update is a transfer with [`Target::Firmware`]. Device writes the image to its update slot instead of
+//! keeping it as a file, and switches to it only after it was verified, so a failed or aborted update leaves the
+//! running firmware in place.
+//!
+//! First payload byte tells the message, integers are big endian like frame fields.
+
+use std::io::{Cursor, Read};
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransferDecodeError {
+    #[error("empty payload")]
+    Empty,
+    #[error("unknown transfer message {0:#04x}")]
+    UnknownMessage(u8),
+    #[error("transfer message {0:#04x} is truncated")]
+    Truncated(u8),
+    #[error("unknown transfer target {0}")]
+    UnknownTarget(u8),
+}
+
+/// What transferred file is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Target {
+    /// file kept by the receiver
+    #[default]
+    File = 0,
+    /// firmware image, applied by the receiver once verified
+    Firmware = 1,
+}
+
+impl TryFrom<u8> for Target {
+    type Error = TransferDecodeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Target::File),
+            1 => Ok(Target::Firmware),
+            value => Err(TransferDecodeError::UnknownTarget(value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// sender opens transfer of `size` bytes with [`crate::crc32`] `crc32`
+    Start {
+        target: Target,
+        size: u32,
+        crc32: u32,
+    },
+    /// sender sends `data` at `offset`
+    Chunk {
+        offset: u32,
+        data: Vec<u8>,
+    },
+    /// sender sent the whole file
+    End,
+    /// sender gave up, receiver may keep what it has to resume later
+    Abort,
+    /// receiver has everything before `offset`
+    Ack {
+        offset: u32,
+    },
+    /// receiver checked CRC of the whole file
+    Verified {
+        ok: bool,
+    },
+}
+
+impl Message {
+    const START: u8 = 0xF0;
+    const CHUNK: u8 = 0xF1;
+    const END: u8 = 0xF2;
+    const ABORT: u8 = 0xF3;
+    const ACK: u8 = 0xF4;
+    const VERIFIED: u8 = 0xF5;
+
+    /// bytes of chunk payload taken by message byte and offset
+    pub const CHUNK_HEADER_LEN: usize = 5;
+    /// most data a chunk can carry in one frame
+    pub const MAX_CHUNK_LEN: usize = u16::MAX as usize - Self::CHUNK_HEADER_LEN;
+
+    pub fn to_payload(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+
+        match self {
+            Message::Start { target, size, crc32 } => {
+                out.push(Self::START);
+                out.push(*target as u8);
+                out.extend(size.to_be_bytes());
+                out.extend(crc32.to_be_bytes());
+            },
+            Message::Chunk { offset, data } => {
+                out.push(Self::CHUNK);
+                out.extend(offset.to_be_bytes());
+                out.extend(data);
+            },
+            Message::End => out.push(Self::END),
+            Message::Abort => out.push(Self::ABORT),
+            Message::Ack { offset } => {
+                out.push(Self::ACK);
+                out.extend(offset.to_be_bytes());
+            },
+            Message::Verified { ok } => {
+                out.push(Self::VERIFIED);
+                out.push(*ok as u8);
+            },
+        }
+
+        out
+    }
+
+    pub fn from_payload(payload: &[u8]) -> Result<Self, TransferDecodeError> {
+        let (&kind, rest) = payload.split_first().ok_or(TransferDecodeError::Empty)?;
+        let mut cursor = Cursor::new(rest);
+
+        // big endian integer of `len` bytes
+        let mut read = |len: usize| {
+            let mut buf = [0; 4];
+            cursor
+                .read_exact(&mut buf[4 - len..])
+                .map(|_| u32::from_be_bytes(buf))
+                .map_err(|_| TransferDecodeError::Truncated(kind))
+        };
+
+        let message = match kind {
+            Self::START => Message::Start {
+                target: (read(1)? as u8).try_into()?,
+                size: read(4)?,
+                crc32: read(4)?,
+            },
+            Self::CHUNK => Message::Chunk {
+                offset: read(4)?,
+                data: rest[4..].to_vec(),
+            },
+            Self::END => Message::End,
+            Self::ABORT => Message::Abort,
+            Self::ACK => Message::Ack { offset: read(4)? },
+            Self::VERIFIED => Message::Verified { ok: read(1)? != 0 },
+            kind => return Err(TransferDecodeError::UnknownMessage(kind)),
+        };
+
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Message, Target, TransferDecodeError};
+
+    #[test]
+    fn payload_round_trip() {
+        let messages = [
+            Message::Start { target: Target::File, size: 70_000, crc32: 0xDEADBEEF },
+            Message::Start { target: Target::Firmware, size: 1024, crc32: 1 },
+            Message::Chunk { offset: 256, data: b"chunk".to_vec() },
+            Message::Chunk { offset: 0, data: Vec::new() },
+            Message::End,
+            Message::Abort,
+            Message::Ack { offset: 512 },
+            Message::Verified { ok: true },
+            Message::Verified { ok: false },
+        ];
+
+        for message in messages {
+            assert_eq!(Message::from_payload(&message.to_payload()).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn invalid_payload() {
+        assert!(matches!(Message::from_payload(&[]), Err(TransferDecodeError::Empty)));
+        assert!(matches!(Message::from_payload(b"hello"), Err(TransferDecodeError::UnknownMessage(b'h'))));
+        assert!(matches!(Message::from_payload(&[0xF1, 0, 0]), Err(TransferDecodeError::Truncated(0xF1))));
+        assert!(matches!(Message::from_payload(&[0xF5]), Err(TransferDecodeError::Truncated(0xF5))));
+        assert!(matches!(Message::from_payload(&[0xF0, 7, 0, 0, 0, 0, 0, 0, 0, 0]), Err(TransferDecodeError::UnknownTarget(7))));
+    }
+}
@@ -18,6 +18,11 @@ constexpr uint8_t BEGIN_FRAME_BYTE = (uint8_t)'(';
 /// @brief Byte that must be at the end of each serialized `Frame`
 constexpr uint8_t END_FRAME_BYTE = (uint8_t)')';
 
+/// @brief XON byte of software flow control
+constexpr uint8_t XON_BYTE = 0x11;
+/// @brief XOFF byte of software flow control
+constexpr uint8_t XOFF_BYTE = 0x13;
+
 /// @brief Lookup table for escape characters
 /// @note Character on the left is replaced with sequence of { `ESCAPE_BYTE`, right byte }
 static constexpr uint8_t ESCAPE_TABLE[3][2] = {
@@ -26,6 +31,14 @@ static constexpr uint8_t ESCAPE_TABLE[3][2] = {
     { END_FRAME_BYTE, 0x43 },
 };
 
+/// @brief Escapes of software flow control bytes, extension of the wire format for links using XON/XOFF
+/// @note They are always decoded, but encoded only when built with `PROTO_ESCAPE_FLOW_CONTROL` defined
+/// @note Firmware built before this extension rejects them with `InvalidEscapeSequence`
+static constexpr uint8_t FLOW_CONTROL_ESCAPE_TABLE[2][2] = {
+    { XON_BYTE, 0x44 },
+    { XOFF_BYTE, 0x45 },
+};
+
 /// @brief Maximum frame size (pre encoding) of any `Frame`
 constexpr size_t FRAME_MAX_SIZE = 1280;
 
@@ -21,6 +21,16 @@ void encode_byte(uint8_t byte, IStaticVec<uint8_t>& out) {
         }
     }
 
+#ifdef PROTO_ESCAPE_FLOW_CONTROL
+    for (auto e : FLOW_CONTROL_ESCAPE_TABLE) {
+        if (byte == e[0]) {
+            out.push_back(ESCAPE_BYTE);
+            out.push_back(e[1]);
+            return;
+        }
+    }
+#endif
+
     // no need to escape
     out.push_back(byte);
 }
@@ -120,6 +130,15 @@ DeserializeError decode_byte(std::span<const uint8_t> data, uint8_t& out, size_t
             }
         }
 
+        // accepted even when not encoded, the other side may be on a link with XON/XOFF
+        for (auto e : FLOW_CONTROL_ESCAPE_TABLE) {
+            if (e[1] == data[1]) {
+                out = e[0];
+                read = 2;
+                return DeserializeOk;
+            }
+        }
+
         return InvalidEscapeSequence;
     } else if (data[0] == BEGIN_FRAME_BYTE || data[0] == END_FRAME_BYTE) {
         // `BEGIN_FRAME_BYTE` and `END_FRAME_BYTE` must be always escaped
@@ -0,0 +1,204 @@
+//! Measures link throughput and error rate against a device echoing frames back
+//!
+//! Every frame carries a big endian u32 sequence number followed by a filler derived from
+//! it, so echoed frames can be matched to the sent ones and checked for corruption.
+
+use std::{collections::HashMap, time::Duration};
+
+use clap::Parser;
+use proto::{DeserializeError, Frame, FrameBuilder};
+use proto_tools::link::LinkArgs;
+use tokio::{io::{AsyncReadExt, AsyncWriteExt}, time::Instant};
+
+#[derive(Debug, Parser)]
+#[command(about = "Link throughput and error-rate tester, requires echoing device")]
+struct Args {
+    #[command(flatten)]
+    link: LinkArgs,
+
+    #[arg(long, default_value_t = 123)]
+    sender: u8,
+
+    #[arg(long, default_value_t = 100)]
+    receiver: u8,
+
+    /// payload size in bytes (at least 4, for the sequence number)
+    #[arg(short, long, default_value_t = 32, value_parser = clap::value_parser!(u16).range(4..))]
+    size: u16,
+
+    /// number of frames to send
+    #[arg(short, long, default_value_t = 1000)]
+    count: u32,
+
+    /// stop sending after this many seconds, even if `count` wasn't reached
+    #[arg(short, long)]
+    duration: Option<f64>,
+
+    /// maximum number of frames awaiting echo
+    #[arg(short, long, default_value_t = 8)]
+    window: usize,
+
+    /// frames not echoed within this many milliseconds are counted as lost
+    #[arg(short, long, default_value_t = 1000)]
+    timeout: u64,
+}
+
+#[derive(Debug, Default)]
+struct Stats {
+    sent: u64,
+    echoed: u64,
+    lost: u64,
+    corrupted: u64,
+    unexpected: u64,
+    crc_errors: u64,
+    decode_errors: u64,
+    bytes_sent: u64,
+    bytes_received: u64,
+    rtt_total: Duration,
+    rtt_min: Option<Duration>,
+    rtt_max: Duration,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("warn"));
+    let args = Args::parse();
+
+    let link = args.link.open().await?;
+    let (mut recv, mut send) = tokio::io::split(link);
+
+    let timeout = Duration::from_millis(args.timeout);
+    let mut stats = Stats::default();
+    let mut in_flight = HashMap::<u32, Instant>::new();
+    let mut frame_builder = FrameBuilder::new();
+    let mut rx_buffer = vec![0u8; 1024];
+
+    let start = Instant::now();
+    let send_deadline = args.duration.map(|d| start + Duration::from_secs_f64(d));
+    let mut next_seq = 0u32;
+
+    loop {
+        let done_sending = next_seq == args.count
+            || send_deadline.is_some_and(|deadline| Instant::now() >= deadline);
+
+        if done_sending && in_flight.is_empty() {
+            break;
+        }
+
+        let can_send = !done_sending && in_flight.len() < args.window;
+
+        tokio::select! {
+            _ = std::future::ready(()), if can_send => {
+                let frame = Frame {
+                    sender: args.sender,
+                    receiver: args.receiver,
+                    data: payload(next_seq, args.size as usize),
+                };
+
+                let wire = frame.serialize()?;
+                send.write_all(&wire).await?;
+
+                in_flight.insert(next_seq, Instant::now());
+                stats.sent += 1;
+                stats.bytes_sent += wire.len() as u64;
+                next_seq += 1;
+            }
+
+            read = recv.read(&mut rx_buffer) => {
+                let read = read?;
+                if read == 0 {
+                    anyhow::bail!("{} closed", args.link.target);
+                }
+
+                stats.bytes_received += read as u64;
+                for result in frame_builder.push_buf(&rx_buffer[..read]) {
+                    stats.record(result, &mut in_flight, args.size as usize);
+                }
+            }
+
+            _ = tokio::time::sleep(Duration::from_millis(10)) => {}
+        }
+
+        let expired = in_flight.len();
+        in_flight.retain(|_, sent_at| sent_at.elapsed() < timeout);
+        stats.lost += (expired - in_flight.len()) as u64;
+    }
+
+    stats.report(start.elapsed());
+    Ok(())
+}
+
+/// sequence number followed by filler, which is derived from it
+fn payload(seq: u32, size: usize) -> Vec<u8> {
+    let mut data = seq.to_be_bytes().to_vec();
+    data.extend((0..size - 4).map(|i| (seq as u8).wrapping_add(i as u8)));
+
+    data
+}
+
+impl Stats {
+    fn record(
+        &mut self,
+        result: Result<Frame, DeserializeError>,
+        in_flight: &mut HashMap<u32, Instant>,
+        size: usize,
+    ) {
+        let frame = match result {
+            Ok(frame) => frame,
+            Err(DeserializeError::CRC32MissMatch { .. }) => {
+                self.crc_errors += 1;
+                return;
+            },
+            Err(_) => {
+                self.decode_errors += 1;
+                return;
+            },
+        };
+
+        let seq = frame.data
+            .get(..4)
+            .map(|seq| u32::from_be_bytes(seq.try_into().unwrap()));
+
+        let Some(sent_at) = seq.and_then(|seq| in_flight.remove(&seq)) else {
+            // echo of an expired frame, or something the device sent on its own
+            self.unexpected += 1;
+            return;
+        };
+
+        if frame.data != payload(seq.unwrap(), size) {
+            self.corrupted += 1;
+            return;
+        }
+
+        let rtt = sent_at.elapsed();
+        self.echoed += 1;
+        self.rtt_total += rtt;
+        self.rtt_min = Some(self.rtt_min.map_or(rtt, |min| min.min(rtt)));
+        self.rtt_max = self.rtt_max.max(rtt);
+    }
+
+    fn report(&self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        let percent = |n: u64| if self.sent == 0 { 0.0 } else { n as f64 * 100.0 / self.sent as f64 };
+
+        println!("elapsed:        {:.3} s", secs);
+        println!("frames sent:    {}", self.sent);
+        println!("frames echoed:  {} ({:.2} frames/s)", self.echoed, self.echoed as f64 / secs);
+        println!("lost:           {} ({:.2}%)", self.lost, percent(self.lost));
+        println!("corrupted:      {} ({:.2}%)", self.corrupted, percent(self.corrupted));
+        println!("crc errors:     {} ({:.2}%)", self.crc_errors, percent(self.crc_errors));
+        println!("decode errors:  {}", self.decode_errors);
+        println!("unexpected:     {}", self.unexpected);
+        println!("tx throughput:  {:.0} B/s", self.bytes_sent as f64 / secs);
+        println!("rx throughput:  {:.0} B/s", self.bytes_received as f64 / secs);
+
+        if let Some(min) = self.rtt_min {
+            println!(
+                "rtt:            min {:?} / avg {:?} / max {:?}",
+                min,
+                self.rtt_total / self.echoed as u32,
+                self.rtt_max,
+            );
+        }
+    }
+}
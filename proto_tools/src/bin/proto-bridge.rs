@@ -0,0 +1,245 @@
+//! Exposes serial device over TCP
+//!
+//! Every frame received from the device is forwarded to connected clients, and every
+//! frame received from a client is written to the device. Listeners can be restricted
+//! to a set of receiver addresses, in which case clients connected through them only
+//! see frames addressed to those receivers.
+
+use std::{net::SocketAddr, str::FromStr, sync::Arc};
+
+use anyhow::Context;
+use clap::Parser;
+use proto::FrameBuilder;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::{broadcast::{self, error::RecvError}, mpsc},
+};
+use tokio_serial::SerialStream;
+
+#[derive(Debug, Parser)]
+#[command(about = "Serial to TCP bridge for protocol frames")]
+struct Args {
+    /// serial port to expose (e.g. /dev/ttyUSB0 or COM7)
+    port: String,
+
+    #[arg(short, long, default_value_t = 115200)]
+    baud_rate: u32,
+
+    /// address to listen on, optionally followed by `=` and a comma separated list
+    /// of receiver addresses clients of this listener are interested in
+    /// (e.g. `0.0.0.0:5001=100,101`), can be passed multiple times
+    #[arg(short, long, default_value = "0.0.0.0:5000")]
+    listen: Vec<ListenerSpec>,
+
+    /// name the bridge is announced under over mDNS, the serial port if not given
+    #[cfg(feature = "mdns")]
+    #[arg(short, long)]
+    name: Option<String>,
+
+    /// don't announce the bridge over mDNS
+    #[cfg(feature = "mdns")]
+    #[arg(long)]
+    no_announce: bool,
+}
+
+#[derive(Debug, Clone)]
+struct ListenerSpec {
+    addr: SocketAddr,
+    receivers: Option<Arc<[u8]>>,
+}
+
+/// frame received from the device, already in wire format
+#[derive(Debug, Clone)]
+struct Packet {
+    receiver: u8,
+    wire: Arc<[u8]>,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
+    let args = Args::parse();
+
+    let device = SerialStream::open(&tokio_serial::new(&args.port, args.baud_rate))
+        .with_context(|| format!("unable to open {}", args.port))?;
+    let (mut recv, mut send) = tokio::io::split(device);
+
+    let (packets_tx, _) = broadcast::channel::<Packet>(256);
+    let (device_tx, mut device_rx) = mpsc::channel::<Vec<u8>>(64);
+
+    // announcements last as long as their daemons
+    #[cfg(feature = "mdns")]
+    let mut announcements = Vec::new();
+
+    for spec in &args.listen {
+        let listener = TcpListener::bind(spec.addr)
+            .await
+            .with_context(|| format!("unable to listen on {}", spec.addr))?;
+
+        log::info!("listening on {}", spec.addr);
+
+        #[cfg(feature = "mdns")]
+        if !args.no_announce {
+            let name = args.name.as_deref().unwrap_or(&args.port);
+            // instance names have to be unique
+            let name = match args.listen.len() {
+                1 => name.to_owned(),
+                _ => format!("{} ({})", name, spec.addr.port()),
+            };
+            let receivers = spec.receivers.as_ref().map(|receivers| {
+                receivers.iter().map(u8::to_string).collect::<Vec<_>>().join(",")
+            });
+
+            match proto_tools::discovery::announce(&name, spec.addr.port(), &args.port, receivers.as_deref()) {
+                Ok(daemon) => announcements.push(daemon),
+                Err(err) => log::warn!("{:#}", err),
+            }
+        }
+
+        tokio::spawn(accept_clients(listener, spec.receivers.clone(), packets_tx.clone(), device_tx.clone()));
+    }
+
+    // device -> clients
+    let reader = async {
+        let mut rx_buffer = vec![0u8; 1024];
+        let mut frame_builder = FrameBuilder::new();
+
+        loop {
+            let read = recv.read(&mut rx_buffer).await?;
+            if read == 0 {
+                anyhow::bail!("{} closed", args.port);
+            }
+
+            for result in frame_builder.push_buf(&rx_buffer[..read]) {
+                match result {
+                    Ok(frame) => {
+                        let packet = Packet {
+                            receiver: frame.receiver,
+                            wire: frame.serialize()?.into(),
+                        };
+
+                        // no clients connected is not an error
+                        let _ = packets_tx.send(packet);
+                    },
+                    Err(err) => log::info!("discarded frame from device, reason `{}`", err),
+                }
+            }
+        }
+    };
+
+    // clients -> device
+    let writer = async {
+        while let Some(data) = device_rx.recv().await {
+            send.write_all(&data).await?;
+        }
+
+        anyhow::Ok(())
+    };
+
+    tokio::select! {
+        result = reader => result,
+        result = writer => result,
+    }
+}
+
+async fn accept_clients(
+    listener: TcpListener,
+    receivers: Option<Arc<[u8]>>,
+    packets: broadcast::Sender<Packet>,
+    device: mpsc::Sender<Vec<u8>>,
+) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                log::info!("client {} connected", peer);
+
+                let receivers = receivers.clone();
+                let packets = packets.subscribe();
+                let device = device.clone();
+
+                tokio::spawn(async move {
+                    if let Err(err) = handle_client(stream, peer, receivers, packets, device).await {
+                        log::warn!("client {} failed: {:?}", peer, err);
+                    }
+
+                    log::info!("client {} disconnected", peer);
+                });
+            },
+            Err(err) => log::warn!("accept failed: {:?}", err),
+        }
+    }
+}
+
+async fn handle_client(
+    stream: TcpStream,
+    peer: SocketAddr,
+    receivers: Option<Arc<[u8]>>,
+    mut packets: broadcast::Receiver<Packet>,
+    device: mpsc::Sender<Vec<u8>>,
+) -> anyhow::Result<()> {
+    let (mut recv, mut send) = stream.into_split();
+
+    let mut rx_buffer = vec![0u8; 1024];
+    let mut frame_builder = FrameBuilder::new();
+
+    loop {
+        tokio::select! {
+            packet = packets.recv() => {
+                match packet {
+                    Ok(packet) => {
+                        let wanted = receivers
+                            .as_ref()
+                            .is_none_or(|r| r.contains(&packet.receiver));
+
+                        if wanted {
+                            send.write_all(&packet.wire).await?;
+                        }
+                    },
+                    Err(RecvError::Lagged(n)) => log::warn!("client {} lagged behind, {} frames dropped", peer, n),
+                    Err(RecvError::Closed) => return Ok(()),
+                }
+            }
+
+            read = recv.read(&mut rx_buffer) => {
+                let read = read?;
+                if read == 0 {
+                    return Ok(());
+                }
+
+                // only whole frames are forwarded, so clients can't interleave each other's bytes
+                for result in frame_builder.push_buf(&rx_buffer[..read]) {
+                    match result {
+                        Ok(frame) => device.send(frame.serialize()?).await?,
+                        Err(err) => log::info!("discarded frame from {}, reason `{}`", peer, err),
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for ListenerSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, receivers) = match s.split_once('=') {
+            Some((addr, receivers)) => {
+                let receivers = receivers
+                    .split(',')
+                    .map(|r| r.trim().parse::<u8>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .with_context(|| format!("invalid receiver list `{}`", receivers))?;
+
+                (addr, Some(receivers.into()))
+            },
+            None => (s, None),
+        };
+
+        Ok(Self {
+            addr: addr.parse()
+                .with_context(|| format!("invalid listen address `{}`", addr))?,
+            receivers,
+        })
+    }
+}
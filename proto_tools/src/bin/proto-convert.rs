@@ -0,0 +1,38 @@
+//! Converts capture files between formats
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use proto_tools::capture::{self, CaptureWriter, Format};
+
+#[derive(Debug, Parser)]
+#[command(about = "Convert capture files between jsonl, csv, pcapng, pcap and hex formats")]
+struct Args {
+    input: PathBuf,
+
+    output: PathBuf,
+
+    /// format of input file, guessed from extension if not provided
+    #[arg(short, long)]
+    from: Option<Format>,
+
+    /// format of output file, guessed from extension if not provided
+    #[arg(short, long)]
+    to: Option<Format>,
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("warn"));
+    let args = Args::parse();
+
+    let records = capture::read(&args.input, args.from)?;
+    let mut writer = CaptureWriter::create(&args.output, args.to)?;
+
+    for record in &records {
+        writer.write(record)?;
+    }
+    writer.flush()?;
+
+    eprintln!("converted {} frames", records.len());
+    Ok(())
+}
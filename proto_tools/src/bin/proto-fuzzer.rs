@@ -0,0 +1,177 @@
+//! Sends deliberately malformed frames to a device, checking it keeps responding
+//!
+//! Every `--probe-every` cases a valid probe frame is sent, and any valid frame received
+//! within `--probe-timeout` counts as the device being alive. Whatever arrived before the probe
+//! is discarded, so a late answer to an earlier case doesn't pass for it.
+
+use std::time::Duration;
+
+use clap::{Parser, ValueEnum};
+use proto::{Frame, FrameBuilder};
+use proto_tools::{fuzz::Case, link::{Link, LinkArgs}};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use tokio::{io::{AsyncReadExt, AsyncWriteExt, ReadHalf, WriteHalf}, time::Instant};
+
+#[derive(Debug, Parser)]
+#[command(about = "Sends malformed frames to a device and checks it survives")]
+struct Args {
+    #[command(flatten)]
+    link: LinkArgs,
+
+    #[arg(long, default_value_t = 123)]
+    sender: u8,
+
+    #[arg(long, default_value_t = 100)]
+    receiver: u8,
+
+    /// payload of the liveness probe frame
+    #[arg(short, long, default_value = "ping")]
+    probe: String,
+
+    /// number of malformed frames to send
+    #[arg(short = 'n', long, default_value_t = 1000)]
+    iterations: u32,
+
+    /// malformed frames sent per second
+    #[arg(short, long, default_value_t = 50.0, value_parser = parse_rate)]
+    rate: f64,
+
+    /// send probe after this many malformed frames
+    #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(u32).range(1..))]
+    probe_every: u32,
+
+    /// milliseconds to wait for probe response
+    #[arg(long, default_value_t = 500)]
+    probe_timeout: u64,
+
+    /// give up after this many consecutive failed probes
+    #[arg(long, default_value_t = 3)]
+    max_failed_probes: u32,
+
+    /// mutations to use, all if not provided
+    #[arg(short, long, value_delimiter = ',')]
+    cases: Vec<Case>,
+
+    /// seed for the random generator, to make a run reproducible
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+/// input is quiet for this long before probe is sent
+const DRAIN_QUIET: Duration = Duration::from_millis(20);
+
+fn parse_rate(rate: &str) -> Result<f64, String> {
+    match rate.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate.is_finite() => Ok(rate),
+        Ok(_) => Err("rate must be a positive number".into()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
+    let args = Args::parse();
+
+    let seed = args.seed.unwrap_or_else(rand::random);
+    log::info!("using seed {}", seed);
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let cases = if args.cases.is_empty() {
+        Case::value_variants()
+            .iter()
+            .copied()
+            .filter(|case| *case != Case::Valid)
+            .collect()
+    } else {
+        args.cases.clone()
+    };
+
+    let link = args.link.open().await?;
+    let (mut recv, mut send) = tokio::io::split(link);
+
+    let interval = Duration::from_secs_f64(1.0 / args.rate);
+    let mut failed_probes = 0;
+    let mut probes = 0;
+    let mut probes_failed_total = 0;
+
+    for i in 0..args.iterations {
+        let case = *cases.choose(&mut rng).unwrap();
+        let wire = case.generate(&mut rng, args.sender, args.receiver);
+
+        log::debug!("#{} {:?}: {:02x?}", i, case, wire);
+        send.write_all(&wire).await?;
+        tokio::time::sleep(interval).await;
+
+        if (i + 1) % args.probe_every == 0 || i + 1 == args.iterations {
+            probes += 1;
+
+            if probe(&args, &mut send, &mut recv).await? {
+                failed_probes = 0;
+            } else {
+                failed_probes += 1;
+                probes_failed_total += 1;
+                log::warn!("device didn't answer probe after case #{} ({:?})", i, case);
+
+                if failed_probes == args.max_failed_probes {
+                    anyhow::bail!("device stopped responding after {} cases (seed {})", i + 1, seed);
+                }
+            }
+        }
+    }
+
+    println!("cases sent:     {}", args.iterations);
+    println!("probes:         {}", probes);
+    println!("failed probes:  {}", probes_failed_total);
+    println!("seed:           {}", seed);
+
+    Ok(())
+}
+
+/// reads until nothing arrives for `DRAIN_QUIET`, so only answers to the probe are seen after it
+async fn drain(args: &Args, recv: &mut ReadHalf<Box<dyn Link>>, rx_buffer: &mut [u8]) -> anyhow::Result<()> {
+    while let Ok(read) = tokio::time::timeout(DRAIN_QUIET, recv.read(rx_buffer)).await {
+        if read? == 0 {
+            anyhow::bail!("{} closed", args.link.target);
+        }
+    }
+
+    Ok(())
+}
+
+/// sends probe frame, and returns whether any valid frame arrived in time
+async fn probe(
+    args: &Args,
+    send: &mut WriteHalf<Box<dyn Link>>,
+    recv: &mut ReadHalf<Box<dyn Link>>,
+) -> anyhow::Result<bool> {
+    let mut rx_buffer = vec![0u8; 1024];
+    drain(args, recv, &mut rx_buffer).await?;
+
+    // unfinished frame from before the probe is dropped with the builder
+    let mut frame_builder = FrameBuilder::new();
+    let frame = Frame {
+        sender: args.sender,
+        receiver: args.receiver,
+        data: args.probe.clone().into_bytes(),
+    };
+    send.write_all(&frame.serialize()?).await?;
+
+    let deadline = Instant::now() + Duration::from_millis(args.probe_timeout);
+
+    loop {
+        let read = match tokio::time::timeout_at(deadline, recv.read(&mut rx_buffer)).await {
+            Ok(read) => read?,
+            Err(_) => return Ok(false),
+        };
+
+        if read == 0 {
+            anyhow::bail!("{} closed", args.link.target);
+        }
+
+        let results = frame_builder.push_buf(&rx_buffer[..read]);
+        if results.iter().any(|result| result.is_ok()) {
+            return Ok(true);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Searches capture files for frames matching given criteria
+
+use std::{io::Write, path::PathBuf, str::FromStr};
+
+use clap::Parser;
+use proto_tools::capture::{self, CaptureWriter, Direction, Format, Record};
+use regex::bytes::Regex;
+
+#[derive(Debug, Parser)]
+#[command(about = "Search capture files for frames")]
+struct Args {
+    /// capture files to search
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+
+    /// format of input files, guessed from extension if not provided
+    #[arg(short, long)]
+    format: Option<Format>,
+
+    /// only frames from this sender, can be passed multiple times
+    #[arg(short, long)]
+    sender: Vec<u8>,
+
+    /// only frames to this receiver, can be passed multiple times
+    #[arg(short, long)]
+    receiver: Vec<u8>,
+
+    #[arg(short, long)]
+    direction: Option<Direction>,
+
+    /// regular expression matched against payload bytes, use `(?-u)` for
+    /// matching non UTF-8 bytes (e.g. `(?-u)\xff`)
+    #[arg(short = 'e', long)]
+    regex: Option<Regex>,
+
+    /// bytes that have to be present in payload (e.g. `01a0ff` or `01 a0 ff`)
+    #[arg(short = 'x', long)]
+    hex: Option<HexPattern>,
+
+    /// select non matching frames
+    #[arg(short = 'v', long)]
+    invert: bool,
+
+    /// only print number of matching frames
+    #[arg(short, long)]
+    count: bool,
+
+    /// print matches in this capture format, instead of human readable lines
+    #[arg(short, long, conflicts_with = "count")]
+    output: Option<Format>,
+}
+
+#[derive(Debug, Clone)]
+struct HexPattern(Vec<u8>);
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("warn"));
+    let args = Args::parse();
+
+    let stdout = std::io::stdout().lock();
+    let mut writer = args.output
+        .map(|format| CaptureWriter::new(stdout, format))
+        .transpose()?;
+
+    let mut total = 0;
+    for path in &args.files {
+        let records = capture::read(path, args.format)?;
+        let mut count = 0;
+
+        for (i, record) in records.iter().enumerate() {
+            if args.matches(record) == args.invert {
+                continue;
+            }
+
+            count += 1;
+            if args.count {
+                continue;
+            }
+
+            if let Some(writer) = writer.as_mut() {
+                writer.write(record)?;
+            } else if args.files.len() > 1 {
+                println!("{}:{}: {}", path.display(), i, describe(record));
+            } else {
+                println!("{}: {}", i, describe(record));
+            }
+        }
+
+        if args.count && args.files.len() > 1 {
+            println!("{}: {}", path.display(), count);
+        }
+
+        total += count;
+    }
+
+    if args.count {
+        println!("{}", total);
+    }
+
+    if let Some(mut writer) = writer {
+        writer.flush()?;
+    }
+
+    // exit code follows grep, 1 if nothing was found
+    if total == 0 {
+        std::io::stdout().flush()?;
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+impl Args {
+    fn matches(&self, record: &Record) -> bool {
+        let frame = &record.frame;
+
+        (self.sender.is_empty() || self.sender.contains(&frame.sender))
+            && (self.receiver.is_empty() || self.receiver.contains(&frame.receiver))
+            && self.direction.is_none_or(|d| d == record.direction)
+            && self.regex.as_ref().is_none_or(|r| r.is_match(&frame.data))
+            && self.hex.as_ref().is_none_or(|HexPattern(pattern)| {
+                pattern.is_empty() || frame.data
+                    .windows(pattern.len())
+                    .any(|w| w == pattern.as_slice())
+            })
+    }
+}
+
+/// `timestamp TX sender -> receiver [len] hex |ascii|`
+fn describe(record: &Record) -> String {
+    let frame = &record.frame;
+    let ascii = frame.data
+        .iter()
+        .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+        .collect::<String>();
+
+    format!(
+        "{}.{:06} {} {:>3} -> {:>3} [{}] {} |{}|",
+        record.timestamp_us / 1_000_000,
+        record.timestamp_us % 1_000_000,
+        record.direction.as_str().to_ascii_uppercase(),
+        frame.sender,
+        frame.receiver,
+        frame.data.len(),
+        hex::encode(&frame.data),
+        ascii,
+    )
+}
+
+impl FromStr for HexPattern {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        proto_tools::bytes::parse_hex(s).map(HexPattern)
+    }
+}
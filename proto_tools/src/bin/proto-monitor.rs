@@ -0,0 +1,344 @@
+//! Terminal UI sniffer, lighter alternative to the GUI for remote and headless use
+//!
+//! Keys: `↑`/`↓`/`PgUp`/`PgDn`/`Home`/`End` select frame, `f` toggles following
+//! newest frame, `c` clears history, `q` quits.
+
+use std::{collections::BTreeMap, io, time::Duration};
+
+use clap::Parser;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use proto::{DeserializeError, Frame, FrameBuilder};
+use proto_tools::{capture, link::LinkArgs};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Row, Table, Wrap},
+};
+use tokio::{io::AsyncReadExt, sync::mpsc};
+
+#[derive(Debug, Parser)]
+#[command(about = "Terminal UI frame monitor")]
+struct Args {
+    #[command(flatten)]
+    link: LinkArgs,
+
+    /// maximum number of frames kept in history
+    #[arg(long, default_value_t = 10_000)]
+    history: usize,
+}
+
+enum Message {
+    Frame(u64, Frame),
+    Discarded(DeserializeError),
+    Closed(String),
+    Key(KeyCode),
+}
+
+#[derive(Debug, Default)]
+struct AddressStats {
+    frames: u64,
+    bytes: u64,
+    last_seen_us: u64,
+}
+
+struct Monitor {
+    target: String,
+    history: usize,
+    frames: Vec<(u64, Frame)>,
+    list: ListState,
+    follow: bool,
+    /// statistics per sender address
+    senders: BTreeMap<u8, AddressStats>,
+    received: u64,
+    discarded: u64,
+    status: Option<String>,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let link = args.link.open().await?;
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    // frames from the device
+    let frames_tx = tx.clone();
+    tokio::spawn(async move {
+        let mut link = link;
+        let mut rx_buffer = vec![0u8; 1024];
+        let mut frame_builder = FrameBuilder::new();
+
+        loop {
+            let read = match link.read(&mut rx_buffer).await {
+                Ok(0) => {
+                    let _ = frames_tx.send(Message::Closed("device closed".into()));
+                    return;
+                },
+                Ok(read) => read,
+                Err(err) => {
+                    let _ = frames_tx.send(Message::Closed(format!("read failed: {}", err)));
+                    return;
+                },
+            };
+
+            for result in frame_builder.push_buf(&rx_buffer[..read]) {
+                let message = match result {
+                    Ok(frame) => Message::Frame(capture::now_us(), frame),
+                    Err(err) => Message::Discarded(err),
+                };
+
+                if frames_tx.send(message).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    // crossterm has no async api without extra features, poll keys on separate thread
+    std::thread::spawn(move || {
+        while let Ok(event) = event::read() {
+            if let Event::Key(key) = event {
+                if key.kind == KeyEventKind::Press && tx.send(Message::Key(key.code)).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+
+    let mut monitor = Monitor::new(args.link.target, args.history);
+    let mut redraw = tokio::time::interval(Duration::from_millis(100));
+
+    let result = async {
+        loop {
+            tokio::select! {
+                message = rx.recv() => {
+                    let Some(message) = message else { break };
+
+                    if !monitor.handle(message) {
+                        break;
+                    }
+                }
+
+                _ = redraw.tick() => {
+                    terminal.draw(|f| monitor.draw(f))?;
+                }
+            }
+        }
+
+        anyhow::Ok(())
+    }.await;
+
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen)?;
+
+    result
+}
+
+impl Monitor {
+    fn new(target: String, history: usize) -> Self {
+        Self {
+            target,
+            history,
+            frames: Vec::new(),
+            list: ListState::default(),
+            follow: true,
+            senders: BTreeMap::new(),
+            received: 0,
+            discarded: 0,
+            status: None,
+        }
+    }
+
+    /// returns false when monitor should exit
+    fn handle(&mut self, message: Message) -> bool {
+        match message {
+            Message::Frame(timestamp_us, frame) => {
+                self.received += 1;
+
+                let stats = self.senders.entry(frame.sender).or_default();
+                stats.frames += 1;
+                stats.bytes += frame.data.len() as u64;
+                stats.last_seen_us = timestamp_us;
+
+                self.frames.push((timestamp_us, frame));
+                if self.frames.len() > self.history {
+                    self.frames.remove(0);
+
+                    if let Some(selected) = self.list.selected() {
+                        self.list.select(Some(selected.saturating_sub(1)));
+                    }
+                }
+
+                if self.follow {
+                    self.list.select(self.frames.len().checked_sub(1));
+                }
+            },
+            Message::Discarded(err) => {
+                self.discarded += 1;
+                self.status = Some(format!("discarded frame: {}", err));
+            },
+            Message::Closed(reason) => self.status = Some(reason),
+            Message::Key(key) => return self.key(key),
+        }
+
+        true
+    }
+
+    fn key(&mut self, key: KeyCode) -> bool {
+        let last = self.frames.len().saturating_sub(1);
+        let selected = self.list.selected().unwrap_or(last);
+
+        let selected = match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('f') => {
+                self.follow = !self.follow;
+                selected
+            },
+            KeyCode::Char('c') => {
+                self.frames.clear();
+                self.senders.clear();
+                0
+            },
+            KeyCode::Up => selected.saturating_sub(1),
+            KeyCode::Down => selected + 1,
+            KeyCode::PageUp => selected.saturating_sub(20),
+            KeyCode::PageDown => selected + 20,
+            KeyCode::Home => 0,
+            KeyCode::End => last,
+            _ => return true,
+        };
+
+        // manual navigation stops following, unless user went back to the newest frame
+        if matches!(key, KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End) {
+            self.follow = selected >= last;
+        }
+
+        self.list.select((!self.frames.is_empty()).then_some(selected.min(last)));
+        true
+    }
+
+    fn draw(&mut self, f: &mut ratatui::Frame) {
+        let [main, status] = split(Direction::Vertical, f.size(), [Constraint::Min(0), Constraint::Length(1)]);
+        let [list, side] = split(Direction::Horizontal, main, [Constraint::Percentage(60), Constraint::Percentage(40)]);
+        let [stats, detail] = split(Direction::Vertical, side, [Constraint::Percentage(40), Constraint::Percentage(60)]);
+
+        let items = self.frames
+            .iter()
+            .map(|(timestamp_us, frame)| ListItem::new(format!(
+                "{} {:>3} -> {:>3} [{:>4}] {}",
+                format_time(*timestamp_us),
+                frame.sender,
+                frame.receiver,
+                frame.data.len(),
+                String::from_utf8_lossy(&frame.data).escape_debug(),
+            )))
+            .collect::<Vec<_>>();
+
+        let title = format!(" {} - {} frames{} ", self.target, self.frames.len(), if self.follow { " (following)" } else { "" });
+        f.render_stateful_widget(
+            List::new(items)
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            list,
+            &mut self.list,
+        );
+
+        let rows = self.senders
+            .iter()
+            .map(|(address, stats)| Row::new([
+                address.to_string(),
+                stats.frames.to_string(),
+                stats.bytes.to_string(),
+                format_time(stats.last_seen_us),
+            ]))
+            .collect::<Vec<_>>();
+
+        f.render_widget(
+            Table::new(rows, [Constraint::Length(7), Constraint::Length(8), Constraint::Length(10), Constraint::Min(15)])
+                .header(Row::new(["sender", "frames", "bytes", "last seen"]).style(Style::default().add_modifier(Modifier::BOLD)))
+                .block(Block::default().borders(Borders::ALL).title(" senders ")),
+            stats,
+        );
+
+        let detail_text = self.list
+            .selected()
+            .and_then(|i| self.frames.get(i))
+            .map(|(timestamp_us, frame)| describe(*timestamp_us, frame))
+            .unwrap_or_default();
+
+        f.render_widget(
+            Paragraph::new(detail_text)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title(" detail ")),
+            detail,
+        );
+
+        f.render_widget(
+            Paragraph::new(format!(
+                "received: {}  discarded: {}  {}",
+                self.received,
+                self.discarded,
+                self.status.as_deref().unwrap_or("q: quit  f: follow  c: clear"),
+            )),
+            status,
+        );
+    }
+}
+
+fn split<const N: usize>(direction: Direction, area: Rect, constraints: [Constraint; N]) -> [Rect; N] {
+    let chunks = Layout::default()
+        .direction(direction)
+        .constraints(constraints)
+        .split(area);
+
+    std::array::from_fn(|i| chunks[i])
+}
+
+fn describe(timestamp_us: u64, frame: &Frame) -> String {
+    let mut out = format!(
+        "time:      {}\nsender:    {}\nreceiver:  {}\nlength:    {}\ncrc32:     {}\n\n",
+        format_time(timestamp_us),
+        frame.sender,
+        frame.receiver,
+        frame.data.len(),
+        frame.calculate_crc32().map(|c| format!("{:08x}", c)).unwrap_or_default(),
+    );
+
+    for (i, chunk) in frame.data.chunks(8).enumerate() {
+        let hex = chunk
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let ascii = chunk
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect::<String>();
+
+        out += &format!("{:04x}  {:<23}  {}\n", i * 8, hex, ascii);
+    }
+
+    out
+}
+
+/// time of day (UTC) with millisecond precision
+fn format_time(timestamp_us: u64) -> String {
+    let ms = timestamp_us / 1000;
+    let secs = ms / 1000;
+
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60,
+        ms % 1000,
+    )
+}
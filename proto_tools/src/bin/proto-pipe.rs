@@ -0,0 +1,116 @@
+//! Filters converting between payload lines and framed bytes over stdin/stdout
+//!
+//! ```sh
+//! echo hello | proto-pipe encode | socat - /dev/ttyUSB0,b115200,raw
+//! socat /dev/ttyUSB0,b115200,raw - | proto-pipe decode --hex
+//! ```
+
+use std::io::{self, BufRead, Read, Write};
+
+use clap::{Parser, Subcommand};
+use proto::{Frame, FrameBuilder};
+
+#[derive(Debug, Parser)]
+#[command(about = "Encode payload lines into frames, or decode frames into payload lines")]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// read payload lines from stdin, write framed bytes to stdout
+    Encode {
+        #[arg(long, default_value_t = 123)]
+        sender: u8,
+
+        #[arg(long, default_value_t = 100)]
+        receiver: u8,
+
+        /// lines are hex bytes (e.g. `01 a0 ff`) instead of text
+        #[arg(short = 'x', long)]
+        hex: bool,
+
+        /// skip empty lines, instead of sending frames with empty payload
+        #[arg(long)]
+        skip_empty: bool,
+    },
+    /// read framed bytes from stdin, write payload lines to stdout
+    Decode {
+        /// print payloads as hex instead of text
+        #[arg(short = 'x', long)]
+        hex: bool,
+
+        /// prefix every line with `sender receiver `
+        #[arg(long)]
+        header: bool,
+    },
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("warn"));
+
+    match Args::parse().command {
+        Command::Encode { sender, receiver, hex, skip_empty } => {
+            let mut stdout = io::stdout().lock();
+
+            for line in io::stdin().lock().lines() {
+                let line = line?;
+                if skip_empty && line.is_empty() {
+                    continue;
+                }
+
+                let data = if hex {
+                    proto_tools::bytes::parse_hex(&line)?
+                } else {
+                    line.into_bytes()
+                };
+
+                let frame = Frame { sender, receiver, data };
+                stdout.write_all(&frame.serialize()?)?;
+                stdout.flush()?;
+            }
+        },
+        Command::Decode { hex, header } => {
+            let mut stdin = io::stdin().lock();
+            let mut stdout = io::stdout().lock();
+
+            let mut rx_buffer = vec![0u8; 1024];
+            let mut frame_builder = FrameBuilder::new();
+
+            loop {
+                let read = match stdin.read(&mut rx_buffer) {
+                    Ok(0) => break,
+                    Ok(read) => read,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(err) => return Err(err.into()),
+                };
+
+                for result in frame_builder.push_buf(&rx_buffer[..read]) {
+                    let frame = match result {
+                        Ok(frame) => frame,
+                        Err(err) => {
+                            log::warn!("discarded frame, reason `{}`", err);
+                            continue;
+                        },
+                    };
+
+                    if header {
+                        write!(stdout, "{} {} ", frame.sender, frame.receiver)?;
+                    }
+
+                    if hex {
+                        stdout.write_all(hex::encode(&frame.data).as_bytes())?;
+                    } else {
+                        stdout.write_all(&frame.data)?;
+                    }
+
+                    stdout.write_all(b"\n")?;
+                    stdout.flush()?;
+                }
+            }
+        },
+    }
+
+    Ok(())
+}
@@ -0,0 +1,282 @@
+//! Line oriented interactive client
+//!
+//! Commands are read from stdin (and optionally from script files), while frames
+//! received from the device are printed as they arrive. Type `help` for list of commands.
+
+use std::{path::{Path, PathBuf}, str::FromStr, sync::{Arc, Mutex}, time::Duration};
+
+use anyhow::Context;
+use clap::Parser;
+use proto::{Frame, FrameBuilder};
+use proto_tools::link::{Link, LinkArgs};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, WriteHalf};
+
+const HELP: &str = "\
+commands:
+  send <receiver> <payload>   send frame, payload is hex (`01 a0 ff`) or quoted text (`\"ping\"`)
+  sender [address]            show or set sender address of sent frames
+  filter [sender=N] [receiver=N]
+                              only print received frames matching all conditions
+  filter off                  print all received frames
+  stats                       show frame and byte counters
+  sleep <ms>                  wait before executing next command
+  source <file>               execute commands from file
+  help                        show this message
+  quit                        exit";
+
+#[derive(Debug, Parser)]
+#[command(about = "Interactive client for protocol devices")]
+struct Args {
+    #[command(flatten)]
+    link: LinkArgs,
+
+    #[arg(long, default_value_t = 123)]
+    sender: u8,
+
+    /// execute commands from these files before reading stdin
+    #[arg(short, long)]
+    script: Vec<PathBuf>,
+}
+
+#[derive(Debug, Default)]
+struct Filter {
+    sender: Option<u8>,
+    receiver: Option<u8>,
+}
+
+#[derive(Debug, Default)]
+struct Stats {
+    frames_sent: u64,
+    frames_received: u64,
+    frames_filtered: u64,
+    frames_discarded: u64,
+    bytes_sent: u64,
+    bytes_received: u64,
+}
+
+/// state shared with the receiving task
+#[derive(Debug, Default)]
+struct Shared {
+    filter: Filter,
+    stats: Stats,
+}
+
+struct Repl {
+    sender: u8,
+    send: WriteHalf<Box<dyn Link>>,
+    shared: Arc<Mutex<Shared>>,
+}
+
+enum Flow {
+    Continue,
+    Quit,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("warn"));
+    let args = Args::parse();
+
+    let link = args.link.open().await?;
+    let (recv, send) = tokio::io::split(link);
+
+    let shared = Arc::new(Mutex::new(Shared::default()));
+    tokio::spawn(receive(recv, shared.clone()));
+
+    let mut repl = Repl {
+        sender: args.sender,
+        send,
+        shared,
+    };
+
+    for script in &args.script {
+        if let Flow::Quit = repl.source(script).await? {
+            return Ok(());
+        }
+    }
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Some(line) = lines.next_line().await? {
+        match repl.execute(&line).await {
+            Ok(Flow::Continue) => (),
+            Ok(Flow::Quit) => break,
+            Err(err) => eprintln!("error: {:#}", err),
+        }
+    }
+
+    Ok(())
+}
+
+async fn receive(mut recv: tokio::io::ReadHalf<Box<dyn Link>>, shared: Arc<Mutex<Shared>>) {
+    let mut rx_buffer = vec![0u8; 1024];
+    let mut frame_builder = FrameBuilder::new();
+
+    loop {
+        let read = match recv.read(&mut rx_buffer).await {
+            Ok(0) => {
+                eprintln!("device closed");
+                std::process::exit(1);
+            },
+            Ok(read) => read,
+            Err(err) => {
+                eprintln!("read failed: {}", err);
+                std::process::exit(1);
+            },
+        };
+
+        let mut shared = shared.lock().unwrap();
+        shared.stats.bytes_received += read as u64;
+
+        for result in frame_builder.push_buf(&rx_buffer[..read]) {
+            match result {
+                Ok(frame) => {
+                    shared.stats.frames_received += 1;
+
+                    if shared.filter.matches(&frame) {
+                        println!("<< {}", describe(&frame));
+                    } else {
+                        shared.stats.frames_filtered += 1;
+                    }
+                },
+                Err(err) => {
+                    shared.stats.frames_discarded += 1;
+                    println!("!! discarded frame: {}", err);
+                },
+            }
+        }
+    }
+}
+
+impl Repl {
+    async fn execute(&mut self, line: &str) -> anyhow::Result<Flow> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(Flow::Continue);
+        }
+
+        let (cmd, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+
+        match cmd {
+            "send" => {
+                let (receiver, payload) = rest
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((rest, ""));
+
+                let frame = Frame {
+                    sender: self.sender,
+                    receiver: receiver.parse().context("invalid receiver address")?,
+                    data: parse_payload(payload.trim())?,
+                };
+
+                let wire = frame.serialize()?;
+                self.send.write_all(&wire).await?;
+
+                let mut shared = self.shared.lock().unwrap();
+                shared.stats.frames_sent += 1;
+                shared.stats.bytes_sent += wire.len() as u64;
+
+                println!(">> {}", describe(&frame));
+            },
+            "sender" => {
+                if !rest.is_empty() {
+                    self.sender = rest.parse().context("invalid sender address")?;
+                }
+
+                println!("sender: {}", self.sender);
+            },
+            "filter" => {
+                let mut shared = self.shared.lock().unwrap();
+
+                if rest == "off" {
+                    shared.filter = Filter::default();
+                } else if !rest.is_empty() {
+                    shared.filter = rest.parse()?;
+                }
+
+                println!("filter: {:?}", shared.filter);
+            },
+            "stats" => {
+                let shared = self.shared.lock().unwrap();
+                let stats = &shared.stats;
+
+                println!("sent:       {} frames, {} bytes", stats.frames_sent, stats.bytes_sent);
+                println!("received:   {} frames, {} bytes", stats.frames_received, stats.bytes_received);
+                println!("filtered:   {}", stats.frames_filtered);
+                println!("discarded:  {}", stats.frames_discarded);
+            },
+            "sleep" => {
+                let ms = rest.parse().context("invalid number of milliseconds")?;
+                tokio::time::sleep(Duration::from_millis(ms)).await;
+            },
+            "source" => return Box::pin(self.source(Path::new(rest))).await,
+            "help" => println!("{}", HELP),
+            "quit" | "exit" => return Ok(Flow::Quit),
+            _ => anyhow::bail!("unknown command `{}`, type `help` for list of commands", cmd),
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    async fn source(&mut self, path: &Path) -> anyhow::Result<Flow> {
+        let script = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("unable to read {}", path.display()))?;
+
+        for (i, line) in script.lines().enumerate() {
+            let flow = self.execute(line)
+                .await
+                .with_context(|| format!("{}:{}", path.display(), i + 1))?;
+
+            if let Flow::Quit = flow {
+                return Ok(Flow::Quit);
+            }
+        }
+
+        Ok(Flow::Continue)
+    }
+}
+
+impl Filter {
+    fn matches(&self, frame: &Frame) -> bool {
+        self.sender.is_none_or(|s| s == frame.sender)
+            && self.receiver.is_none_or(|r| r == frame.receiver)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut filter = Filter::default();
+
+        for condition in s.split_whitespace() {
+            match condition.split_once('=') {
+                Some(("sender", n)) => filter.sender = Some(n.parse().context("invalid sender address")?),
+                Some(("receiver", n)) => filter.receiver = Some(n.parse().context("invalid receiver address")?),
+                _ => anyhow::bail!("invalid filter condition `{}`", condition),
+            }
+        }
+
+        Ok(filter)
+    }
+}
+
+/// quoted payload is text, anything else is hex
+fn parse_payload(payload: &str) -> anyhow::Result<Vec<u8>> {
+    match payload.strip_prefix('"').and_then(|p| p.strip_suffix('"')) {
+        Some(text) => Ok(text.as_bytes().to_vec()),
+        None => proto_tools::bytes::parse_hex(payload),
+    }
+}
+
+fn describe(frame: &Frame) -> String {
+    format!(
+        "{:>3} -> {:>3} [{}] {} {:?}",
+        frame.sender,
+        frame.receiver,
+        frame.data.len(),
+        hex::encode(&frame.data),
+        String::from_utf8_lossy(&frame.data),
+    )
+}
@@ -0,0 +1,115 @@
+//! Replays frames from a capture file to a device
+//!
+//! By default original gaps between frames are preserved (optionally sped up or slowed
+//! down with `--speed`), `--rate` sends frames at fixed rate instead.
+
+use std::{path::PathBuf, time::Duration};
+
+use clap::Parser;
+use proto::FrameBuilder;
+use proto_tools::{capture::{self, Direction, Format}, link::LinkArgs};
+use tokio::{io::{AsyncReadExt, AsyncWriteExt}, time::Instant};
+
+#[derive(Debug, Parser)]
+#[command(about = "Replay capture file to a device")]
+struct Args {
+    #[command(flatten)]
+    link: LinkArgs,
+
+    /// capture file to replay
+    capture: PathBuf,
+
+    /// format of the capture file, guessed from extension if not provided
+    #[arg(short, long)]
+    format: Option<Format>,
+
+    /// which side of the capture to replay
+    #[arg(short, long, default_value = "tx")]
+    direction: Direction,
+
+    /// multiplier applied to original timing (2.0 replays twice as fast)
+    #[arg(short, long, default_value_t = 1.0)]
+    speed: f64,
+
+    /// ignore original timing, and send this many frames per second
+    #[arg(short, long, conflicts_with = "speed")]
+    rate: Option<f64>,
+
+    /// replay capture this many times
+    #[arg(short = 'n', long, default_value_t = 1)]
+    repeat: u32,
+
+    /// seconds to keep listening for responses after last frame was sent
+    #[arg(long, default_value_t = 1.0)]
+    linger: f64,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
+    let args = Args::parse();
+
+    anyhow::ensure!(args.speed > 0.0, "speed has to be positive");
+    anyhow::ensure!(args.rate.is_none_or(|r| r > 0.0), "rate has to be positive");
+
+    let records = capture::read(&args.capture, args.format)?
+        .into_iter()
+        .filter(|r| r.direction == args.direction)
+        .collect::<Vec<_>>();
+
+    anyhow::ensure!(!records.is_empty(), "no {} frames in {}", args.direction.as_str(), args.capture.display());
+
+    let link = args.link.open().await?;
+    let (mut recv, mut send) = tokio::io::split(link);
+
+    // log everything device sends back, while replaying
+    let reader = tokio::spawn(async move {
+        let mut rx_buffer = vec![0u8; 1024];
+        let mut frame_builder = FrameBuilder::new();
+
+        while let Ok(read @ 1..) = recv.read(&mut rx_buffer).await {
+            for result in frame_builder.push_buf(&rx_buffer[..read]) {
+                match result {
+                    Ok(frame) => log::info!(
+                        "received {} -> {}: {}",
+                        frame.sender,
+                        frame.receiver,
+                        hex::encode(&frame.data),
+                    ),
+                    Err(err) => log::info!("discarded frame, reason `{}`", err),
+                }
+            }
+        }
+    });
+
+    let first_ts = records[0].timestamp_us;
+
+    for iteration in 0..args.repeat {
+        let start = Instant::now();
+
+        for (i, record) in records.iter().enumerate() {
+            let offset = match args.rate {
+                Some(rate) => Duration::from_secs_f64(i as f64 / rate),
+                None => Duration::from_micros(record.timestamp_us.saturating_sub(first_ts))
+                    .div_f64(args.speed),
+            };
+
+            tokio::time::sleep_until(start + offset).await;
+            send.write_all(&record.frame.serialize()?).await?;
+
+            log::info!(
+                "[{}/{}] sent {} -> {}: {}",
+                iteration * records.len() as u32 + i as u32 + 1,
+                args.repeat * records.len() as u32,
+                record.frame.sender,
+                record.frame.receiver,
+                hex::encode(&record.frame.data),
+            );
+        }
+    }
+
+    tokio::time::sleep(Duration::from_secs_f64(args.linger)).await;
+    reader.abort();
+
+    Ok(())
+}
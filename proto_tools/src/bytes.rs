@@ -0,0 +1,94 @@
+use anyhow::Context;
+
+/// parses user provided hex bytes, accepting `01a0ff`, `01 A0 FF`, `0x01, 0xa0, 0xff`
+/// and `01:a0:ff` styles
+pub fn parse_hex(s: &str) -> anyhow::Result<Vec<u8>> {
+    let digits = s
+        .split(|c: char| c.is_whitespace() || c == ',' || c == ':')
+        .map(|part| part.trim_start_matches("0x").trim_start_matches("0X"))
+        .collect::<String>();
+
+    hex::decode(&digits).with_context(|| format!("invalid hex `{}`", s))
+}
+
+/// formats bytes as `01 A0 FF`, accepted back by `parse_hex`
+pub fn format_hex(bytes: &[u8]) -> String {
+    bytes.iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// bytes of a hex dump as printed by `xxd`, `hexdump -C`, debuggers or plain lists of bytes,
+/// leading offsets, text columns (after `|` or the first group that isn't hex) and `#` comments are skipped
+pub fn parse_dump(s: &str) -> Vec<u8> {
+    let mut out = Vec::new();
+
+    for line in s.lines() {
+        let line = line
+            .split(['#', '|'])
+            .next()
+            .unwrap_or_default();
+
+        let groups = line
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|group| !group.is_empty())
+            .map(|group| group.trim_start_matches("0x").trim_start_matches("0X"))
+            .collect::<Vec<_>>();
+
+        // offset ends with colon, or is wider than the byte groups after it
+        let skip = match groups.as_slice() {
+            [first, ..] if first.ends_with(':') => 1,
+            [first, second, ..] if first.len() >= 4 && first.len() != second.len() => 1,
+            _ => 0,
+        };
+
+        for group in &groups[skip..] {
+            match hex::decode(group) {
+                Ok(bytes) => out.extend(bytes),
+                Err(_) => break,
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_hex, parse_dump, parse_hex};
+
+    #[test]
+    fn hex_styles() {
+        for s in ["01a0ff", "01 A0 FF", "0x01, 0xa0, 0xff", "01:a0:ff", " 01a0\tff "] {
+            assert_eq!(parse_hex(s).unwrap(), [0x01, 0xa0, 0xff], "{}", s);
+        }
+
+        assert!(parse_hex("01a").is_err());
+        assert!(parse_hex("zz").is_err());
+    }
+
+    #[test]
+    fn format_round_trip() {
+        assert_eq!(format_hex(&[0x01, 0xa0, 0xff]), "01 A0 FF");
+        assert_eq!(format_hex(&[]), "");
+        assert_eq!(parse_hex(&format_hex(b"(x)")).unwrap(), b"(x)");
+    }
+
+    #[test]
+    fn dump_styles() {
+        let dumps = [
+            "00000000: 7e01 0203  ~...\n00000004: 04              .",
+            "00000000  7e 01 02 03  |~...|\n00000004  04           |.|\n",
+            "0000 7e 01 02\n0003 03 04",
+            "0x7e, 0x01, 0x02 # start\n0x03, 0x04",
+            "7e 01 02 03 04",
+        ];
+
+        for dump in dumps {
+            assert_eq!(parse_dump(dump), [0x7e, 0x01, 0x02, 0x03, 0x04], "{}", dump);
+        }
+
+        assert!(parse_dump("no hex here").is_empty());
+    }
+}
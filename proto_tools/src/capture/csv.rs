@@ -0,0 +1,37 @@
+use std::io::{BufRead, Write};
+
+use anyhow::Context;
+
+use super::{FlatRecord, Record};
+
+pub const HEADER: &str = "timestamp_us,direction,sender,receiver,data,crc32,bookmark";
+
+pub fn write_header<W: Write>(out: &mut W) -> anyhow::Result<()> {
+    writeln!(out, "{}", HEADER)?;
+
+    Ok(())
+}
+
+pub fn write<W: Write>(out: &mut W, record: &Record) -> anyhow::Result<()> {
+    // header is written separately, so appending to existing files doesn't repeat it
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(out);
+
+    writer.serialize(FlatRecord::from(record))?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+pub fn read<R: BufRead>(input: R) -> anyhow::Result<Vec<Record>> {
+    csv::Reader::from_reader(input)
+        .deserialize::<FlatRecord>()
+        .enumerate()
+        .map(|(i, row)| {
+            row.map_err(anyhow::Error::from)
+                .and_then(Record::try_from)
+                .with_context(|| format!("invalid record at row {}", i + 1))
+        })
+        .collect()
+}
@@ -0,0 +1,49 @@
+use std::io::{BufRead, Write};
+
+use anyhow::Context;
+use proto::FrameBuilder;
+
+use super::{Direction, Record};
+
+pub fn write<W: Write>(out: &mut W, record: &Record) -> anyhow::Result<()> {
+    writeln!(out, "{}", hex::encode(record.frame.serialize()?))?;
+
+    Ok(())
+}
+
+/// every line is fed through `FrameBuilder`, so frames split across lines are also found,
+/// whitespace between bytes and `#` comments are ignored
+pub fn read<R: BufRead>(input: R) -> anyhow::Result<Vec<Record>> {
+    let mut out = Vec::new();
+    let mut frame_builder = FrameBuilder::new();
+
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        let line = line
+            .split('#')
+            .next()
+            .unwrap_or_default();
+
+        let digits = line
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>();
+
+        let bytes = hex::decode(&digits)
+            .with_context(|| format!("invalid hex at line {}", i + 1))?;
+
+        for result in frame_builder.push_buf(&bytes) {
+            match result {
+                Ok(frame) => out.push(Record {
+                    timestamp_us: 0,
+                    direction: Direction::Rx,
+                    frame,
+                    bookmark: None,
+                }),
+                Err(err) => log::warn!("skipping invalid frame at line {}: {}", i + 1, err),
+            }
+        }
+    }
+
+    Ok(out)
+}
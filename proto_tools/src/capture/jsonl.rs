@@ -0,0 +1,32 @@
+use std::io::{BufRead, Write};
+
+use anyhow::Context;
+
+use super::{FlatRecord, Record};
+
+pub fn write<W: Write>(out: &mut W, record: &Record) -> anyhow::Result<()> {
+    serde_json::to_writer(&mut *out, &FlatRecord::from(record))?;
+    out.write_all(b"\n")?;
+
+    Ok(())
+}
+
+pub fn read<R: BufRead>(input: R) -> anyhow::Result<Vec<Record>> {
+    let mut out = Vec::new();
+
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record = serde_json::from_str::<FlatRecord>(&line)
+            .map_err(anyhow::Error::from)
+            .and_then(Record::try_from)
+            .with_context(|| format!("invalid record at line {}", i + 1))?;
+
+        out.push(record);
+    }
+
+    Ok(out)
+}
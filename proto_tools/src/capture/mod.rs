@@ -0,0 +1,375 @@
+//! Capture files, sequences of timestamped frames saved to disk
+//!
+//! Supported formats:
+//! * `jsonl` - one JSON object per line, payload as hex string
+//! * `csv` - same fields as `jsonl`, with header row
+//! * `pcapng` - frames in wire format as packets with `LINKTYPE_USER0` link type
+//! * `pcap` - same packets in classic libpcap format, without direction and bookmarks
+//! * `hex` - one frame in wire format per line, as hex, without timestamps and direction
+
+use std::{fs::{File, OpenOptions}, io::{BufRead, BufReader, BufWriter, Write}, path::Path, time::{SystemTime, UNIX_EPOCH}};
+
+use anyhow::Context;
+use proto::Frame;
+
+mod csv;
+mod hexdump;
+mod jsonl;
+mod pcap;
+mod pcapng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// frame sent to the device
+    Tx,
+    /// frame received from the device
+    Rx,
+}
+
+/// single captured frame, serialized the same way as in `jsonl` captures
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(into = "FlatRecord", try_from = "FlatRecord")]
+pub struct Record {
+    /// microseconds since unix epoch
+    pub timestamp_us: u64,
+    pub direction: Direction,
+    pub frame: Frame,
+    /// RGB color of bookmark set on the frame
+    pub bookmark: Option<[u8; 3]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    Jsonl,
+    Csv,
+    Pcapng,
+    Pcap,
+    Hex,
+}
+
+/// `Record` with flattened frame and hex encoded payload, shared by text based formats
+#[derive(serde::Serialize, serde::Deserialize)]
+struct FlatRecord {
+    timestamp_us: u64,
+    direction: Direction,
+    sender: u8,
+    receiver: u8,
+    /// payload as hex
+    data: String,
+    /// crc32 of the frame as hex, only informative, ignored when reading
+    #[serde(default)]
+    crc32: String,
+    /// bookmark color as `#rrggbb`, empty if frame isn't bookmarked
+    #[serde(default)]
+    bookmark: String,
+}
+
+/// Writes records one by one, in selected format
+pub struct CaptureWriter<W: Write> {
+    out: W,
+    format: Format,
+}
+
+impl Record {
+    /// creates record timestamped with current time
+    pub fn now(direction: Direction, frame: Frame) -> Self {
+        Self {
+            timestamp_us: now_us(),
+            direction,
+            frame,
+            bookmark: None,
+        }
+    }
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Tx => "tx",
+            Direction::Rx => "rx",
+        }
+    }
+}
+
+impl Format {
+    /// guesses format from file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "jsonl" | "json" => Some(Format::Jsonl),
+            "csv" => Some(Format::Csv),
+            "pcapng" => Some(Format::Pcapng),
+            "pcap" | "cap" => Some(Format::Pcap),
+            "hex" | "txt" => Some(Format::Hex),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Jsonl => "jsonl",
+            Format::Csv => "csv",
+            Format::Pcapng => "pcapng",
+            Format::Pcap => "pcap",
+            Format::Hex => "hex",
+        }
+    }
+}
+
+impl<W: Write> CaptureWriter<W> {
+    /// creates new writer, and writes file header (if format has one)
+    pub fn new(mut out: W, format: Format) -> anyhow::Result<Self> {
+        match format {
+            Format::Csv => csv::write_header(&mut out)?,
+            Format::Pcapng => pcapng::write_header(&mut out)?,
+            Format::Pcap => pcap::write_header(&mut out)?,
+            Format::Jsonl | Format::Hex => (),
+        }
+
+        Ok(Self { out, format })
+    }
+
+    pub fn write(&mut self, record: &Record) -> anyhow::Result<()> {
+        match self.format {
+            Format::Jsonl => jsonl::write(&mut self.out, record),
+            Format::Csv => csv::write(&mut self.out, record),
+            Format::Pcapng => pcapng::write(&mut self.out, record),
+            Format::Pcap => pcap::write(&mut self.out, record),
+            Format::Hex => hexdump::write(&mut self.out, record),
+        }
+    }
+
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        Ok(self.out.flush()?)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl CaptureWriter<BufWriter<File>> {
+    /// creates file at `path` (format is guessed from extension if not provided)
+    pub fn create(path: &Path, format: Option<Format>) -> anyhow::Result<Self> {
+        let format = resolve_format(path, format)?;
+        let file = File::create(path)
+            .with_context(|| format!("unable to create {}", path.display()))?;
+
+        Self::new(BufWriter::new(file), format)
+    }
+
+    /// opens file at `path` for appending, header is written only if file is empty
+    pub fn append(path: &Path, format: Option<Format>) -> anyhow::Result<Self> {
+        let format = resolve_format(path, format)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("unable to open {}", path.display()))?;
+
+        if file.metadata()?.len() == 0 {
+            Self::new(BufWriter::new(file), format)
+        } else {
+            Ok(Self { out: BufWriter::new(file), format })
+        }
+    }
+}
+
+/// reads all records from `input`
+pub fn read_from<R: BufRead>(input: R, format: Format) -> anyhow::Result<Vec<Record>> {
+    match format {
+        Format::Jsonl => jsonl::read(input),
+        Format::Csv => csv::read(input),
+        Format::Pcapng => pcapng::read(input),
+        Format::Pcap => pcap::read(input),
+        Format::Hex => hexdump::read(input),
+    }
+}
+
+/// reads all records from file at `path` (format is guessed from extension if not provided)
+pub fn read(path: &Path, format: Option<Format>) -> anyhow::Result<Vec<Record>> {
+    let format = resolve_format(path, format)?;
+    let file = File::open(path)
+        .with_context(|| format!("unable to open {}", path.display()))?;
+
+    read_from(BufReader::new(file), format)
+        .with_context(|| format!("unable to read {}", path.display()))
+}
+
+/// current time as microseconds since unix epoch
+pub fn now_us() -> u64 {
+    timestamp_us(SystemTime::now())
+}
+
+/// microseconds since unix epoch of `time`, as stored in captures
+pub fn timestamp_us(time: SystemTime) -> u64 {
+    time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or_default()
+}
+
+impl From<Record> for FlatRecord {
+    fn from(record: Record) -> Self {
+        Self::from(&record)
+    }
+}
+
+impl From<&Record> for FlatRecord {
+    fn from(record: &Record) -> Self {
+        Self {
+            timestamp_us: record.timestamp_us,
+            direction: record.direction,
+            sender: record.frame.sender,
+            receiver: record.frame.receiver,
+            data: hex::encode(&record.frame.data),
+            crc32: record.frame
+                .calculate_crc32()
+                .map(|crc| format!("{:08x}", crc))
+                .unwrap_or_default(),
+            bookmark: record.bookmark.map(format_color).unwrap_or_default(),
+        }
+    }
+}
+
+impl TryFrom<FlatRecord> for Record {
+    type Error = anyhow::Error;
+
+    fn try_from(flat: FlatRecord) -> Result<Self, Self::Error> {
+        Ok(Self {
+            timestamp_us: flat.timestamp_us,
+            direction: flat.direction,
+            frame: Frame {
+                sender: flat.sender,
+                receiver: flat.receiver,
+                data: hex::decode(&flat.data).context("invalid payload")?,
+            },
+            bookmark: match flat.bookmark.as_str() {
+                "" => None,
+                color => Some(parse_color(color)?),
+            },
+        })
+    }
+}
+
+/// `#rrggbb`
+fn format_color(rgb: [u8; 3]) -> String {
+    format!("#{}", hex::encode(rgb))
+}
+
+fn parse_color(s: &str) -> anyhow::Result<[u8; 3]> {
+    let rgb = s
+        .strip_prefix('#')
+        .and_then(|digits| hex::decode(digits).ok())
+        .and_then(|bytes| bytes.try_into().ok());
+
+    rgb.with_context(|| format!("invalid color `{}`, expected #rrggbb", s))
+}
+
+fn resolve_format(path: &Path, format: Option<Format>) -> anyhow::Result<Format> {
+    format
+        .or_else(|| Format::from_path(path))
+        .with_context(|| format!("unable to guess capture format of {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use proto::Frame;
+
+    use super::{read_from, CaptureWriter, Direction, Format, Record};
+
+    fn records() -> Vec<Record> {
+        vec![
+            Record {
+                timestamp_us: 1_700_000_000_123_456,
+                direction: Direction::Tx,
+                frame: Frame { sender: 123, receiver: 100, data: b"hell(o w)or\x1bld".to_vec() },
+                bookmark: None,
+            },
+            Record {
+                timestamp_us: 1_700_000_000_223_456,
+                direction: Direction::Rx,
+                frame: Frame { sender: 100, receiver: 123, data: Vec::new() },
+                bookmark: Some([255, 160, 0]),
+            },
+        ]
+    }
+
+    fn round_trip(format: Format) -> Vec<Record> {
+        let mut writer = CaptureWriter::new(Vec::new(), format).unwrap();
+        for record in records() {
+            writer.write(&record).unwrap();
+        }
+
+        read_from(writer.into_inner().as_slice(), format).unwrap()
+    }
+
+    #[test]
+    fn jsonl() {
+        assert_eq!(round_trip(Format::Jsonl), records());
+    }
+
+    #[test]
+    fn csv() {
+        assert_eq!(round_trip(Format::Csv), records());
+    }
+
+    #[test]
+    fn pcapng() {
+        assert_eq!(round_trip(Format::Pcapng), records());
+    }
+
+    #[test]
+    fn pcap() {
+        // classic pcap has no direction nor comments
+        let expected = records()
+            .into_iter()
+            .map(|r| Record { direction: Direction::Rx, bookmark: None, ..r })
+            .collect::<Vec<_>>();
+
+        assert_eq!(round_trip(Format::Pcap), expected);
+    }
+
+    #[test]
+    fn pcap_format_guessed() {
+        assert_eq!(Format::from_path(std::path::Path::new("bus.pcap")), Some(Format::Pcap));
+        assert_eq!(Format::from_path(std::path::Path::new("bus.PCAPNG")), Some(Format::Pcapng));
+    }
+
+    #[test]
+    fn hex() {
+        // hex dumps don't carry timestamps nor direction
+        let frames = round_trip(Format::Hex)
+            .into_iter()
+            .map(|r| r.frame)
+            .collect::<Vec<_>>();
+
+        assert_eq!(frames, records().into_iter().map(|r| r.frame).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn csv_without_crc() {
+        let input = "timestamp_us,direction,sender,receiver,data\n1,rx,1,2,6869\n";
+        let records = read_from(input.as_bytes(), Format::Csv).unwrap();
+
+        assert_eq!(records[0].frame.data, b"hi");
+    }
+
+    #[test]
+    fn append() {
+        let path = std::env::temp_dir().join(format!("proto_tools_append_{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        // header must be written only once
+        for record in records() {
+            let mut writer = CaptureWriter::append(&path, None).unwrap();
+            writer.write(&record).unwrap();
+            writer.flush().unwrap();
+        }
+
+        let read = super::read(&path, None).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read, records());
+    }
+}
@@ -0,0 +1,93 @@
+//! Classic (libpcap) format, for tools that don't read pcapng
+//!
+//! Packets are frames in wire format with the same link type as in pcapng. The format has no place
+//! for direction nor comments, so frames are read back as received and bookmarks are lost.
+
+use std::io::{BufRead, Write};
+
+use anyhow::Context;
+use proto::Frame;
+
+use super::{Direction, Record, pcapng::LINK_TYPE};
+
+/// timestamps in microseconds
+const MAGIC_US: u32 = 0xA1B2C3D4;
+/// timestamps in nanoseconds, only read
+const MAGIC_NS: u32 = 0xA1B23C4D;
+const VERSION_MAJOR: u16 = 2;
+const VERSION_MINOR: u16 = 4;
+/// longest packet, frames are much shorter
+const SNAP_LEN: u32 = 65535;
+
+pub fn write_header<W: Write>(out: &mut W) -> anyhow::Result<()> {
+    out.write_all(&MAGIC_US.to_le_bytes())?;
+    out.write_all(&VERSION_MAJOR.to_le_bytes())?;
+    out.write_all(&VERSION_MINOR.to_le_bytes())?;
+    // time zone offset and accuracy, always zero
+    out.write_all(&[0; 8])?;
+    out.write_all(&SNAP_LEN.to_le_bytes())?;
+    out.write_all(&(LINK_TYPE as u32).to_le_bytes())?;
+
+    Ok(())
+}
+
+pub fn write<W: Write>(out: &mut W, record: &Record) -> anyhow::Result<()> {
+    let wire = record.frame.serialize()?;
+
+    out.write_all(&((record.timestamp_us / 1_000_000) as u32).to_le_bytes())?;
+    out.write_all(&((record.timestamp_us % 1_000_000) as u32).to_le_bytes())?;
+    out.write_all(&(wire.len() as u32).to_le_bytes())?;
+    out.write_all(&(wire.len() as u32).to_le_bytes())?;
+    out.write_all(&wire)?;
+
+    Ok(())
+}
+
+pub fn read<R: BufRead>(mut input: R) -> anyhow::Result<Vec<Record>> {
+    let mut header = [0u8; 24];
+    input.read_exact(&mut header).context("truncated pcap header")?;
+
+    let magic = u32::from_le_bytes(header[..4].try_into().unwrap());
+    let (big_endian, nanos) = match magic {
+        MAGIC_US => (false, false),
+        MAGIC_NS => (false, true),
+        m if m.swap_bytes() == MAGIC_US => (true, false),
+        m if m.swap_bytes() == MAGIC_NS => (true, true),
+        _ => anyhow::bail!("invalid pcap magic number"),
+    };
+
+    let link_type = read_u32(&header[20..], big_endian) & 0xFFFF;
+    anyhow::ensure!(link_type == LINK_TYPE as u32, "unsupported link type {}", link_type);
+
+    let mut out = Vec::new();
+
+    loop {
+        let mut packet_header = [0u8; 16];
+        match input.read_exact(&mut packet_header) {
+            Ok(()) => (),
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(err) => return Err(err.into()),
+        }
+
+        let seconds = read_u32(&packet_header, big_endian) as u64;
+        let fraction = read_u32(&packet_header[4..], big_endian) as u64;
+        let captured = read_u32(&packet_header[8..], big_endian) as usize;
+
+        let mut wire = vec![0; captured];
+        input.read_exact(&mut wire).context("truncated packet data")?;
+
+        let timestamp_us = seconds * 1_000_000 + if nanos { fraction / 1000 } else { fraction };
+
+        match Frame::deserialize(&wire) {
+            Ok(frame) => out.push(Record { timestamp_us, direction: Direction::Rx, frame, bookmark: None }),
+            Err(err) => log::warn!("skipping invalid frame: {}", err),
+        }
+    }
+
+    Ok(out)
+}
+
+fn read_u32(b: &[u8], big_endian: bool) -> u32 {
+    let b = b[..4].try_into().unwrap();
+    if big_endian { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) }
+}
@@ -0,0 +1,218 @@
+//! Minimal pcapng support, only section header, interface description
+//! and enhanced packet blocks are written, other blocks are skipped while reading
+//!
+//! Packets are frames in wire format, timestamps use default (microsecond) resolution.
+//! Wireshark dissector for them is in `proto_tools/wireshark/proto.lua`.
+
+use std::io::{BufRead, Write};
+
+use anyhow::Context;
+use proto::Frame;
+
+use super::{Direction, Record};
+
+/// `LINKTYPE_USER0`, reserved for private use
+pub const LINK_TYPE: u16 = 147;
+
+const SECTION_HEADER_BLOCK: u32 = 0x0A0D0D0A;
+const INTERFACE_DESCRIPTION_BLOCK: u32 = 0x00000001;
+const ENHANCED_PACKET_BLOCK: u32 = 0x00000006;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B3C4D;
+
+const OPT_END_OF_OPT: u16 = 0;
+/// shown by Wireshark as packet comment
+const OPT_COMMENT: u16 = 1;
+const OPT_EPB_FLAGS: u16 = 2;
+
+/// bookmarks are stored as packet comments starting with this, followed by color
+const BOOKMARK_PREFIX: &str = "bookmark ";
+
+const FLAG_INBOUND: u32 = 0b01;
+const FLAG_OUTBOUND: u32 = 0b10;
+
+pub fn write_header<W: Write>(out: &mut W) -> anyhow::Result<()> {
+    let mut shb = Vec::new();
+    shb.extend(BYTE_ORDER_MAGIC.to_le_bytes());
+    shb.extend(1u16.to_le_bytes());
+    shb.extend(0u16.to_le_bytes());
+    // section length not specified
+    shb.extend((-1i64).to_le_bytes());
+    write_block(out, SECTION_HEADER_BLOCK, &shb)?;
+
+    let mut idb = Vec::new();
+    idb.extend(LINK_TYPE.to_le_bytes());
+    idb.extend(0u16.to_le_bytes());
+    // no snap length limit
+    idb.extend(0u32.to_le_bytes());
+    write_block(out, INTERFACE_DESCRIPTION_BLOCK, &idb)?;
+
+    Ok(())
+}
+
+pub fn write<W: Write>(out: &mut W, record: &Record) -> anyhow::Result<()> {
+    let wire = record.frame.serialize()?;
+
+    let mut epb = Vec::new();
+    epb.extend(0u32.to_le_bytes());
+    epb.extend(((record.timestamp_us >> 32) as u32).to_le_bytes());
+    epb.extend((record.timestamp_us as u32).to_le_bytes());
+    epb.extend((wire.len() as u32).to_le_bytes());
+    epb.extend((wire.len() as u32).to_le_bytes());
+    epb.extend(&wire);
+    epb.resize(padded(epb.len()), 0);
+
+    let flags = match record.direction {
+        Direction::Tx => FLAG_OUTBOUND,
+        Direction::Rx => FLAG_INBOUND,
+    };
+
+    epb.extend(OPT_EPB_FLAGS.to_le_bytes());
+    epb.extend(4u16.to_le_bytes());
+    epb.extend(flags.to_le_bytes());
+
+    if let Some(rgb) = record.bookmark {
+        let comment = format!("{}{}", BOOKMARK_PREFIX, super::format_color(rgb));
+        epb.extend(OPT_COMMENT.to_le_bytes());
+        epb.extend((comment.len() as u16).to_le_bytes());
+        epb.extend(comment.as_bytes());
+        epb.resize(padded(epb.len()), 0);
+    }
+
+    epb.extend(OPT_END_OF_OPT.to_le_bytes());
+    epb.extend(0u16.to_le_bytes());
+
+    write_block(out, ENHANCED_PACKET_BLOCK, &epb)
+}
+
+pub fn read<R: BufRead>(mut input: R) -> anyhow::Result<Vec<Record>> {
+    let mut out = Vec::new();
+    let mut big_endian = false;
+
+    loop {
+        let mut header = [0u8; 8];
+        match input.read_exact(&mut header) {
+            Ok(()) => (),
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(err) => return Err(err.into()),
+        }
+
+        let block_type = u32::from_le_bytes(header[..4].try_into().unwrap());
+        if block_type == SECTION_HEADER_BLOCK {
+            // byte order of section is only known after reading its magic
+            let mut magic = [0u8; 4];
+            input.read_exact(&mut magic)?;
+
+            big_endian = match u32::from_le_bytes(magic) {
+                BYTE_ORDER_MAGIC => false,
+                m if m.swap_bytes() == BYTE_ORDER_MAGIC => true,
+                _ => anyhow::bail!("invalid pcapng byte order magic"),
+            };
+
+            let len = read_u32(&header[4..], big_endian) as usize;
+            let mut rest = vec![0; len.checked_sub(12).context("invalid block length")?];
+            input.read_exact(&mut rest)?;
+            continue;
+        }
+
+        let len = read_u32(&header[4..], big_endian) as usize;
+        let mut body = vec![0; len.checked_sub(8).context("invalid block length")?];
+        input.read_exact(&mut body)?;
+
+        // body includes trailing copy of the block length
+        let body = &body[..body.len().saturating_sub(4)];
+        let block_type = read_u32(&header, big_endian);
+
+        if block_type == INTERFACE_DESCRIPTION_BLOCK {
+            let link_type = read_u16(body.get(..2).context("truncated block")?, big_endian);
+            anyhow::ensure!(link_type == LINK_TYPE, "unsupported link type {}", link_type);
+        } else if block_type == ENHANCED_PACKET_BLOCK {
+            if let Some(record) = read_packet(body, big_endian)? {
+                out.push(record);
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+fn read_packet(body: &[u8], big_endian: bool) -> anyhow::Result<Option<Record>> {
+    anyhow::ensure!(body.len() >= 20, "truncated enhanced packet block");
+
+    let ts_high = read_u32(&body[4..], big_endian) as u64;
+    let ts_low = read_u32(&body[8..], big_endian) as u64;
+    let captured = read_u32(&body[12..], big_endian) as usize;
+
+    let wire = body
+        .get(20..20 + captured)
+        .context("truncated packet data")?;
+
+    let mut direction = Direction::Rx;
+    let mut bookmark = None;
+    let mut options = &body[padded(20 + captured).min(body.len())..];
+
+    while options.len() >= 4 {
+        let code = read_u16(options, big_endian);
+        let len = read_u16(&options[2..], big_endian) as usize;
+
+        if code == OPT_END_OF_OPT {
+            break;
+        }
+
+        if code == OPT_EPB_FLAGS
+            && len == 4
+            && options.len() >= 8
+            && read_u32(&options[4..], big_endian) & 0b11 == FLAG_OUTBOUND
+        {
+            direction = Direction::Tx;
+        }
+
+        if code == OPT_COMMENT {
+            bookmark = options
+                .get(4..4 + len)
+                .and_then(|comment| std::str::from_utf8(comment).ok())
+                .and_then(|comment| comment.strip_prefix(BOOKMARK_PREFIX))
+                .and_then(|color| super::parse_color(color).ok())
+                .or(bookmark);
+        }
+
+        options = &options[(4 + padded(len)).min(options.len())..];
+    }
+
+    match Frame::deserialize(wire) {
+        Ok(frame) => Ok(Some(Record {
+            timestamp_us: (ts_high << 32) | ts_low,
+            direction,
+            frame,
+            bookmark,
+        })),
+        Err(err) => {
+            log::warn!("skipping invalid frame: {}", err);
+            Ok(None)
+        },
+    }
+}
+
+fn write_block<W: Write>(out: &mut W, block_type: u32, body: &[u8]) -> anyhow::Result<()> {
+    let len = (body.len() + 12) as u32;
+
+    out.write_all(&block_type.to_le_bytes())?;
+    out.write_all(&len.to_le_bytes())?;
+    out.write_all(body)?;
+    out.write_all(&len.to_le_bytes())?;
+
+    Ok(())
+}
+
+fn padded(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+fn read_u16(b: &[u8], big_endian: bool) -> u16 {
+    let b = b[..2].try_into().unwrap();
+    if big_endian { u16::from_be_bytes(b) } else { u16::from_le_bytes(b) }
+}
+
+fn read_u32(b: &[u8], big_endian: bool) -> u32 {
+    let b = b[..4].try_into().unwrap();
+    if big_endian { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) }
+}
@@ -0,0 +1,97 @@
+//! Announcing proto-bridge instances on the LAN over mDNS (DNS-SD), so clients find them without entering host:port
+
+use std::{collections::BTreeMap, net::SocketAddr};
+
+use anyhow::Context;
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+
+/// DNS-SD service type of proto-bridge
+pub const SERVICE_TYPE: &str = "_proto-bridge._tcp.local.";
+
+/// Bridge announced on the network
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bridge {
+    /// instance name, the serial port unless given on bridge command line
+    pub name: String,
+    pub addr: SocketAddr,
+    /// serial port exposed by the bridge
+    pub device: Option<String>,
+    /// receiver addresses clients see frames of, all if `None`
+    pub receivers: Option<String>,
+}
+
+impl Bridge {
+    /// name to open the bridge with, see `serial_com::transport::Target::new`
+    pub fn target_name(&self) -> String {
+        format!("tcp://{}", self.addr)
+    }
+}
+
+/// host name of this machine in `.local.` domain, required by the announcement
+fn local_host_name() -> String {
+    let host = std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .unwrap_or_else(|_| "proto-bridge".into());
+
+    format!("{}.local.", host.trim_end_matches(".local"))
+}
+
+/// announces bridge listening on `port`, until returned daemon is shut down or the process exits
+pub fn announce(name: &str, port: u16, device: &str, receivers: Option<&str>) -> anyhow::Result<ServiceDaemon> {
+    let daemon = ServiceDaemon::new().context("unable to start mDNS daemon")?;
+
+    let mut properties = vec![("device", device)];
+    if let Some(receivers) = receivers {
+        properties.push(("receivers", receivers));
+    }
+
+    let info = ServiceInfo::new(SERVICE_TYPE, name, &local_host_name(), "", port, &properties[..])?
+        .enable_addr_auto();
+    daemon.register(info).context("unable to announce bridge over mDNS")?;
+
+    Ok(daemon)
+}
+
+/// looks for bridges in background thread, `on_change` gets all known bridges whenever one appears or disappears,
+/// looking stops once it returns false
+pub fn browse(mut on_change: impl FnMut(Vec<Bridge>) -> bool + Send + 'static) -> anyhow::Result<()> {
+    let daemon = ServiceDaemon::new().context("unable to start mDNS daemon")?;
+    let events = daemon.browse(SERVICE_TYPE)?;
+
+    std::thread::spawn(move || {
+        // by full name of the instance
+        let mut bridges = BTreeMap::new();
+
+        while let Ok(event) = events.recv() {
+            match event {
+                ServiceEvent::ServiceResolved(info) => {
+                    let Some(ip) = info.get_addresses().iter().next() else {
+                        continue;
+                    };
+
+                    let name = info.get_fullname().trim_end_matches(SERVICE_TYPE).trim_end_matches('.').to_owned();
+                    bridges.insert(info.get_fullname().to_owned(), Bridge {
+                        name,
+                        addr: SocketAddr::new(*ip, info.get_port()),
+                        device: info.get_property_val_str("device").map(str::to_owned),
+                        receivers: info.get_property_val_str("receivers").map(str::to_owned),
+                    });
+                },
+                ServiceEvent::ServiceRemoved(_, fullname) => {
+                    if bridges.remove(&fullname).is_none() {
+                        continue;
+                    }
+                },
+                _ => continue,
+            }
+
+            if !on_change(bridges.values().cloned().collect()) {
+                break;
+            }
+        }
+
+        let _ = daemon.shutdown();
+    });
+
+    Ok(())
+}
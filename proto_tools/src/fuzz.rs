@@ -0,0 +1,147 @@
+//! Generating valid and deliberately malformed frames, for robustness testing of devices
+
+use clap::ValueEnum;
+use proto::{encoding::{Encoding, ESCAPE_BYTE, ESCAPE_TABLE, FLOW_CONTROL_ESCAPE_TABLE}, Frame, FrameBuilder};
+use rand::Rng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Case {
+    /// valid frame with random payload
+    Valid,
+    /// valid frame with flipped bit in its CRC
+    BadCrc,
+    /// escape byte followed by byte outside of escape table
+    BadEscape,
+    /// DATA_LEN field not matching actual payload size
+    BadLength,
+    /// payload longer than the receiver's frame buffer
+    Oversize,
+    /// frame cut short, missing its end byte
+    Truncated,
+    /// random bytes followed by a valid frame
+    Noise,
+}
+
+impl Case {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Case::Valid => "valid",
+            Case::BadCrc => "bad CRC",
+            Case::BadEscape => "bad escape",
+            Case::BadLength => "bad length",
+            Case::Oversize => "oversize",
+            Case::Truncated => "truncated",
+            Case::Noise => "noise",
+        }
+    }
+
+    /// wire bytes of the case
+    pub fn generate(self, rng: &mut impl Rng, sender: u8, receiver: u8) -> Vec<u8> {
+        let data = random_payload(rng, 0..64);
+
+        match self {
+            Case::Valid => Frame { sender, receiver, data }.serialize().unwrap(),
+            Case::BadCrc => flip_crc_bit(&Frame { sender, receiver, data }, rng.gen_range(0..32)).unwrap(),
+            Case::BadEscape => {
+                let mut wire = Frame { sender, receiver, data }.serialize().unwrap();
+                let invalid = loop {
+                    let b = rng.gen::<u8>();
+                    if !ESCAPE_TABLE.iter().chain(FLOW_CONTROL_ESCAPE_TABLE).any(|(_, escaped)| escaped[1] == b) {
+                        break b;
+                    }
+                };
+
+                // somewhere after the begin byte, but before the end byte
+                let pos = rng.gen_range(1..wire.len() - 1);
+                wire.splice(pos..pos, [ESCAPE_BYTE, invalid]);
+                wire
+            },
+            Case::BadLength => {
+                let frame = Frame { sender, receiver, data };
+                let len = loop {
+                    let len = rng.gen::<u16>();
+                    if len as usize != frame.data.len() {
+                        break len;
+                    }
+                };
+
+                wire(sender, receiver, len, &frame.data, frame.calculate_crc32().unwrap())
+            },
+            Case::Oversize => {
+                let data = random_payload(rng, FrameBuilder::FRAME_MAX_LEN..4 * FrameBuilder::FRAME_MAX_LEN);
+                Frame { sender, receiver, data }.serialize().unwrap()
+            },
+            Case::Truncated => {
+                let wire = Frame { sender, receiver, data }.serialize().unwrap();
+                let len = rng.gen_range(1..wire.len());
+
+                wire[..len].to_vec()
+            },
+            Case::Noise => {
+                let mut wire = random_payload(rng, 1..128);
+                wire.extend(Frame { sender, receiver, data }.serialize().unwrap());
+                wire
+            },
+        }
+    }
+}
+
+/// wire bytes of `frame` with `bit` (0-31) of its CRC flipped
+pub fn flip_crc_bit(frame: &Frame, bit: u32) -> Result<Vec<u8>, proto::SerializeError> {
+    let crc = frame.calculate_crc32()? ^ (1 << bit);
+
+    Ok(wire(frame.sender, frame.receiver, frame.data.len() as u16, &frame.data, crc))
+}
+
+pub fn random_payload(rng: &mut impl Rng, len: std::ops::Range<usize>) -> Vec<u8> {
+    let len = rng.gen_range(len);
+    (0..len).map(|_| rng.gen()).collect()
+}
+
+/// assembles wire format by hand, so individual fields can be broken
+/// (keep in sync with Frame::serialize)
+fn wire(sender: u8, receiver: u8, len: u16, data: &[u8], crc: u32) -> Vec<u8> {
+    let mut out = vec![Frame::BEGIN_FRAME_BYTE];
+
+    out.encode(&[sender, receiver]).unwrap();
+    out.encode(&len.to_be_bytes()).unwrap();
+    out.encode(data).unwrap();
+    out.encode(&crc.to_be_bytes()).unwrap();
+    out.push(Frame::END_FRAME_BYTE);
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use proto::Frame;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{flip_crc_bit, Case};
+
+    #[test]
+    fn valid_and_malformed() {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        for _ in 0..100 {
+            let valid = Case::Valid.generate(&mut rng, 1, 2);
+            assert_eq!(valid.first(), Some(&Frame::BEGIN_FRAME_BYTE));
+            assert_eq!(valid.last(), Some(&Frame::END_FRAME_BYTE));
+
+            for case in [Case::BadCrc, Case::BadEscape, Case::BadLength, Case::Truncated] {
+                assert!(Frame::deserialize(&case.generate(&mut rng, 1, 2)).is_err(), "{:?}", case);
+            }
+        }
+    }
+
+    #[test]
+    fn flipped_crc() {
+        let frame = Frame { sender: 1, receiver: 2, data: b"ping".to_vec() };
+
+        for bit in [0, 7, 31] {
+            let wire = flip_crc_bit(&frame, bit).unwrap();
+            assert_eq!(wire.len(), frame.serialize().unwrap().len());
+            assert!(Frame::deserialize(&wire).is_err(), "{}", bit);
+        }
+    }
+}
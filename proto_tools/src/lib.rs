@@ -0,0 +1,9 @@
+//! Shared pieces of the protocol command line tools
+
+pub mod bytes;
+pub mod capture;
+#[cfg(feature = "mdns")]
+pub mod discovery;
+pub mod fuzz;
+pub mod link;
+pub mod schema;
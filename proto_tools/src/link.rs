@@ -0,0 +1,45 @@
+use anyhow::Context;
+use tokio::{io::{AsyncRead, AsyncWrite}, net::TcpStream};
+use tokio_serial::SerialStream;
+
+/// Byte stream a device can be reached through
+pub trait Link: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T> Link for T
+where
+    T: AsyncRead + AsyncWrite + Unpin + Send,
+{}
+
+/// command line arguments selecting a device, flatten into tool's own arguments
+#[derive(Debug, Clone, clap::Args)]
+pub struct LinkArgs {
+    /// serial port (e.g. /dev/ttyUSB0 or COM7), or `tcp://host:port` of a proto-bridge
+    pub target: String,
+
+    #[arg(short, long, default_value_t = 115200)]
+    pub baud_rate: u32,
+}
+
+impl LinkArgs {
+    pub async fn open(&self) -> anyhow::Result<Box<dyn Link>> {
+        open(&self.target, self.baud_rate).await
+    }
+}
+
+/// opens `target`, which is either path of a serial port or `tcp://host:port` address,
+/// `baud_rate` is ignored for TCP targets
+pub async fn open(target: &str, baud_rate: u32) -> anyhow::Result<Box<dyn Link>> {
+    if let Some(addr) = target.strip_prefix("tcp://") {
+        let stream = TcpStream::connect(addr)
+            .await
+            .with_context(|| format!("unable to connect to {}", addr))?;
+        stream.set_nodelay(true)?;
+
+        Ok(Box::new(stream))
+    } else {
+        let stream = SerialStream::open(&tokio_serial::new(target, baud_rate))
+            .with_context(|| format!("unable to open {}", target))?;
+
+        Ok(Box::new(stream))
+    }
+}
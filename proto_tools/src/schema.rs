@@ -0,0 +1,351 @@
+//! User-defined layouts of binary payloads, loaded from TOML or JSON
+//!
+//! ```toml
+//! [[message]]
+//! name = "telemetry"
+//! # payloads starting with these bytes are decoded as this message, `prefix` is text
+//! prefix_hex = "10"
+//!
+//! [[message.field]]
+//! name = "temperature"
+//! offset = 1
+//! type = "i16"
+//! endian = "big"
+//! scale = 0.1
+//! unit = "°C"
+//!
+//! # known commands, suggested while typing in command input
+//! [[command]]
+//! name = "reset"
+//! payload_hex = "01"
+//! description = "restarts the controller"
+//! ```
+
+use std::{fs, path::Path};
+
+use anyhow::Context;
+use proto::Frame;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    F32,
+    F64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Endian {
+    #[default]
+    Little,
+    Big,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Field {
+    pub name: String,
+    /// position in payload, in bytes, counted from its start (prefix included)
+    pub offset: usize,
+    #[serde(rename = "type")]
+    pub ty: FieldType,
+    #[serde(default)]
+    pub endian: Endian,
+    /// raw value is multiplied by it
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    #[serde(default)]
+    pub unit: String,
+}
+
+/// Layout of payloads starting with the same bytes, e.g. one command of the device
+#[derive(Debug, Clone, Deserialize)]
+pub struct Message {
+    pub name: String,
+    /// text payload starts with
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// hex bytes payload starts with, alternative to `prefix`
+    #[serde(default)]
+    pub prefix_hex: Option<String>,
+    /// match only frames from this sender
+    #[serde(default)]
+    pub sender: Option<u8>,
+    #[serde(default, rename = "field")]
+    pub fields: Vec<Field>,
+    /// `prefix` or `prefix_hex` as bytes, filled when schema is loaded
+    #[serde(skip)]
+    prefix_bytes: Vec<u8>,
+}
+
+/// Named payload the device understands, offered as completion of command input
+#[derive(Debug, Clone, Deserialize)]
+pub struct Command {
+    pub name: String,
+    /// text payload
+    #[serde(default)]
+    pub payload: Option<String>,
+    /// hex bytes payload, alternative to `payload`
+    #[serde(default)]
+    pub payload_hex: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    /// address command is sent to, `None` to keep the one entered
+    #[serde(default)]
+    pub receiver: Option<u8>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Schema {
+    /// checked in order, the first matching one is used
+    #[serde(default, rename = "message")]
+    pub messages: Vec<Message>,
+    #[serde(default, rename = "command")]
+    pub commands: Vec<Command>,
+}
+
+/// Payload decoded by its message layout
+#[derive(Debug, Clone)]
+pub struct Decoded<'a> {
+    pub message: &'a Message,
+    /// scaled values, `None` if payload is too short for the field
+    pub values: Vec<(&'a Field, Option<f64>)>,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+impl FieldType {
+    /// size in bytes
+    pub fn width(&self) -> usize {
+        match self {
+            FieldType::U8 | FieldType::I8 => 1,
+            FieldType::U16 | FieldType::I16 => 2,
+            FieldType::U32 | FieldType::I32 | FieldType::F32 => 4,
+            FieldType::F64 => 8,
+        }
+    }
+}
+
+impl Field {
+    /// scaled value of the field in `data`
+    pub fn decode(&self, data: &[u8]) -> Option<f64> {
+        let bytes = data.get(self.offset..self.offset.checked_add(self.ty.width())?)?;
+
+        macro_rules! decode {
+            ($ty:ty) => {{
+                let bytes = bytes.try_into().unwrap();
+                match self.endian {
+                    Endian::Little => <$ty>::from_le_bytes(bytes) as f64,
+                    Endian::Big => <$ty>::from_be_bytes(bytes) as f64,
+                }
+            }};
+        }
+
+        let raw = match self.ty {
+            FieldType::U8 => decode!(u8),
+            FieldType::I8 => decode!(i8),
+            FieldType::U16 => decode!(u16),
+            FieldType::I16 => decode!(i16),
+            FieldType::U32 => decode!(u32),
+            FieldType::I32 => decode!(i32),
+            FieldType::F32 => decode!(f32),
+            FieldType::F64 => decode!(f64),
+        };
+
+        Some(raw * self.scale)
+    }
+
+    /// e.g. `23.5 °C`, `-` if payload is too short
+    pub fn format(&self, value: Option<f64>) -> String {
+        match (value, self.unit.is_empty()) {
+            (None, _) => "-".into(),
+            (Some(value), true) => format!("{}", value),
+            (Some(value), false) => format!("{} {}", value, self.unit),
+        }
+    }
+}
+
+impl Message {
+    pub fn matches(&self, frame: &Frame) -> bool {
+        frame.data.starts_with(&self.prefix_bytes)
+            && self.sender.is_none_or(|sender| sender == frame.sender)
+    }
+}
+
+impl Schema {
+    /// loads schema from `.toml` or `.json` file
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("unable to read schema {}", path.display()))?;
+
+        let schema = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => serde_json::from_str(&text).map_err(anyhow::Error::from),
+            _ => toml::from_str(&text).map_err(anyhow::Error::from),
+        };
+
+        schema
+            .and_then(Self::prepare)
+            .with_context(|| format!("invalid schema {}", path.display()))
+    }
+
+    pub fn from_toml(text: &str) -> anyhow::Result<Self> {
+        Self::prepare(toml::from_str(text)?)
+    }
+
+    /// converts prefixes to bytes, and checks payloads of commands
+    fn prepare(mut self) -> anyhow::Result<Self> {
+        for message in &mut self.messages {
+            message.prefix_bytes = match (&message.prefix, &message.prefix_hex) {
+                (Some(_), Some(_)) => anyhow::bail!("message `{}` has both prefix and prefix_hex", message.name),
+                (Some(text), None) => text.clone().into_bytes(),
+                (None, Some(hex)) => crate::bytes::parse_hex(hex)
+                    .with_context(|| format!("invalid prefix of message `{}`", message.name))?,
+                (None, None) => Vec::new(),
+            };
+        }
+
+        for command in &self.commands {
+            match (&command.payload, &command.payload_hex) {
+                (Some(_), None) => (),
+                (None, Some(hex)) => {
+                    crate::bytes::parse_hex(hex)
+                        .with_context(|| format!("invalid payload of command `{}`", command.name))?;
+                },
+                _ => anyhow::bail!("command `{}` needs either payload or payload_hex", command.name),
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// commands with names starting with `input`, ignoring case, all of them if `input` is empty
+    pub fn complete(&self, input: &str) -> Vec<&Command> {
+        let input = input.trim().to_lowercase();
+
+        self.commands
+            .iter()
+            .filter(|command| command.name.to_lowercase().starts_with(&input))
+            .collect()
+    }
+
+    /// fields of the first message matching `frame`
+    pub fn decode(&self, frame: &Frame) -> Option<Decoded<'_>> {
+        let message = self.messages.iter().find(|message| message.matches(frame))?;
+
+        Some(Decoded {
+            message,
+            values: message.fields
+                .iter()
+                .map(|field| (field, field.decode(&frame.data)))
+                .collect(),
+        })
+    }
+}
+
+impl Decoded<'_> {
+    /// single line, e.g. `telemetry temperature=23.5 °C`
+    pub fn summary(&self) -> String {
+        self.values
+            .iter()
+            .fold(self.message.name.clone(), |mut summary, (field, value)| {
+                summary.push_str(&format!(" {}={}", field.name, field.format(*value)));
+                summary
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proto::Frame;
+
+    use super::Schema;
+
+    const SCHEMA: &str = r#"
+        [[message]]
+        name = "telemetry"
+        prefix_hex = "10"
+
+        [[message.field]]
+        name = "temperature"
+        offset = 1
+        type = "i16"
+        endian = "big"
+        scale = 0.5
+        unit = "C"
+
+        [[message.field]]
+        name = "counter"
+        offset = 3
+        type = "u32"
+
+        [[message]]
+        name = "status"
+        prefix = "STATUS"
+
+        [[command]]
+        name = "reset"
+        payload_hex = "01"
+        description = "restarts the controller"
+
+        [[command]]
+        name = "Read status"
+        payload = "STATUS?"
+        receiver = 100
+    "#;
+
+    fn frame(data: &[u8]) -> Frame {
+        Frame { sender: 1, receiver: 2, data: data.to_vec() }
+    }
+
+    #[test]
+    fn decode_fields() {
+        let schema = Schema::from_toml(SCHEMA).unwrap();
+
+        let decoded = schema.decode(&frame(&[0x10, 0xff, 0xfe, 5, 0, 0, 0])).unwrap();
+        assert_eq!(decoded.message.name, "telemetry");
+        assert_eq!(decoded.values[0].1, Some(-1.0));
+        assert_eq!(decoded.values[1].1, Some(5.0));
+        assert_eq!(decoded.summary(), "telemetry temperature=-1 C counter=5");
+
+        // too short for the counter
+        let decoded = schema.decode(&frame(&[0x10, 0, 4])).unwrap();
+        assert_eq!(decoded.values[1].1, None);
+
+        assert_eq!(schema.decode(&frame(b"STATUS 1")).unwrap().message.name, "status");
+        assert!(schema.decode(&frame(b"ON")).is_none());
+    }
+
+    #[test]
+    fn complete_commands() {
+        let schema = Schema::from_toml(SCHEMA).unwrap();
+
+        let names = |input| schema.complete(input).iter().map(|command| command.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names(""), ["reset", "Read status"]);
+        assert_eq!(names("re"), ["reset", "Read status"]);
+        assert_eq!(names("READ"), ["Read status"]);
+        assert!(names("x").is_empty());
+
+        assert_eq!(schema.complete("read")[0].receiver, Some(100));
+    }
+
+    #[test]
+    fn invalid_command() {
+        assert!(Schema::from_toml("[[command]]\nname = \"x\"").is_err());
+        assert!(Schema::from_toml("[[command]]\nname = \"x\"\npayload_hex = \"zz\"").is_err());
+        assert!(Schema::from_toml("[[command]]\nname = \"x\"\npayload = \"a\"\npayload_hex = \"61\"").is_err());
+    }
+
+    #[test]
+    fn invalid_prefix() {
+        assert!(Schema::from_toml("[[message]]\nname = \"x\"\nprefix_hex = \"zz\"").is_err());
+        assert!(Schema::from_toml("[[message]]\nname = \"x\"\nprefix = \"a\"\nprefix_hex = \"61\"").is_err());
+    }
+}
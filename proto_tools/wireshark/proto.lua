@@ -0,0 +1,115 @@
+-- Wireshark dissector for frames captured by terminal / proto_tools (pcapng, LINKTYPE_USER0)
+--
+-- Install by copying to the Wireshark personal plugins directory
+-- (Help -> About Wireshark -> Folders -> Personal Lua Plugins), or run
+--   wireshark -X lua_script:proto.lua capture.pcapng
+--
+-- Wire format: ( SENDER RECEIVER DATA_LEN DATA CRC32 )
+-- everything between parentheses is escaped with 0x1B, see proto/src/encoding.rs
+
+local p = Proto("stm32proto", "STM32 serial protocol")
+
+local BEGIN = 0x28
+local END = 0x29
+local ESCAPE = 0x1B
+local UNESCAPE = { [0x41] = ESCAPE, [0x42] = BEGIN, [0x43] = END }
+
+local f_sender = ProtoField.uint8("stm32proto.sender", "Sender", base.DEC_HEX)
+local f_receiver = ProtoField.uint8("stm32proto.receiver", "Receiver", base.DEC_HEX)
+local f_length = ProtoField.uint16("stm32proto.length", "Data length", base.DEC)
+local f_data = ProtoField.bytes("stm32proto.data", "Data")
+local f_text = ProtoField.string("stm32proto.text", "Data (text)")
+local f_crc = ProtoField.uint32("stm32proto.crc32", "CRC32", base.HEX)
+local f_escapes = ProtoField.uint16("stm32proto.escapes", "Escaped bytes", base.DEC)
+
+p.fields = { f_sender, f_receiver, f_length, f_data, f_text, f_crc, f_escapes }
+
+local e_malformed = ProtoExpert.new("stm32proto.malformed", "Malformed frame", expert.group.MALFORMED, expert.severity.ERROR)
+p.experts = { e_malformed }
+
+-- removes escaping of bytes between frame delimiters, returns decoded bytes and number of escape sequences
+local function unescape(tvb)
+    local out = ByteArray.new()
+    local escapes = 0
+    local i = 1
+
+    while i < tvb:len() - 1 do
+        local byte = tvb(i, 1):uint()
+
+        if byte == ESCAPE then
+            if i + 1 >= tvb:len() - 1 then
+                return nil, escapes
+            end
+
+            local decoded = UNESCAPE[tvb(i + 1, 1):uint()]
+            if decoded == nil then
+                return nil, escapes
+            end
+
+            out:append(ByteArray.new(string.format("%02x", decoded)))
+            escapes = escapes + 1
+            i = i + 2
+        else
+            out:append(tvb(i, 1):bytes())
+            i = i + 1
+        end
+    end
+
+    return out, escapes
+end
+
+function p.dissector(tvb, pinfo, tree)
+    pinfo.cols.protocol = "STM32"
+
+    local subtree = tree:add(p, tvb(), "STM32 serial protocol frame")
+
+    if tvb:len() < 2 or tvb(0, 1):uint() ~= BEGIN or tvb(tvb:len() - 1, 1):uint() ~= END then
+        subtree:add_proto_expert_info(e_malformed, "missing frame delimiters")
+        return
+    end
+
+    local decoded, escapes = unescape(tvb)
+    if decoded == nil then
+        subtree:add_proto_expert_info(e_malformed, "invalid escape sequence")
+        return
+    end
+
+    local frame = decoded:tvb("Unescaped frame")
+    -- sender, receiver, length and crc
+    if frame:len() < 8 then
+        subtree:add_proto_expert_info(e_malformed, "frame too short")
+        return
+    end
+
+    local length = frame(2, 2):uint()
+    if frame:len() ~= 8 + length then
+        subtree:add_proto_expert_info(e_malformed, "data length doesn't match frame size")
+        return
+    end
+
+    local sender = frame(0, 1):uint()
+    local receiver = frame(1, 1):uint()
+
+    subtree:add(f_sender, frame(0, 1))
+    subtree:add(f_receiver, frame(1, 1))
+    subtree:add(f_length, frame(2, 2))
+
+    if length > 0 then
+        subtree:add(f_data, frame(4, length))
+        subtree:add(f_text, frame(4, length))
+    end
+
+    subtree:add(f_crc, frame(4 + length, 4))
+    subtree:add(f_escapes, escapes):set_generated()
+
+    pinfo.cols.src = tostring(sender)
+    pinfo.cols.dst = tostring(receiver)
+
+    local info = string.format("%d -> %d, %d bytes", sender, receiver, length)
+    if length > 0 then
+        info = info .. ": " .. frame(4, length):string()
+    end
+    pinfo.cols.info = info
+end
+
+DissectorTable.get("wtap_encap"):add(wtap.USER0, p)
@@ -0,0 +1,196 @@
+//! Nordic UART Service over Bluetooth LE, for boards bridging the protocol through a BLE module instead of a cable
+//!
+//! HM-10 modules and their clones use a single characteristic both ways instead, they are supported too.
+
+use std::{io, pin::Pin, time::Duration};
+
+use btleplug::{
+    api::{Central, CharPropFlags, Characteristic, Manager as _, Peripheral as _, ScanFilter, ValueNotification, WriteType},
+    platform::{Adapter, Manager, Peripheral},
+};
+use futures_util::{Stream, StreamExt};
+use uuid::{Uuid, uuid};
+
+/// service of Nordic UART
+pub const NUS_SERVICE: Uuid = uuid!("6e400001-b5a3-f393-e0a9-e50e24dcca9e");
+/// written by us, received by the board
+const NUS_RX: Uuid = uuid!("6e400002-b5a3-f393-e0a9-e50e24dcca9e");
+/// notified by the board
+const NUS_TX: Uuid = uuid!("6e400003-b5a3-f393-e0a9-e50e24dcca9e");
+pub const HM10_SERVICE: Uuid = uuid!("0000ffe0-0000-1000-8000-00805f9b34fb");
+/// written and notified
+const HM10_DATA: Uuid = uuid!("0000ffe1-0000-1000-8000-00805f9b34fb");
+/// longest write, fits into the default ATT MTU
+const CHUNK_LEN: usize = 20;
+/// how long opening a device looks for it
+const FIND_TIMEOUT: Duration = Duration::from_secs(10);
+/// how often found devices are checked while looking for one
+const FIND_POLL: Duration = Duration::from_millis(250);
+
+/// Board found by `scan`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BleDevice {
+    /// advertised name, if any
+    pub name: Option<String>,
+    /// MAC address, or identifier given by the OS on platforms hiding addresses
+    pub id: String,
+}
+
+impl BleDevice {
+    /// name to open the device with, see `Target::new`
+    pub fn target_name(&self) -> String {
+        format!("ble://{}", self.name.as_deref().unwrap_or(&self.id))
+    }
+}
+
+/// Connection to a board, closed when dropped
+pub struct BlePort {
+    peripheral: Peripheral,
+    /// characteristic data is written to
+    write: Characteristic,
+    /// characteristic data is notified on
+    notify: Uuid,
+    notifications: Pin<Box<dyn Stream<Item = ValueNotification> + Send>>,
+    /// notified bytes that didn't fit into the last read
+    pending: Vec<u8>,
+}
+
+fn to_io(err: btleplug::Error) -> io::Error {
+    io::Error::other(err)
+}
+
+/// first Bluetooth adapter of the system
+async fn adapter() -> io::Result<Adapter> {
+    let manager = Manager::new().await.map_err(to_io)?;
+
+    manager.adapters()
+        .await
+        .map_err(to_io)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no Bluetooth adapter"))
+}
+
+/// boards advertising Nordic UART or HM-10 service, found within `duration`
+pub async fn scan(duration: Duration) -> io::Result<Vec<BleDevice>> {
+    let adapter = adapter().await?;
+    adapter.start_scan(ScanFilter { services: vec![NUS_SERVICE, HM10_SERVICE] }).await.map_err(to_io)?;
+    tokio::time::sleep(duration).await;
+    let _ = adapter.stop_scan().await;
+
+    let mut found = Vec::new();
+    for peripheral in adapter.peripherals().await.map_err(to_io)? {
+        let Some(properties) = peripheral.properties().await.map_err(to_io)? else {
+            continue;
+        };
+
+        // filter isn't supported by every platform
+        if properties.services.iter().any(|service| [NUS_SERVICE, HM10_SERVICE].contains(service)) {
+            found.push(BleDevice { name: properties.local_name, id: peripheral.id().to_string() });
+        }
+    }
+
+    Ok(found)
+}
+
+/// waits until board with name, address or id `device` is seen by the scan
+async fn find(adapter: &Adapter, device: &str) -> io::Result<Peripheral> {
+    loop {
+        for peripheral in adapter.peripherals().await.map_err(to_io)? {
+            let Some(properties) = peripheral.properties().await.map_err(to_io)? else {
+                continue;
+            };
+
+            let matches = properties.local_name.as_deref() == Some(device)
+                || properties.address.to_string().eq_ignore_ascii_case(device)
+                || peripheral.id().to_string() == device;
+
+            if matches {
+                return Ok(peripheral);
+            }
+        }
+
+        tokio::time::sleep(FIND_POLL).await;
+    }
+}
+
+impl BlePort {
+    /// connects to board with name, address or id `device`
+    pub async fn open(device: &str) -> io::Result<Self> {
+        let adapter = adapter().await?;
+        adapter.start_scan(ScanFilter::default()).await.map_err(to_io)?;
+
+        let found = tokio::time::timeout(FIND_TIMEOUT, find(&adapter, device)).await;
+        let _ = adapter.stop_scan().await;
+        let peripheral = found
+            .map_err(|_| io::Error::new(io::ErrorKind::NotFound, format!("no Bluetooth device `{}` found", device)))??;
+
+        peripheral.connect().await.map_err(to_io)?;
+        peripheral.discover_services().await.map_err(to_io)?;
+
+        let characteristics = peripheral.characteristics();
+        let find_characteristic = |uuid| characteristics.iter().find(|c| c.uuid == uuid).cloned();
+
+        let (write, notify) = match (find_characteristic(NUS_RX), find_characteristic(NUS_TX), find_characteristic(HM10_DATA)) {
+            (Some(rx), Some(tx), _) => (rx, tx),
+            (_, _, Some(data)) => (data.clone(), data),
+            _ => {
+                let _ = peripheral.disconnect().await;
+                return Err(io::Error::new(io::ErrorKind::Unsupported, "device has no Nordic UART service"));
+            },
+        };
+
+        peripheral.subscribe(&notify).await.map_err(to_io)?;
+        let notifications = peripheral.notifications().await.map_err(to_io)?;
+
+        Ok(Self {
+            peripheral,
+            write,
+            notify: notify.uuid,
+            notifications,
+            pending: Vec::new(),
+        })
+    }
+
+    /// reads notified bytes, 0 once connection is lost
+    pub async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pending.is_empty() {
+            match self.notifications.next().await {
+                Some(notification) if notification.uuid == self.notify => self.pending = notification.value,
+                Some(_) => (),
+                None => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.pending.len());
+        buf[..len].copy_from_slice(&self.pending[..len]);
+        self.pending.drain(..len);
+
+        Ok(len)
+    }
+
+    pub async fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        // acknowledged writes would be limited to a few per connection interval
+        let write_type = if self.write.properties.contains(CharPropFlags::WRITE_WITHOUT_RESPONSE) {
+            WriteType::WithoutResponse
+        } else {
+            WriteType::WithResponse
+        };
+
+        for chunk in data.chunks(CHUNK_LEN) {
+            self.peripheral.write(&self.write, chunk, write_type).await.map_err(to_io)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for BlePort {
+    fn drop(&mut self) {
+        // board keeps connection otherwise, and isn't advertised to be opened again
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let peripheral = self.peripheral.clone();
+            runtime.spawn(async move { peripheral.disconnect().await });
+        }
+    }
+}
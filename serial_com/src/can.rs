@@ -0,0 +1,242 @@
+//! Tunneling of byte messages over CAN bus, for boards exposing CAN instead of UART
+//!
+//! Every write is split into CAN frames carrying a header byte and up to 7 data bytes. The header marks
+//! the first and the last fragment, and numbers fragments, so a lost one drops the whole message instead
+//! of corrupting it. Each side sends with its own CAN id, so several boards can share the bus.
+//!
+//! The socket itself is Linux only (SocketCAN) and needs `can` feature.
+
+use std::{io, mem};
+
+/// header bit of the first fragment of a message
+const FIRST: u8 = 0x80;
+/// header bit of the last fragment of a message
+const LAST: u8 = 0x40;
+/// sequence number of fragment within its message, wrapping around
+const SEQ_MASK: u8 = 0x3F;
+/// data bytes of a fragment, after the header
+const FRAGMENT_LEN: usize = 7;
+/// id boards listen on, if not given in target
+const DEFAULT_TX_ID: u16 = 0x100;
+/// id boards answer with, if not given in target
+const DEFAULT_RX_ID: u16 = 0x101;
+/// highest standard (11 bit) id
+const MAX_ID: u16 = 0x7FF;
+
+/// Interface and ids parsed from target name, e.g. `can0?tx=0x100&rx=0x101`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanTarget {
+    pub interface: String,
+    /// id of sent frames
+    pub tx_id: u16,
+    /// id of frames sent by the board, others are ignored
+    pub rx_id: u16,
+}
+
+impl CanTarget {
+    pub fn parse(target: &str) -> io::Result<Self> {
+        let invalid = |what: &str| io::Error::new(io::ErrorKind::InvalidInput, format!("invalid CAN target `{}`, {}", target, what));
+
+        let (interface, query) = target.split_once('?').unwrap_or((target, ""));
+        if interface.is_empty() {
+            return Err(invalid("missing interface"));
+        }
+
+        let mut parsed = Self { interface: interface.to_owned(), tx_id: DEFAULT_TX_ID, rx_id: DEFAULT_RX_ID };
+
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').ok_or_else(|| invalid("expected `key=value`"))?;
+            let id = match value.strip_prefix("0x") {
+                Some(hex) => u16::from_str_radix(hex, 16),
+                None => value.parse(),
+            };
+            let id = id.ok().filter(|&id| id <= MAX_ID).ok_or_else(|| invalid("ids are 11 bit"))?;
+
+            match key {
+                "tx" => parsed.tx_id = id,
+                "rx" => parsed.rx_id = id,
+                _ => return Err(invalid("expected `tx` or `rx`")),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// splits `data` into CAN frame payloads, empty data is sent as a single empty fragment
+pub fn fragment(data: &[u8]) -> Vec<Vec<u8>> {
+    let chunks = data.chunks(FRAGMENT_LEN).collect::<Vec<_>>();
+    let chunks = if chunks.is_empty() { vec![&data[..0]] } else { chunks };
+    let count = chunks.len();
+
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut header = i as u8 & SEQ_MASK;
+            if i == 0 {
+                header |= FIRST;
+            }
+            if i == count - 1 {
+                header |= LAST;
+            }
+
+            let mut payload = Vec::with_capacity(chunk.len() + 1);
+            payload.push(header);
+            payload.extend_from_slice(chunk);
+            payload
+        })
+        .collect()
+}
+
+/// Joins fragments received from one board back into messages
+#[derive(Debug, Default)]
+pub struct Reassembler {
+    buffer: Vec<u8>,
+    /// sequence number of the next fragment, `None` while no message is started
+    next_seq: Option<u8>,
+    /// messages dropped because one of their fragments was lost
+    pub dropped: u64,
+}
+
+impl Reassembler {
+    /// takes payload of a received CAN frame, returns message once its last fragment arrives
+    pub fn push(&mut self, payload: &[u8]) -> Option<Vec<u8>> {
+        let (&header, data) = payload.split_first()?;
+        let seq = header & SEQ_MASK;
+
+        if header & FIRST != 0 {
+            if self.next_seq.is_some() {
+                self.dropped += 1;
+            }
+
+            self.buffer.clear();
+        } else if self.next_seq != Some(seq) {
+            // fragment lost, or arrived in the middle of a message
+            if self.next_seq.take().is_some() {
+                self.dropped += 1;
+            }
+
+            self.buffer.clear();
+            return None;
+        }
+
+        self.buffer.extend_from_slice(data);
+
+        if header & LAST != 0 {
+            self.next_seq = None;
+            Some(mem::take(&mut self.buffer))
+        } else {
+            self.next_seq = Some((seq + 1) & SEQ_MASK);
+            None
+        }
+    }
+}
+
+#[cfg(all(feature = "can", target_os = "linux"))]
+pub use socket::CanPort;
+
+#[cfg(all(feature = "can", target_os = "linux"))]
+mod socket {
+    use std::io;
+
+    use socketcan::{CanFrame, EmbeddedFrame, Id, StandardId, tokio::CanSocket};
+
+    use super::{CanTarget, Reassembler, fragment};
+
+    /// Opened CAN interface, carrying messages of one board
+    pub struct CanPort {
+        socket: CanSocket,
+        target: CanTarget,
+        reassembler: Reassembler,
+        /// reassembled bytes that didn't fit into the last read
+        pending: Vec<u8>,
+    }
+
+    impl CanPort {
+        pub fn open(target: CanTarget) -> io::Result<Self> {
+            let socket = CanSocket::open(&target.interface)?;
+
+            Ok(Self { socket, target, reassembler: Reassembler::default(), pending: Vec::new() })
+        }
+
+        pub async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            while self.pending.is_empty() {
+                let frame = self.socket.read_frame().await?;
+
+                // frames of other nodes on the bus
+                if frame.id() != Id::Standard(StandardId::new(self.target.rx_id).unwrap()) {
+                    continue;
+                }
+
+                let dropped = self.reassembler.dropped;
+                if let Some(message) = self.reassembler.push(frame.data()) {
+                    self.pending = message;
+                }
+
+                if self.reassembler.dropped != dropped {
+                    log::warn!("CAN fragment lost on {}, message dropped", self.target.interface);
+                }
+            }
+
+            let len = buf.len().min(self.pending.len());
+            buf[..len].copy_from_slice(&self.pending[..len]);
+            self.pending.drain(..len);
+
+            Ok(len)
+        }
+
+        pub async fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+            let id = StandardId::new(self.target.tx_id).unwrap();
+
+            for payload in fragment(data) {
+                let frame = CanFrame::new(id, &payload)
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "fragment doesn't fit CAN frame"))?;
+                self.socket.write_frame(frame).await?;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CanTarget, Reassembler, fragment};
+
+    #[test]
+    fn fragments_reassembled() {
+        for len in [0, 1, 7, 8, 14, 15, 1000] {
+            let data = (0..len).map(|i| i as u8).collect::<Vec<_>>();
+            let fragments = fragment(&data);
+            assert!(fragments.iter().all(|payload| payload.len() <= 8));
+
+            let mut reassembler = Reassembler::default();
+            let messages = fragments.iter().filter_map(|payload| reassembler.push(payload)).collect::<Vec<_>>();
+            assert_eq!(messages, vec![data]);
+        }
+    }
+
+    #[test]
+    fn lost_fragment_drops_message() {
+        let data = (0..30).collect::<Vec<u8>>();
+        let mut fragments = fragment(&data);
+        fragments.remove(2);
+
+        let mut reassembler = Reassembler::default();
+        assert!(fragments.iter().all(|payload| reassembler.push(payload).is_none()));
+        assert_eq!(reassembler.dropped, 1);
+
+        // following message is received whole
+        let messages = fragment(b"next").iter().filter_map(|payload| reassembler.push(payload)).collect::<Vec<_>>();
+        assert_eq!(messages, vec![b"next".to_vec()]);
+    }
+
+    #[test]
+    fn target_parsed() {
+        assert_eq!(CanTarget::parse("can0").unwrap(), CanTarget { interface: "can0".into(), tx_id: 0x100, rx_id: 0x101 });
+        assert_eq!(CanTarget::parse("vcan1?tx=0x7FF&rx=12").unwrap(), CanTarget { interface: "vcan1".into(), tx_id: 0x7FF, rx_id: 12 });
+        assert!(CanTarget::parse("can0?tx=0x800").is_err());
+        assert!(CanTarget::parse("?rx=1").is_err());
+    }
+}
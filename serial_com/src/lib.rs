@@ -0,0 +1,1310 @@
+
+
+//! Device handling of the terminal, usable without its UI
+//!
+//! `SerialHandler` owns every opened device (serial port, TCP or UDP socket, Bluetooth LE, CAN bus, loopback), each one is served
+//! by its own task, which writes requested data and splits everything read into frames.
+//! Devices are driven by `Cmd`s sent to the handler, what happens on them is reported to a `Listener`.
+//! Failed commands are answered with `SerialComError`, so callers can tell e.g. a disconnected device
+//! from a cancelled write.
+//!
+//! ```ignore
+//! let (cmd_tx, cmd_rx) = tokio::sync::mpsc::channel(1);
+//! tokio::spawn(async move { SerialHandler::new(listener, cmd_rx).run().await });
+//!
+//! let target = Target::new(transport::LOOPBACK, tokio_serial::new(transport::LOOPBACK, 115200));
+//! let device = target.open().await?;
+//! let (result_tx, result) = oneshot::channel();
+//! cmd_tx.send(Cmd::RegisterDevice { device, target, queue: Default::default(), traffic: Default::default(), read_only: false, result: result_tx }).await?;
+//! let handle = result.await?;
+//!
+//! let (result_tx, result) = oneshot::channel();
+//! cmd_tx.send(Cmd::SendData { handle, data: frame.serialize()?, framing: Framing::Frame, result: result_tx }).await?;
+//! result.await??;
+//! ```
+
+use std::{borrow::Cow, sync::{Arc, atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering}}, collections::HashMap, future::Future, io, time::{Duration, SystemTime}};
+
+use proto::{DecoderStats, DeserializeError, Frame, FrameBuilder};
+use tokio::sync::mpsc::{self, Receiver, Sender, UnboundedReceiver, UnboundedSender, error::TrySendError};
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+
+use transport::{Port, Target};
+
+#[cfg(feature = "ble")]
+pub mod ble;
+pub mod can;
+pub mod transport;
+
+static HANDLE_COUNTER: AtomicU64 = AtomicU64::new(0);
+/// longest wait between reopen attempts, however many failed
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+/// requests waiting in the worker of a device, further ones are handled by `QueuePolicy`
+pub const QUEUE_CAPACITY: usize = 64;
+/// requests waiting for room in the worker of a device under `QueuePolicy::Wait`, further ones fail
+/// with `SerialComError::QueueFull`
+pub const PARKED_CAPACITY: usize = 1024;
+/// shortest time between two `Listener::received` calls of a device, reads in between are batched,
+/// so fast devices don't wake the application up for every chunk
+pub const FLUSH_INTERVAL: Duration = Duration::from_millis(33);
+
+/// request for device worker, and channel for the result
+type WorkerRequest = (Request, oneshot::Sender<Result<(), SerialComError>>);
+
+enum Request {
+    /// data, what it is, and `TxQueue` generation it was queued in
+    Write(Vec<u8>, Framing, u64),
+    Control(LineControl),
+    Pacing(Pacing),
+    /// limit of received frame length, in wire bytes
+    MaxFrameLen(usize),
+    Timeouts(Timeouts),
+    Reconnect(ReconnectPolicy),
+    Rs485(Option<Rs485>),
+}
+
+/// control of port lines, other than data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineControl {
+    Dtr(bool),
+    Rts(bool),
+    /// holds TX line in break condition for given time
+    Break(Duration),
+}
+
+/// What data written by `Cmd::SendData` is, only frames are escaped on ports using XON/XOFF
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framing {
+    /// serialized frame, or the first part of one written in several writes
+    Frame,
+    /// rest of a frame started by an earlier write
+    Continuation,
+    /// bytes written as they are, e.g. AT commands
+    Raw,
+}
+
+/// Modem line enabling transmitter of RS-485 transceiver
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DirectionLine {
+    #[default]
+    Rts,
+    /// used by some adapters instead of RTS
+    Dtr,
+}
+
+/// Direction control of half-duplex RS-485 transceiver, its transmitter is enabled only while writing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rs485 {
+    pub line: DirectionLine,
+    /// line is deasserted while transmitting, for inverting transceivers
+    pub inverted: bool,
+    /// wait after transmitter is enabled, before the first byte
+    pub delay_before: Duration,
+    /// turnaround, wait after the last byte left, before transmitter is disabled
+    pub delay_after: Duration,
+}
+
+impl Rs485 {
+    /// control of the line, enabling (`transmit`) or disabling the transmitter
+    fn control(&self, transmit: bool) -> LineControl {
+        let level = transmit != self.inverted;
+
+        match self.line {
+            DirectionLine::Rts => LineControl::Rts(level),
+            DirectionLine::Dtr => LineControl::Dtr(level),
+        }
+    }
+}
+
+/// Limits of how fast data is written to the device, so its RX buffer isn't overrun
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Pacing {
+    /// minimum time between two writes
+    pub min_gap: Duration,
+    /// maximum number of writes per second, 0 for unlimited
+    pub max_rate: u32,
+    /// minimum time between end of a received frame and the next write, for half-duplex peers
+    /// that can't receive right after they transmitted
+    pub turnaround: Duration,
+}
+
+/// Limits of how long the device may not respond, so a wedged adapter doesn't hang its senders
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Timeouts {
+    /// longest time one write can take, it fails with `SerialComError::WriteTimeout` then
+    pub write: Option<Duration>,
+    /// longest time without received data, device is reopened then
+    pub read: Option<Duration>,
+}
+
+/// How a device is reopened after it disconnects, or its read or write fails
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReconnectPolicy {
+    /// device stays closed after failure if false
+    pub enabled: bool,
+    /// wait before the first attempt
+    pub initial_delay: Duration,
+    /// every following wait is this many times longer, up to a minute
+    pub backoff: f64,
+    /// device stays closed after this many failed attempts, 0 for unlimited
+    pub max_attempts: u32,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            initial_delay: Duration::from_secs(1),
+            backoff: 1.5,
+            max_attempts: 0,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// wait before the next attempt after `attempts` failed ones, `None` if there are no more attempts
+    pub fn delay(&self, attempts: u32) -> Option<Duration> {
+        if !self.enabled || (self.max_attempts != 0 && attempts >= self.max_attempts) {
+            return None;
+        }
+
+        let secs = self.initial_delay.as_secs_f64() * self.backoff.max(1.0).powi(attempts as i32);
+        Some(Duration::from_secs_f64(secs.min(MAX_RECONNECT_DELAY.as_secs_f64())))
+    }
+}
+
+/// Whether device is usable, reported to `Listener` when it changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connected,
+    /// device will be reopened in `delay`, in its `attempt`th attempt since it disconnected
+    Reconnecting {
+        attempt: u32,
+        delay: Duration,
+    },
+    /// `ReconnectPolicy` gave up, device stays closed
+    Failed,
+}
+
+impl Pacing {
+    /// minimum time between starts of two writes satisfying both limits
+    fn interval(&self) -> Duration {
+        match self.max_rate {
+            0 => self.min_gap,
+            rate => self.min_gap.max(Duration::from_secs(1) / rate),
+        }
+    }
+}
+
+/// What happens to a write when queue of the device is full, e.g. because its port is stuck
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueuePolicy {
+    /// sender waits until there is room, so it is slowed down to the rate of the device, up to
+    /// `PARKED_CAPACITY` requests wait in order
+    #[default]
+    Wait,
+    /// write fails with `SerialComError::QueueFull` right away
+    Reject,
+}
+
+/// Writes waiting in the worker of a device, shared with its window
+#[derive(Debug, Default)]
+pub struct TxQueue {
+    pending: AtomicUsize,
+    bytes: AtomicUsize,
+    /// bumped on cancel, writes queued before that are dropped
+    generation: AtomicU64,
+    /// `QueuePolicy::Reject` is used
+    reject: AtomicBool,
+}
+
+/// Bytes and frames that went through a device since it was registered, shared with its window
+///
+/// Counted by the worker, so they don't depend on what the application keeps of its history.
+#[derive(Debug, Default)]
+pub struct Traffic {
+    bytes_sent: AtomicU64,
+    frames_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    frames_received: AtomicU64,
+}
+
+/// `Traffic` at one moment
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TrafficCounts {
+    pub bytes_sent: u64,
+    /// writes starting a frame, raw writes and rests of split frames only count as bytes
+    pub frames_sent: u64,
+    pub bytes_received: u64,
+    /// frames received valid
+    pub frames_received: u64,
+}
+
+/// Why a device couldn't be opened or a command to it failed
+#[derive(Debug, thiserror::Error)]
+pub enum SerialComError {
+    #[error("unable to open {target}")]
+    PortOpen {
+        target: String,
+        #[source]
+        source: io::Error,
+    },
+    /// device is waiting to be reopened
+    #[error("device is disconnected")]
+    Disconnected,
+    /// device was closed, or never registered
+    #[error("invalid device handle")]
+    InvalidHandle,
+    /// task of the device stopped, e.g. it panicked
+    #[error("device worker stopped")]
+    WorkerStopped,
+    /// write dropped by `TxQueue::cancel`
+    #[error("send cancelled")]
+    Cancelled,
+    /// write dropped, because `TxQueue` of the device is full and `QueuePolicy::Reject` is used,
+    /// or `PARKED_CAPACITY` requests already wait for room
+    #[error("send queue of the device is full")]
+    QueueFull,
+    /// device was registered as `read_only`
+    #[error("device is open for monitoring only, nothing is sent to it")]
+    ReadOnly,
+    #[error("unable to write to device")]
+    Write(#[source] io::Error),
+    #[error("write didn't finish in {0:?}")]
+    WriteTimeout(Duration),
+    /// device was reopened, in case its adapter got stuck
+    #[error("nothing received for {0:?}, reopening device")]
+    ReadTimeout(Duration),
+    #[error("line control is supported only by serial ports")]
+    ControlUnsupported,
+    #[error("unable to control port lines")]
+    Control(#[from] tokio_serial::Error),
+    /// unfinished frame dropped for exceeding maximum frame length
+    #[error("longer than {max_len} bytes, dropped")]
+    DecoderOverflow {
+        max_len: usize,
+    },
+    #[error(transparent)]
+    Decode(#[from] DeserializeError),
+}
+
+impl TxQueue {
+    /// number of writes not yet done
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::Relaxed)
+    }
+
+    /// total size of writes not yet done
+    pub fn bytes(&self) -> usize {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// there are more writes than the worker holds, further ones wait or are rejected
+    pub fn is_full(&self) -> bool {
+        self.pending() >= QUEUE_CAPACITY
+    }
+
+    pub fn policy(&self) -> QueuePolicy {
+        match self.reject.load(Ordering::Relaxed) {
+            true => QueuePolicy::Reject,
+            false => QueuePolicy::Wait,
+        }
+    }
+
+    /// takes effect for writes made from now on
+    pub fn set_policy(&self, policy: QueuePolicy) {
+        self.reject.store(policy == QueuePolicy::Reject, Ordering::Relaxed);
+    }
+
+    /// drops every write queued so far, they fail with `SerialComError::Cancelled`
+    pub fn cancel(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn push(&self, len: usize) -> u64 {
+        self.pending.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(len, Ordering::Relaxed);
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    /// removes write from counters, returns false if it was cancelled meanwhile
+    fn pop(&self, len: usize, generation: u64) -> bool {
+        self.pending.fetch_sub(1, Ordering::Relaxed);
+        self.bytes.fetch_sub(len, Ordering::Relaxed);
+        generation == self.generation.load(Ordering::Relaxed)
+    }
+}
+
+impl Traffic {
+    pub fn counts(&self) -> TrafficCounts {
+        TrafficCounts {
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            frames_sent: self.frames_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            frames_received: self.frames_received.load(Ordering::Relaxed),
+        }
+    }
+
+    /// counts `wire` bytes, as they were written after escaping
+    fn sent(&self, wire: &[u8], framing: Framing) {
+        self.bytes_sent.fetch_add(wire.len() as u64, Ordering::Relaxed);
+
+        if framing == Framing::Frame {
+            self.frames_sent.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn received(&self, bytes: usize, frames: usize) {
+        self.bytes_received.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.frames_received.fetch_add(frames as u64, Ordering::Relaxed);
+    }
+}
+
+/// enforces `Pacing` of a device, across reconnects
+#[derive(Debug, Default)]
+struct Pacer {
+    pacing: Pacing,
+    last_write: Option<tokio::time::Instant>,
+    /// time the last frame was received
+    last_frame_end: Option<tokio::time::Instant>,
+}
+
+impl Pacer {
+    /// waits until next write is allowed
+    async fn wait(&mut self) {
+        if let Some(last_write) = self.last_write {
+            tokio::time::sleep_until(last_write + self.pacing.interval()).await;
+        }
+
+        if let Some(last_frame_end) = self.last_frame_end {
+            tokio::time::sleep_until(last_frame_end + self.pacing.turnaround).await;
+        }
+
+        self.last_write = Some(tokio::time::Instant::now());
+    }
+}
+
+/// state of device worker, kept across reconnects
+#[derive(Debug, Default)]
+struct WorkerState {
+    pacer: Pacer,
+    frame_builder: FrameBuilder,
+    timeouts: Timeouts,
+    reconnect: ReconnectPolicy,
+    rs485: Option<Rs485>,
+    traffic: Arc<Traffic>,
+}
+
+/// Bytes read from a device since the last `Listener::received` call, and frames they completed,
+/// see `FLUSH_INTERVAL`
+#[derive(Debug)]
+pub struct Received {
+    /// bytes as they were read, including ones outside of frames, with time of the read
+    pub data: Vec<(SystemTime, Vec<u8>)>,
+    /// frames completed by `data`, with time of the read completing them and their wire bytes,
+    /// including ones that failed to deserialize, unfinished frames dropped for their length
+    /// come before frames of the same read as `SerialComError::DecoderOverflow`
+    pub frames: Vec<(SystemTime, Vec<u8>, Result<Frame, SerialComError>)>,
+    /// bytes of unfinished frame, after `data`
+    pub buffered: usize,
+    /// counters of the decoder, since device was opened
+    pub stats: DecoderStats,
+}
+
+impl Received {
+    /// appends read done after the ones already batched
+    fn merge(&mut self, later: Received) {
+        self.data.extend(later.data);
+        self.frames.extend(later.frames);
+        self.buffered = later.buffered;
+        self.stats = later.stats;
+    }
+}
+
+/// Application side of `SerialHandler`, told about everything that happens on devices
+pub trait Listener: Send + Sync + 'static {
+    /// device was opened, or it disconnected and is being reopened
+    fn connection(self: &Arc<Self>, handle: DeviceHandle, state: ConnectionState) -> impl Future<Output = ()> + Send;
+
+    /// data was read from device, returns false if device is no longer known, its worker stops then
+    fn received(self: &Arc<Self>, handle: DeviceHandle, received: Received) -> impl Future<Output = bool> + Send;
+
+    /// request to device was completed or rejected, so its `TxQueue` changed
+    fn request_done(self: &Arc<Self>, handle: DeviceHandle);
+
+    /// something went wrong on device outside of any request, e.g. `SerialComError::ReadTimeout`
+    fn failed(self: &Arc<Self>, handle: DeviceHandle, error: SerialComError) -> impl Future<Output = ()> + Send;
+}
+
+pub struct SerialHandler<L> {
+    listener: Arc<L>,
+    cmd_rx: Receiver<Cmd>,
+    
+    devices: HashMap<DeviceHandle, DeviceThread>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DeviceHandle(u64);
+
+impl DeviceHandle {
+    /// new unique handle, for devices that don't have a port (capture viewers)
+    pub fn detached() -> Self {
+        Self(HANDLE_COUNTER.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+pub enum Cmd {
+    RegisterDevice {
+        device: Port,
+        /// used to reopen the device after it disconnects
+        target: Target,
+        /// counts writes of the device
+        queue: Arc<TxQueue>,
+        /// counts bytes and frames of the device
+        traffic: Arc<Traffic>,
+        /// nothing is written to the device, nor its lines are changed, e.g. to sniff a bus safely
+        read_only: bool,
+        result: oneshot::Sender<DeviceHandle>,
+    },
+    CloseDevice {
+        handle: DeviceHandle,
+    },
+    SendData {
+        handle: DeviceHandle,
+        data: Vec<u8>,
+        framing: Framing,
+        result: oneshot::Sender<Result<(), SerialComError>>,
+    },
+    Control {
+        handle: DeviceHandle,
+        control: LineControl,
+        result: oneshot::Sender<Result<(), SerialComError>>,
+    },
+    SetPacing {
+        handle: DeviceHandle,
+        pacing: Pacing,
+        result: oneshot::Sender<Result<(), SerialComError>>,
+    },
+    SetMaxFrameLen {
+        handle: DeviceHandle,
+        max_len: usize,
+        result: oneshot::Sender<Result<(), SerialComError>>,
+    },
+    SetTimeouts {
+        handle: DeviceHandle,
+        timeouts: Timeouts,
+        result: oneshot::Sender<Result<(), SerialComError>>,
+    },
+    SetReconnect {
+        handle: DeviceHandle,
+        policy: ReconnectPolicy,
+        result: oneshot::Sender<Result<(), SerialComError>>,
+    },
+    /// `None` leaves port lines as they are
+    SetRs485 {
+        handle: DeviceHandle,
+        rs485: Option<Rs485>,
+        result: oneshot::Sender<Result<(), SerialComError>>,
+    },
+}
+
+struct DeviceThread {
+    cancel_token: CancellationToken,
+    tx: Sender<WorkerRequest>,
+    /// requests that didn't fit into `tx`, passed on in order by `unpark`
+    parked: UnboundedSender<WorkerRequest>,
+    /// requests in `parked` not passed on yet
+    parked_len: Arc<AtomicUsize>,
+    queue: Arc<TxQueue>,
+    read_only: bool,
+}
+
+impl<L: Listener> SerialHandler<L> {
+    pub fn new(listener: Arc<L>, cmd_rx: Receiver<Cmd>) -> Self {
+        Self {
+            listener,
+            cmd_rx,
+            devices: Default::default(),
+        }
+    }
+
+    /// serves `Cmd`s until all senders are dropped
+    pub async fn run(&mut self) {
+        while let Some(cmd) = self.cmd_rx.recv().await {
+            match cmd {
+                Cmd::RegisterDevice { device, target, queue, traffic, read_only, result } => {
+                    let handle = DeviceHandle(
+                        HANDLE_COUNTER.fetch_add(1, Ordering::Relaxed)
+                    );
+                    
+                    let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+                    let cancel_token = CancellationToken::new();
+                    tokio::spawn(Self::device_handler(
+                        self.listener.clone(),
+                        cancel_token.clone(),
+                        handle,
+                        target,
+                        device,
+                        queue.clone(),
+                        traffic,
+                        rx,
+                    ));
+
+                    let (parked, parked_rx) = mpsc::unbounded_channel();
+                    let parked_len = Arc::new(AtomicUsize::new(0));
+                    tokio::spawn(unpark(tx.clone(), queue.clone(), parked_len.clone(), parked_rx));
+
+                    if result.send(handle).is_ok() {
+                        self.devices
+                            .entry(handle)
+                            .or_insert(DeviceThread {
+                                cancel_token,
+                                tx,
+                                parked,
+                                parked_len,
+                                queue,
+                                read_only,
+                            });
+                    }
+                },
+                Cmd::CloseDevice { handle } => {
+                    if let Some(v) = self.devices.remove(&handle) {
+                        v.cancel_token.cancel();
+                    }
+                },
+                Cmd::SendData { handle, data, framing, result } => {
+                    let generation = match self.devices.get(&handle) {
+                        Some(v) => v.queue.push(data.len()),
+                        None => 0,
+                    };
+
+                    self.forward(handle, Request::Write(data, framing, generation), result);
+                },
+                Cmd::Control { handle, control, result } => {
+                    self.forward(handle, Request::Control(control), result);
+                },
+                Cmd::SetPacing { handle, pacing, result } => {
+                    self.forward(handle, Request::Pacing(pacing), result);
+                },
+                Cmd::SetMaxFrameLen { handle, max_len, result } => {
+                    self.forward(handle, Request::MaxFrameLen(max_len), result);
+                },
+                Cmd::SetTimeouts { handle, timeouts, result } => {
+                    self.forward(handle, Request::Timeouts(timeouts), result);
+                },
+                Cmd::SetReconnect { handle, policy, result } => {
+                    self.forward(handle, Request::Reconnect(policy), result);
+                },
+                Cmd::SetRs485 { handle, rs485, result } => {
+                    self.forward(handle, Request::Rs485(rs485), result);
+                },
+            }
+        }
+    }
+
+    /// passes request to worker of device with `handle`
+    fn forward(&self, handle: DeviceHandle, request: Request, result: oneshot::Sender<Result<(), SerialComError>>) {
+        let Some(v) = self.devices.get(&handle) else {
+            let _ = result.send(Err(SerialComError::InvalidHandle));
+            return;
+        };
+
+        // checked here, so it holds while the device is reopened too
+        if v.read_only && matches!(request, Request::Write(..) | Request::Control(_) | Request::Rs485(Some(_))) {
+            reject(&v.queue, (request, result), SerialComError::ReadOnly);
+            return;
+        }
+
+        // while some requests are parked, the following ones go after them, so order is kept
+        let request = if v.parked_len.load(Ordering::Acquire) == 0 {
+            match v.tx.try_send((request, result)) {
+                Ok(()) => return,
+                Err(TrySendError::Full(request)) => request,
+                Err(TrySendError::Closed(request)) => {
+                    reject(&v.queue, request, SerialComError::WorkerStopped);
+                    return;
+                },
+            }
+        } else {
+            (request, result)
+        };
+
+        let reject_write = v.queue.policy() == QueuePolicy::Reject && matches!(request.0, Request::Write(..));
+        if reject_write || v.parked_len.load(Ordering::Acquire) >= PARKED_CAPACITY {
+            reject(&v.queue, request, SerialComError::QueueFull);
+            return;
+        }
+
+        // waits aside, so other devices are served meanwhile
+        v.parked_len.fetch_add(1, Ordering::AcqRel);
+        if let Err(err) = v.parked.send(request) {
+            v.parked_len.fetch_sub(1, Ordering::AcqRel);
+            reject(&v.queue, err.0, SerialComError::WorkerStopped);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn device_handler(
+        listener: Arc<L>,
+        cancel: CancellationToken,
+        handle: DeviceHandle,
+        target: Target,
+        device: Port,
+        queue: Arc<TxQueue>,
+        traffic: Arc<Traffic>,
+        mut rx: Receiver<WorkerRequest>,
+    ) {
+        let mut device = Some(device);
+        let mut state = WorkerState { traffic, ..Default::default() };
+
+        loop {
+            let port = match device.take() {
+                Some(port) => port,
+                None => match Self::reconnect(&listener, &cancel, handle, &target, &mut state, &queue, &mut rx).await {
+                    Some(port) => port,
+                    None => return,
+                },
+            };
+
+            listener.connection(handle, ConnectionState::Connected).await;
+            Self::run_port(&listener, &cancel, handle, port, &mut state, &queue, &mut rx).await;
+
+            if cancel.is_cancelled() {
+                return;
+            }
+
+            log::warn!("device {:?} disconnected", handle);
+        }
+    }
+
+    /// tries to reopen device as `ReconnectPolicy` says, until it succeeds or handler is cancelled,
+    /// frames sent in the meantime are rejected
+    async fn reconnect(
+        listener: &Arc<L>,
+        cancel: &CancellationToken,
+        handle: DeviceHandle,
+        target: &Target,
+        state: &mut WorkerState,
+        queue: &TxQueue,
+        rx: &mut Receiver<WorkerRequest>,
+    ) -> Option<Port> {
+        let mut attempts = 0;
+        let mut next_attempt = Self::schedule(listener, handle, &state.reconnect, attempts).await;
+
+        loop {
+            tokio::select! {
+                biased;
+
+                _ = cancel.cancelled() => { return None; },
+
+                option = rx.recv() => {
+                    let (request, r) = option?;
+                    let reschedule = matches!(request, Request::Reconnect(_));
+                    let _ = r.send(match request {
+                        Request::Pacing(pacing) => {
+                            state.pacer.pacing = pacing;
+                            Ok(())
+                        },
+                        Request::MaxFrameLen(max_len) => {
+                            state.frame_builder.set_max_len(max_len);
+                            Ok(())
+                        },
+                        Request::Timeouts(timeouts) => {
+                            state.timeouts = timeouts;
+                            Ok(())
+                        },
+                        Request::Reconnect(policy) => {
+                            state.reconnect = policy;
+                            Ok(())
+                        },
+                        Request::Rs485(rs485) => {
+                            state.rs485 = rs485;
+                            Ok(())
+                        },
+                        Request::Write(data, _, generation) => {
+                            listener.request_done(handle);
+                            if queue.pop(data.len(), generation) {
+                                Err(SerialComError::Disconnected)
+                            } else {
+                                Err(SerialComError::Cancelled)
+                            }
+                        },
+                        Request::Control(_) => Err(SerialComError::Disconnected),
+                    });
+
+                    if reschedule {
+                        next_attempt = Self::schedule(listener, handle, &state.reconnect, attempts).await;
+                    }
+                }
+
+                _ = tokio::time::sleep_until(next_attempt.unwrap_or_else(tokio::time::Instant::now)), if next_attempt.is_some() => {
+                    match target.open().await {
+                        Ok(port) => return Some(port),
+                        Err(err) => log::debug!("unable to reopen device: {}", err),
+                    }
+
+                    attempts += 1;
+                    next_attempt = Self::schedule(listener, handle, &state.reconnect, attempts).await;
+                }
+            }
+        }
+    }
+
+    /// time of next reopen attempt after `attempts` failed ones, `None` if there is none, reported to listener
+    async fn schedule(listener: &Arc<L>, handle: DeviceHandle, policy: &ReconnectPolicy, attempts: u32) -> Option<tokio::time::Instant> {
+        match policy.delay(attempts) {
+            Some(delay) => {
+                listener.connection(handle, ConnectionState::Reconnecting { attempt: attempts + 1, delay }).await;
+                Some(tokio::time::Instant::now() + delay)
+            },
+            None => {
+                log::warn!("device {:?} won't be reopened anymore", handle);
+                listener.connection(handle, ConnectionState::Failed).await;
+                None
+            },
+        }
+    }
+
+    /// communicates with opened port, returns when it is cancelled or port disconnects
+    async fn run_port(
+        listener: &Arc<L>,
+        cancel: &CancellationToken,
+        handle: DeviceHandle,
+        mut device: Port,
+        state: &mut WorkerState,
+        queue: &TxQueue,
+        rx: &mut Receiver<WorkerRequest>,
+    ) {
+        // fits any UDP datagram, smaller reads would truncate them
+        let mut rx_buffer = vec![0u8; 65536];
+        let mut last_read = tokio::time::Instant::now();
+
+        // lines are asserted when port is opened, it would block the bus
+        if let Some(rs485) = state.rs485 {
+            if let Err(err) = device.control(rs485.control(false)).await {
+                log::warn!("device {:?}: {:#}", handle, err);
+            }
+        }
+
+        // reads not passed to listener yet, and when they will be
+        let mut batch: Option<Received> = None;
+        let mut flush_at = tokio::time::Instant::now();
+        let mut last_flush: Option<tokio::time::Instant> = None;
+
+        loop {
+            let read_timeout = state.timeouts.read;
+            let read_deadline = last_read + read_timeout.unwrap_or_default();
+
+            tokio::select! {
+                biased;
+
+                _ = cancel.cancelled() => { return; },
+
+                _ = tokio::time::sleep_until(flush_at), if batch.is_some() => {
+                    last_flush = Some(tokio::time::Instant::now());
+                    Self::flush(listener, cancel, handle, &mut batch).await;
+                }
+
+                _ = tokio::time::sleep_until(read_deadline), if read_timeout.is_some() => {
+                    let error = SerialComError::ReadTimeout(read_timeout.unwrap_or_default());
+                    log::warn!("device {:?}: {}", handle, error);
+                    listener.failed(handle, error).await;
+                    break;
+                }
+
+                option = rx.recv() => {
+                    if let Some((request, r)) = option {
+                        // reading pauses while waiting for pacing, data is buffered by OS meanwhile
+                        let result = Self::handle_request(&mut device, state, queue, request).await;
+                        let failed = matches!(result, Err(SerialComError::Write(_)));
+                        let _ = r.send(result);
+                        listener.request_done(handle);
+
+                        // port is broken, it's reopened
+                        if failed {
+                            break;
+                        }
+                    } else {
+                        // inform about error?
+                        cancel.cancel()
+                    }
+                }
+
+                result = device.read(&mut rx_buffer) => {
+                    match result {
+                        // end of stream, port is gone
+                        Ok(0) => break,
+                        Ok(read) => {
+                            last_read = tokio::time::Instant::now();
+                            let time = SystemTime::now();
+                            let overflows = state.frame_builder.stats().overflows;
+                            let decoded = state.frame_builder.push_buf_raw(&rx_buffer[..read]);
+
+                            // invalid frames occupied the bus too
+                            if !decoded.is_empty() {
+                                state.pacer.last_frame_end = Some(last_read);
+                            }
+
+                            // dropped while being assembled, so they have no wire bytes
+                            let max_len = state.frame_builder.max_len();
+                            let mut frames = (overflows..state.frame_builder.stats().overflows)
+                                .map(|_| (time, Vec::new(), Err(SerialComError::DecoderOverflow { max_len })))
+                                .collect::<Vec<_>>();
+                            frames.extend(decoded.into_iter().map(|(raw, result)| (time, raw, result.map_err(SerialComError::from))));
+
+                            for (_, _, result) in &frames {
+                                if let Err(err) = result {
+                                    log::info!("discarded frame, reason `{}`", err);
+                                }
+                            }
+
+                            state.traffic.received(read, frames.iter().filter(|(_, _, result)| result.is_ok()).count());
+
+                            let received = Received {
+                                data: vec![(time, rx_buffer[..read].to_vec())],
+                                frames,
+                                buffered: state.frame_builder.buffered(),
+                                stats: state.frame_builder.stats(),
+                            };
+
+                            // read after a quiet period is passed right away, following ones wait for the interval
+                            match batch.as_mut() {
+                                Some(batch) => batch.merge(received),
+                                None => {
+                                    batch = Some(received);
+                                    flush_at = last_flush.map_or(last_read, |last_flush| last_read.max(last_flush + FLUSH_INTERVAL));
+                                },
+                            }
+                        },
+                        Err(err) => {
+                            log::warn!("{:?}", err);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        // data read before port failed
+        Self::flush(listener, cancel, handle, &mut batch).await;
+    }
+
+    /// passes batched reads to listener
+    async fn flush(listener: &Arc<L>, cancel: &CancellationToken, handle: DeviceHandle, batch: &mut Option<Received>) {
+        let Some(received) = batch.take() else {
+            return;
+        };
+
+        if !listener.received(handle, received).await {
+            // device was closed by the application meanwhile
+            cancel.cancel()
+        }
+    }
+
+    async fn handle_request(device: &mut Port, state: &mut WorkerState, queue: &TxQueue, request: Request) -> Result<(), SerialComError> {
+        match request {
+            Request::Write(data, framing, generation) => {
+                // cancelled writes don't wait for pacing
+                if generation != queue.generation.load(Ordering::Relaxed) {
+                    queue.pop(data.len(), generation);
+                    return Err(SerialComError::Cancelled);
+                }
+
+                state.pacer.wait().await;
+                log::info!("SENDING FRAME: {:02X?}", data);
+                let wire = match framing {
+                    Framing::Frame | Framing::Continuation if device.escapes_flow_control() => proto::encoding::escape_flow_control(&data),
+                    _ => Cow::Borrowed(&data[..]),
+                };
+
+                let write = Self::transmit(device, &wire, state.rs485);
+                let result = match state.timeouts.write {
+                    Some(limit) => tokio::time::timeout(limit, write).await.unwrap_or(Err(SerialComError::WriteTimeout(limit))),
+                    None => write.await,
+                };
+
+                // written even if cancelled while waiting for pacing
+                queue.pop(data.len(), generation);
+
+                // transmitter could be left enabled by the timeout
+                if let (Err(SerialComError::WriteTimeout(_)), Some(rs485)) = (&result, state.rs485) {
+                    let _ = device.control(rs485.control(false)).await;
+                }
+
+                result?;
+                state.traffic.sent(&wire, framing);
+            },
+            Request::Control(control) => device.control(control).await?,
+            Request::Pacing(pacing) => state.pacer.pacing = pacing,
+            Request::MaxFrameLen(max_len) => state.frame_builder.set_max_len(max_len),
+            Request::Timeouts(timeouts) => state.timeouts = timeouts,
+            Request::Reconnect(policy) => state.reconnect = policy,
+            Request::Rs485(rs485) => {
+                if let Some(rs485) = rs485 {
+                    device.control(rs485.control(false)).await?;
+                }
+
+                state.rs485 = rs485;
+            },
+        }
+
+        Ok(())
+    }
+
+    /// writes `data`, with transmitter enabled only meanwhile if `rs485` is given
+    async fn transmit(device: &mut Port, data: &[u8], rs485: Option<Rs485>) -> Result<(), SerialComError> {
+        let Some(rs485) = rs485 else {
+            return device.write_all(data).await.map_err(SerialComError::Write);
+        };
+
+        device.control(rs485.control(true)).await?;
+        tokio::time::sleep(rs485.delay_before).await;
+
+        // flush waits until the last byte is sent out, not just handed to the driver
+        let result = match device.write_all(data).await {
+            Ok(()) => device.flush().await,
+            Err(err) => Err(err),
+        };
+
+        tokio::time::sleep(rs485.delay_after).await;
+        device.control(rs485.control(false)).await?;
+
+        result.map_err(SerialComError::Write)
+    }
+}
+
+/// passes requests parked by `SerialHandler::forward` to the worker in order, as it makes room
+async fn unpark(tx: Sender<WorkerRequest>, queue: Arc<TxQueue>, parked_len: Arc<AtomicUsize>, mut parked: UnboundedReceiver<WorkerRequest>) {
+    while let Some(request) = parked.recv().await {
+        if let Err(err) = tx.send(request).await {
+            reject(&queue, err.0, SerialComError::WorkerStopped);
+        }
+
+        // only once it's passed on, so no later request overtakes it
+        parked_len.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// answers request that didn't reach the worker
+fn reject(queue: &TxQueue, (request, result): WorkerRequest, err: SerialComError) {
+    if let Request::Write(data, _, generation) = &request {
+        queue.pop(data.len(), *generation);
+    }
+
+    let _ = result.send(Err(err));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use proto::Frame;
+    use tokio::sync::{mpsc, oneshot};
+
+    use std::time::Duration;
+
+    use crate::{Cmd, ConnectionState, DeviceHandle, Framing, Listener, Pacing, QUEUE_CAPACITY, QueuePolicy, Received, ReconnectPolicy, SerialComError, SerialHandler, Timeouts, Traffic, TrafficCounts, TxQueue, transport::{self, Port, Target}};
+
+    /// passes received frames to the test
+    struct Frames(mpsc::UnboundedSender<Result<Frame, SerialComError>>);
+
+    impl Listener for Frames {
+        async fn connection(self: &Arc<Self>, _handle: DeviceHandle, _state: ConnectionState) {}
+
+        async fn received(self: &Arc<Self>, _handle: DeviceHandle, received: Received) -> bool {
+            for (_, _, frame) in received.frames {
+                let _ = self.0.send(frame);
+            }
+
+            true
+        }
+
+        fn request_done(self: &Arc<Self>, _handle: DeviceHandle) {}
+
+        async fn failed(self: &Arc<Self>, _handle: DeviceHandle, error: SerialComError) {
+            let _ = self.0.send(Err(error));
+        }
+    }
+
+    /// runs handler telling `listener` about devices, returns its command channel
+    fn spawn_handler<L: Listener>(listener: Arc<L>) -> mpsc::Sender<Cmd> {
+        let (cmd_tx, cmd_rx) = mpsc::channel(1);
+        tokio::spawn(async move { SerialHandler::new(listener, cmd_rx).run().await });
+        cmd_tx
+    }
+
+    /// runs handler passing received frames and failures to the returned channel
+    fn spawn_frames_handler() -> (mpsc::Sender<Cmd>, mpsc::UnboundedReceiver<Result<Frame, SerialComError>>) {
+        let (frames_tx, frames) = mpsc::unbounded_channel();
+        (spawn_handler(Arc::new(Frames(frames_tx))), frames)
+    }
+
+    /// sends command made by `cmd` with result channel, and waits for the result
+    async fn request<T>(cmd_tx: &mpsc::Sender<Cmd>, cmd: impl FnOnce(oneshot::Sender<T>) -> Cmd) -> T {
+        let (result_tx, result) = oneshot::channel();
+        cmd_tx.send(cmd(result_tx)).await.ok().unwrap();
+        result.await.unwrap()
+    }
+
+    /// opens loopback device and registers it with `queue` and `traffic`
+    async fn open_loopback_with(cmd_tx: &mpsc::Sender<Cmd>, queue: Arc<TxQueue>, traffic: Arc<Traffic>, read_only: bool) -> DeviceHandle {
+        let target = Target::new(transport::LOOPBACK, tokio_serial::new(transport::LOOPBACK, 115200));
+        let device = target.open().await.unwrap();
+
+        request(cmd_tx, |result| Cmd::RegisterDevice { device, target, queue, traffic, read_only, result }).await
+    }
+
+    /// registers loopback escaping written frames like a port using XON/XOFF
+    async fn open_escaping_loopback(cmd_tx: &mpsc::Sender<Cmd>, traffic: Arc<Traffic>) -> DeviceHandle {
+        let target = Target::new(transport::LOOPBACK, tokio_serial::new(transport::LOOPBACK, 115200));
+        let device = Port::loopback(true);
+
+        request(cmd_tx, |result| Cmd::RegisterDevice { device, target, queue: Default::default(), traffic, read_only: false, result }).await
+    }
+
+    async fn open_loopback(cmd_tx: &mpsc::Sender<Cmd>) -> DeviceHandle {
+        open_loopback_with(cmd_tx, Default::default(), Default::default(), false).await
+    }
+
+    #[tokio::test]
+    async fn loopback() {
+        let (cmd_tx, mut frames) = spawn_frames_handler();
+        let handle = open_loopback(&cmd_tx).await;
+
+        let frame = Frame { sender: 1, receiver: 2, data: b"ping".to_vec() };
+        request(&cmd_tx, |result| Cmd::SendData { handle, data: frame.serialize().unwrap(), framing: Framing::Frame, result }).await.unwrap();
+
+        assert_eq!(frames.recv().await.unwrap().unwrap(), frame);
+    }
+
+    #[tokio::test]
+    async fn traffic_counted() {
+        let (cmd_tx, mut frames) = spawn_frames_handler();
+        let traffic = Arc::new(Traffic::default());
+        let handle = open_escaping_loopback(&cmd_tx, traffic.clone()).await;
+
+        // raw write is counted only as bytes, even if it looks like a frame, split frame is counted once
+        let frame = Frame { sender: 1, receiver: 2, data: b"\x11ping".to_vec() }.serialize().unwrap();
+        let (first, rest) = frame.split_at(frame.len() / 2);
+        let writes = [(b"(ok)".to_vec(), Framing::Raw), (first.to_vec(), Framing::Frame), (rest.to_vec(), Framing::Continuation)];
+        for (data, framing) in writes {
+            request(&cmd_tx, |result| Cmd::SendData { handle, data, framing, result }).await.unwrap();
+        }
+
+        assert!(frames.recv().await.unwrap().is_err());
+        frames.recv().await.unwrap().unwrap();
+
+        // escaped XON is counted as two bytes, as written
+        let bytes = 4 + frame.len() as u64 + 1;
+        assert_eq!(traffic.counts(), TrafficCounts { bytes_sent: bytes, frames_sent: 1, bytes_received: bytes, frames_received: 1 });
+    }
+
+    /// collects bytes read from devices
+    struct Bytes(mpsc::UnboundedSender<Vec<u8>>);
+
+    impl Listener for Bytes {
+        async fn connection(self: &Arc<Self>, _handle: DeviceHandle, _state: ConnectionState) {}
+
+        async fn received(self: &Arc<Self>, _handle: DeviceHandle, received: Received) -> bool {
+            for (_, data) in received.data {
+                let _ = self.0.send(data);
+            }
+
+            true
+        }
+
+        fn request_done(self: &Arc<Self>, _handle: DeviceHandle) {}
+
+        async fn failed(self: &Arc<Self>, _handle: DeviceHandle, _error: SerialComError) {}
+    }
+
+    #[tokio::test]
+    async fn raw_writes_not_escaped() {
+        let (bytes_tx, mut bytes) = mpsc::unbounded_channel();
+        let cmd_tx = spawn_handler(Arc::new(Bytes(bytes_tx)));
+        let handle = open_escaping_loopback(&cmd_tx, Default::default()).await;
+
+        request(&cmd_tx, |result| Cmd::SendData { handle, data: b"\x11".to_vec(), framing: Framing::Raw, result }).await.unwrap();
+        assert_eq!(bytes.recv().await.unwrap(), b"\x11");
+
+        // XON in a frame doesn't reach the wire
+        let frame = Frame { sender: 1, receiver: 2, data: b"\x11".to_vec() };
+        request(&cmd_tx, |result| Cmd::SendData { handle, data: frame.serialize().unwrap(), framing: Framing::Frame, result }).await.unwrap();
+        assert!(!bytes.recv().await.unwrap().contains(&0x11));
+    }
+
+    #[tokio::test]
+    async fn typed_errors() {
+        let (cmd_tx, mut frames) = spawn_frames_handler();
+
+        let result = request(&cmd_tx, |result| Cmd::SendData { handle: DeviceHandle::detached(), data: Vec::new(), framing: Framing::Raw, result }).await;
+        assert!(matches!(result, Err(SerialComError::InvalidHandle)));
+
+        let handle = open_loopback(&cmd_tx).await;
+
+        // loopback has no lines to drive transceiver with
+        let result = request(&cmd_tx, |result| Cmd::SetRs485 { handle, rs485: Some(Default::default()), result }).await;
+        assert!(matches!(result, Err(SerialComError::ControlUnsupported)));
+
+        request(&cmd_tx, |result| Cmd::SetMaxFrameLen { handle, max_len: 16, result }).await.unwrap();
+
+        let frame = Frame { sender: 1, receiver: 2, data: vec![0x55; 32] };
+        request(&cmd_tx, |result| Cmd::SendData { handle, data: frame.serialize().unwrap(), framing: Framing::Frame, result }).await.unwrap();
+
+        assert!(matches!(frames.recv().await.unwrap(), Err(SerialComError::DecoderOverflow { max_len: 16 })));
+    }
+
+    #[tokio::test]
+    async fn full_queue_rejects() {
+        let (cmd_tx, _frames) = spawn_frames_handler();
+        let queue = Arc::new(TxQueue::default());
+        queue.set_policy(QueuePolicy::Reject);
+        let handle = open_loopback_with(&cmd_tx, queue.clone(), Default::default(), false).await;
+
+        // only the first write is done, the next one waits for pacing and the rest stay queued
+        let pacing = Pacing { min_gap: Duration::from_secs(3600), ..Default::default() };
+        request(&cmd_tx, |result| Cmd::SetPacing { handle, pacing, result }).await.unwrap();
+
+        let mut results = Vec::new();
+        for _ in 0..QUEUE_CAPACITY + 3 {
+            let (result_tx, result) = oneshot::channel();
+            cmd_tx.send(Cmd::SendData { handle, data: vec![0], framing: Framing::Raw, result: result_tx }).await.ok().unwrap();
+            results.push(result);
+        }
+
+        assert!(queue.is_full());
+        assert!(matches!(results.pop().unwrap().await.unwrap(), Err(SerialComError::QueueFull)));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn full_queue_keeps_order() {
+        let (cmd_tx, mut frames) = spawn_frames_handler();
+        let queue = Arc::new(TxQueue::default());
+        let handle = open_loopback_with(&cmd_tx, queue.clone(), Default::default(), false).await;
+
+        // slow enough for the worker queue to fill up
+        let pacing = Pacing { min_gap: Duration::from_millis(2), ..Default::default() };
+        request(&cmd_tx, |result| Cmd::SetPacing { handle, pacing, result }).await.unwrap();
+
+        let count = QUEUE_CAPACITY as u32 * 2;
+        let mut results = Vec::new();
+        for i in 0..count {
+            let data = Frame { sender: 1, receiver: 2, data: i.to_be_bytes().to_vec() }.serialize().unwrap();
+            let (result_tx, result) = oneshot::channel();
+            cmd_tx.send(Cmd::SendData { handle, data, framing: Framing::Frame, result: result_tx }).await.ok().unwrap();
+            results.push(result);
+        }
+
+        assert!(queue.is_full());
+        for result in results {
+            result.await.unwrap().unwrap();
+        }
+
+        for i in 0..count {
+            assert_eq!(frames.recv().await.unwrap().unwrap().data, i.to_be_bytes());
+        }
+    }
+
+    #[tokio::test]
+    async fn read_only() {
+        let (cmd_tx, _frames) = spawn_frames_handler();
+        let queue = Arc::new(TxQueue::default());
+        let handle = open_loopback_with(&cmd_tx, queue.clone(), Default::default(), true).await;
+
+        let result = request(&cmd_tx, |result| Cmd::SendData { handle, data: vec![0], framing: Framing::Raw, result }).await;
+        assert!(matches!(result, Err(SerialComError::ReadOnly)));
+        assert_eq!(queue.pending(), 0);
+
+        // settings not writing anything are allowed
+        request(&cmd_tx, |result| Cmd::SetMaxFrameLen { handle, max_len: 64, result }).await.unwrap();
+    }
+
+    #[test]
+    fn reconnect_backoff() {
+        let policy = ReconnectPolicy { enabled: true, initial_delay: Duration::from_secs(1), backoff: 2.0, max_attempts: 10 };
+
+        assert_eq!(policy.delay(0), Some(Duration::from_secs(1)));
+        assert_eq!(policy.delay(3), Some(Duration::from_secs(8)));
+        // capped
+        assert_eq!(policy.delay(9), Some(Duration::from_secs(60)));
+        assert_eq!(policy.delay(10), None);
+
+        assert_eq!(ReconnectPolicy { enabled: false, ..policy }.delay(0), None);
+        assert_eq!(ReconnectPolicy { max_attempts: 0, ..policy }.delay(1000), Some(Duration::from_secs(60)));
+    }
+
+    #[tokio::test]
+    async fn timeouts() {
+        let (cmd_tx, mut frames) = spawn_frames_handler();
+        let handle = open_loopback(&cmd_tx).await;
+
+        let limit = Duration::from_millis(50);
+        let timeouts = Timeouts { write: Some(limit), read: Some(limit) };
+        request(&cmd_tx, |result| Cmd::SetTimeouts { handle, timeouts, result }).await.unwrap();
+
+        // more than loopback buffers, while nothing reads it back
+        let result = request(&cmd_tx, |result| Cmd::SendData { handle, data: vec![0; 1 << 20], framing: Framing::Raw, result }).await;
+        assert!(matches!(result, Err(SerialComError::WriteTimeout(_))));
+
+        assert!(matches!(frames.recv().await.unwrap(), Err(SerialComError::ReadTimeout(_))));
+    }
+
+    #[tokio::test]
+    async fn turnaround() {
+        let (cmd_tx, mut frames) = spawn_frames_handler();
+        let handle = open_loopback(&cmd_tx).await;
+
+        let turnaround = Duration::from_millis(200);
+        request(&cmd_tx, |result| Cmd::SetPacing { handle, pacing: Pacing { turnaround, ..Default::default() }, result }).await.unwrap();
+
+        let data = Frame { sender: 1, receiver: 2, data: b"ping".to_vec() }.serialize().unwrap();
+
+        // nothing was received yet, so the first write doesn't wait
+        request(&cmd_tx, |result| Cmd::SendData { handle, data: data.clone(), framing: Framing::Frame, result }).await.unwrap();
+        frames.recv().await.unwrap().unwrap();
+
+        let start = tokio::time::Instant::now();
+        request(&cmd_tx, |result| Cmd::SendData { handle, data, framing: Framing::Frame, result }).await.unwrap();
+
+        // frame end was taken slightly before `start`
+        assert!(start.elapsed() >= turnaround / 2);
+    }
+
+    /// counts `received` calls, and frames they carried
+    #[derive(Default)]
+    struct Batches {
+        calls: std::sync::atomic::AtomicUsize,
+        frames: std::sync::atomic::AtomicUsize,
+    }
+
+    impl Listener for Batches {
+        async fn connection(self: &Arc<Self>, _handle: DeviceHandle, _state: ConnectionState) {}
+
+        async fn received(self: &Arc<Self>, _handle: DeviceHandle, received: Received) -> bool {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            self.frames.fetch_add(received.frames.len(), std::sync::atomic::Ordering::Relaxed);
+            true
+        }
+
+        fn request_done(self: &Arc<Self>, _handle: DeviceHandle) {}
+
+        async fn failed(self: &Arc<Self>, _handle: DeviceHandle, _error: SerialComError) {}
+    }
+
+    #[tokio::test]
+    async fn reads_batched() {
+        let batches = Arc::new(Batches::default());
+        let cmd_tx = spawn_handler(batches.clone());
+        let handle = open_loopback(&cmd_tx).await;
+
+        let data = Frame { sender: 1, receiver: 2, data: b"ping".to_vec() }.serialize().unwrap();
+        for _ in 0..20 {
+            request(&cmd_tx, |result| Cmd::SendData { handle, data: data.clone(), framing: Framing::Frame, result }).await.unwrap();
+        }
+
+        tokio::time::sleep(crate::FLUSH_INTERVAL * 3).await;
+        assert_eq!(batches.frames.load(std::sync::atomic::Ordering::Relaxed), 20);
+        assert!(batches.calls.load(std::sync::atomic::Ordering::Relaxed) < 20);
+    }
+}
@@ -0,0 +1,248 @@
+use std::{io, net::SocketAddr, time::Duration};
+
+use tokio::{io::{AsyncReadExt, AsyncWriteExt, DuplexStream}, net::{TcpStream, UdpSocket, lookup_host}};
+use tokio_serial::{FlowControl, SerialPort, SerialPortBuilder, SerialStream};
+
+#[cfg(feature = "ble")]
+use crate::ble::BlePort;
+#[cfg(all(feature = "can", target_os = "linux"))]
+use crate::can::{CanPort, CanTarget};
+use crate::{LineControl, SerialComError};
+
+/// how long connecting to network device can take
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// local address of UDP socket, if not given in target
+const UDP_DEFAULT_BIND: &str = "0.0.0.0:0";
+/// name of pseudo-device echoing everything sent to it
+pub const LOOPBACK: &str = "loopback";
+/// bytes written to loopback, that weren't read back yet
+const LOOPBACK_BUFFER: usize = 64 * 1024;
+
+/// Where device is reached, kept to reopen it after disconnect
+pub enum Target {
+    Serial {
+        path: String,
+        builder: SerialPortBuilder,
+    },
+    /// `host:port` of e.g. serial to TCP bridge
+    Tcp(String),
+    /// every datagram carries part of the byte stream, e.g. from Wi-Fi module
+    Udp {
+        /// local address datagrams are received on
+        bind: String,
+        /// `host:port` datagrams are sent to
+        remote: String,
+    },
+    /// name, address or OS identifier of board with Nordic UART service, see `ble`
+    Ble(String),
+    /// SocketCAN interface with optional ids, e.g. `can0?tx=0x100&rx=0x101`, see `can`
+    Can(String),
+    /// no hardware, sent bytes are received back
+    Loopback,
+}
+
+/// Opened connection to a device
+pub enum Port {
+    Serial {
+        stream: SerialStream,
+        /// port uses XON/XOFF, flow control bytes in written frames are escaped, see
+        /// `proto::encoding::FLOW_CONTROL_ESCAPE_TABLE`
+        escape_flow_control: bool,
+    },
+    Tcp(TcpStream),
+    Udp {
+        socket: UdpSocket,
+        remote: SocketAddr,
+    },
+    Loopback {
+        /// written bytes
+        tx: DuplexStream,
+        /// the other end of `tx`
+        rx: DuplexStream,
+        /// written frames are escaped like on a serial port using XON/XOFF
+        escape_flow_control: bool,
+    },
+    /// boxed, it's much larger than the other ports
+    #[cfg(feature = "ble")]
+    Ble(Box<BlePort>),
+    #[cfg(all(feature = "can", target_os = "linux"))]
+    Can(CanPort),
+}
+
+impl Target {
+    /// target for name entered in the device picker, one of:
+    /// * `tcp://host:port`
+    /// * `udp://host:port` or `udp://host:port?bind=local_host:local_port`
+    /// * `ble://name`, `ble://address` or `ble://id`, Bluetooth LE board (needs `ble` feature)
+    /// * `can://interface` or `can://interface?tx=id&rx=id`, board on CAN bus (needs `can` feature, Linux only)
+    /// * `loopback`
+    /// * path of serial port, opened with `serial` line parameters
+    pub fn new(name: &str, serial: SerialPortBuilder) -> Self {
+        if name == LOOPBACK {
+            return Target::Loopback;
+        }
+
+        if let Some(addr) = name.strip_prefix("tcp://") {
+            return Target::Tcp(addr.to_owned());
+        }
+
+        if let Some(device) = name.strip_prefix("ble://") {
+            return Target::Ble(device.to_owned());
+        }
+
+        if let Some(target) = name.strip_prefix("can://") {
+            return Target::Can(target.to_owned());
+        }
+
+        if let Some(addr) = name.strip_prefix("udp://") {
+            let (remote, bind) = addr.split_once("?bind=").unwrap_or((addr, UDP_DEFAULT_BIND));
+
+            return Target::Udp {
+                bind: bind.to_owned(),
+                remote: remote.to_owned(),
+            };
+        }
+
+        Target::Serial {
+            path: name.to_owned(),
+            builder: serial,
+        }
+    }
+
+    /// name it was created from
+    pub fn name(&self) -> String {
+        match self {
+            Target::Serial { path, .. } => path.clone(),
+            Target::Tcp(addr) => format!("tcp://{}", addr),
+            Target::Udp { bind, remote } => format!("udp://{}?bind={}", remote, bind),
+            Target::Ble(device) => format!("ble://{}", device),
+            Target::Can(target) => format!("can://{}", target),
+            Target::Loopback => LOOPBACK.to_owned(),
+        }
+    }
+
+    pub async fn open(&self) -> Result<Port, SerialComError> {
+        self.try_open().await.map_err(|source| SerialComError::PortOpen { target: self.name(), source })
+    }
+
+    async fn try_open(&self) -> io::Result<Port> {
+        match self {
+            Target::Serial { builder, .. } => {
+                let stream = SerialStream::open(builder)?;
+                let escape_flow_control = stream.flow_control()? == FlowControl::Software;
+
+                Ok(Port::Serial { stream, escape_flow_control })
+            },
+            Target::Tcp(addr) => {
+                let stream = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(addr))
+                    .await
+                    .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "timed out connecting"))??;
+                stream.set_nodelay(true)?;
+
+                Ok(Port::Tcp(stream))
+            },
+            Target::Udp { bind, remote } => {
+                let remote = lookup_host(remote)
+                    .await?
+                    .next()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "host name resolved to no address"))?;
+
+                let socket = UdpSocket::bind(bind).await?;
+
+                Ok(Port::Udp { socket, remote })
+            },
+            Target::Loopback => Ok(Port::loopback(false)),
+            #[cfg(feature = "ble")]
+            Target::Ble(device) => Ok(Port::Ble(Box::new(BlePort::open(device).await?))),
+            #[cfg(not(feature = "ble"))]
+            Target::Ble(_) => Err(io::Error::new(io::ErrorKind::Unsupported, "built without Bluetooth support")),
+            #[cfg(all(feature = "can", target_os = "linux"))]
+            Target::Can(target) => Ok(Port::Can(CanPort::open(CanTarget::parse(target)?)?)),
+            #[cfg(not(all(feature = "can", target_os = "linux")))]
+            Target::Can(_) => Err(io::Error::new(io::ErrorKind::Unsupported, "built without CAN support, it's available on Linux only")),
+        }
+    }
+}
+
+/// whether device with this name has serial lines (DTR, RTS, break)
+pub fn is_serial(name: &str) -> bool {
+    !name.contains("://") && name != LOOPBACK
+}
+
+impl Port {
+    /// pseudo-device echoing everything written to it, see `Target::Loopback`
+    pub fn loopback(escape_flow_control: bool) -> Self {
+        let (tx, rx) = tokio::io::duplex(LOOPBACK_BUFFER);
+        Port::Loopback { tx, rx, escape_flow_control }
+    }
+
+    /// whether flow control bytes in frames written to the port have to be escaped, raw writes are left as they are
+    pub fn escapes_flow_control(&self) -> bool {
+        matches!(self, Port::Serial { escape_flow_control: true, .. } | Port::Loopback { escape_flow_control: true, .. })
+    }
+
+    pub async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Port::Serial { stream, .. } => stream.read(buf).await,
+            Port::Tcp(stream) => stream.read(buf).await,
+            // datagrams from anyone are accepted, device may send from a different port than it listens on
+            Port::Udp { socket, .. } => loop {
+                // empty datagram would look like end of stream
+                match socket.recv_from(buf).await? {
+                    (0, _) => continue,
+                    (read, _) => break Ok(read),
+                }
+            },
+            Port::Loopback { rx, .. } => rx.read(buf).await,
+            #[cfg(feature = "ble")]
+            Port::Ble(port) => port.read(buf).await,
+            #[cfg(all(feature = "can", target_os = "linux"))]
+            Port::Can(port) => port.read(buf).await,
+        }
+    }
+
+    pub async fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        match self {
+            Port::Serial { stream, .. } => stream.write_all(data).await,
+            Port::Tcp(stream) => stream.write_all(data).await,
+            Port::Udp { socket, remote } => socket.send_to(data, *remote).await.map(|_| ()),
+            Port::Loopback { tx, .. } => tx.write_all(data).await,
+            #[cfg(feature = "ble")]
+            Port::Ble(port) => port.write_all(data).await,
+            #[cfg(all(feature = "can", target_os = "linux"))]
+            Port::Can(port) => port.write_all(data).await,
+        }
+    }
+
+    /// waits until written data is sent out
+    pub async fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Port::Serial { stream, .. } => stream.flush().await,
+            Port::Tcp(stream) => stream.flush().await,
+            Port::Udp { .. } => Ok(()),
+            Port::Loopback { tx, .. } => tx.flush().await,
+            #[cfg(feature = "ble")]
+            Port::Ble(_) => Ok(()),
+            #[cfg(all(feature = "can", target_os = "linux"))]
+            Port::Can(_) => Ok(()),
+        }
+    }
+
+    pub async fn control(&mut self, control: LineControl) -> Result<(), SerialComError> {
+        let Port::Serial { stream: port, .. } = self else {
+            return Err(SerialComError::ControlUnsupported);
+        };
+
+        match control {
+            LineControl::Dtr(level) => port.write_data_terminal_ready(level)?,
+            LineControl::Rts(level) => port.write_request_to_send(level)?,
+            LineControl::Break(duration) => {
+                port.set_break()?;
+                tokio::time::sleep(duration).await;
+                port.clear_break()?;
+            },
+        }
+
+        Ok(())
+    }
+}
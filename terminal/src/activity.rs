@@ -0,0 +1,111 @@
+use std::{collections::VecDeque, sync::Arc, time::{Duration, Instant}};
+
+use eframe::egui::{self, Sense, Stroke, pos2, vec2};
+use serial_com::{Traffic, TrafficCounts};
+
+/// traffic is sampled this often for the sparkline
+const SAMPLE_PERIOD: Duration = Duration::from_millis(500);
+/// samples shown, 30 s
+const SAMPLES: usize = 60;
+const SPARKLINE_SIZE: [f32; 2] = [90.0, 14.0];
+
+/// Counters of a device kept by `serial_com`, with recent line activity sampled from them
+///
+/// They don't change when lists of the device are filtered or cleared.
+#[derive(Debug, Default)]
+pub struct Activity {
+    pub traffic: Arc<Traffic>,
+    /// counters at the last sample
+    last: TrafficCounts,
+    last_sample: Option<Instant>,
+    /// bytes sent and received in each period, oldest first
+    samples: VecDeque<u64>,
+}
+
+/// `bytes` as B, kB or MB
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..=9_999 => format!("{} B", bytes),
+        10_000..=9_999_999 => format!("{:.1} kB", bytes as f64 / 1e3),
+        _ => format!("{:.1} MB", bytes as f64 / 1e6),
+    }
+}
+
+impl Activity {
+    pub fn new(traffic: Arc<Traffic>) -> Self {
+        Self { traffic, ..Default::default() }
+    }
+
+    fn sample(&mut self) {
+        let now = Instant::now();
+        let counts = self.traffic.counts();
+
+        let elapsed = match self.last_sample {
+            Some(last_sample) => now - last_sample,
+            None => {
+                self.last_sample = Some(now);
+                self.last = counts;
+                return;
+            },
+        };
+
+        if elapsed < SAMPLE_PERIOD {
+            return;
+        }
+
+        // periods window wasn't drawn in are quiet, traffic of them is shown in the last one
+        let missed = (elapsed.as_millis() / SAMPLE_PERIOD.as_millis()) as usize - 1;
+        self.samples.extend(std::iter::repeat_n(0, missed.min(SAMPLES)));
+
+        let bytes = |counts: TrafficCounts| counts.bytes_sent + counts.bytes_received;
+        self.samples.push_back(bytes(counts) - bytes(self.last));
+
+        while self.samples.len() > SAMPLES {
+            self.samples.pop_front();
+        }
+
+        self.last = counts;
+        self.last_sample = Some(now);
+    }
+
+    /// compact counters with sparkline of bytes per period, for the top of device window
+    pub fn draw(&mut self, ui: &mut egui::Ui) {
+        self.sample();
+        let counts = self.traffic.counts();
+
+        let (rect, response) = ui.allocate_exact_size(vec2(SPARKLINE_SIZE[0], SPARKLINE_SIZE[1]), Sense::hover());
+        let max = self.samples.iter().copied().max().unwrap_or_default().max(1) as f32;
+        let step = rect.width() / (SAMPLES - 1) as f32;
+        // newest sample is at the right edge
+        let offset = SAMPLES - self.samples.len();
+
+        let points = self.samples
+            .iter()
+            .enumerate()
+            .map(|(i, &bytes)| pos2(rect.left() + (offset + i) as f32 * step, rect.bottom() - bytes as f32 / max * rect.height()))
+            .collect::<Vec<_>>();
+
+        let painter = ui.painter_at(rect);
+        painter.hline(rect.x_range(), rect.bottom(), Stroke::new(1.0, ui.visuals().weak_text_color()));
+        painter.add(egui::Shape::line(points, Stroke::new(1.0, ui.visuals().selection.stroke.color)));
+
+        response.on_hover_text(format!(
+            "bytes sent and received every {:.1} s, in the last {} s",
+            SAMPLE_PERIOD.as_secs_f32(),
+            SAMPLES as u64 * SAMPLE_PERIOD.as_millis() as u64 / 1000,
+        ));
+
+        ui.monospace(format!("↑ {} / {}  ↓ {} / {}",
+            counts.frames_sent,
+            format_bytes(counts.bytes_sent),
+            counts.frames_received,
+            format_bytes(counts.bytes_received),
+        ))
+        .on_hover_text("frames / bytes sent and received since the port was opened, including cleared and filtered out ones");
+
+        // keeps the line moving while there is something to show
+        if self.samples.iter().any(|&bytes| bytes > 0) {
+            ui.ctx().request_repaint_after(SAMPLE_PERIOD);
+        }
+    }
+}
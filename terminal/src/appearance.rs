@@ -0,0 +1,68 @@
+use eframe::{egui::{self, FontId, TextStyle, Visuals}, epaint::FontFamily};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+
+/// name of text style frame lists are drawn with, its size is set by user
+const FRAME_FONT: &str = "frames";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    /// dark or light, as OS is set
+    #[default]
+    System,
+    Dark,
+    Light,
+}
+
+/// font of frame lists
+pub fn frame_font(style: &egui::Style) -> FontId {
+    TextStyle::Name(FRAME_FONT.into()).resolve(style)
+}
+
+/// sets theme, frame list font and scale from `settings`
+pub fn apply(ctx: &egui::Context, settings: &Settings, system_theme: Option<eframe::Theme>) {
+    let mut style = (*ctx.style()).clone();
+
+    style.visuals = match settings.theme {
+        Theme::System => system_theme.unwrap_or(eframe::Theme::Dark).egui_visuals(),
+        Theme::Dark => Visuals::dark(),
+        Theme::Light => Visuals::light(),
+    };
+    style.text_styles.insert(
+        TextStyle::Name(FRAME_FONT.into()),
+        FontId::new(settings.frame_font_size, FontFamily::Monospace),
+    );
+
+    ctx.set_style(style);
+    ctx.set_pixels_per_point(settings.pixels_per_point);
+}
+
+/// settings dialog contents, returns true if anything changed
+pub fn draw(ui: &mut egui::Ui, settings: &mut Settings) -> bool {
+    let mut changed = false;
+
+    egui::Grid::new("appearance").num_columns(2).show(ui, |ui| {
+        ui.label("Theme:");
+        ui.horizontal(|ui| {
+            for (theme, name) in [(Theme::System, "system"), (Theme::Dark, "dark"), (Theme::Light, "light")] {
+                changed |= ui.selectable_value(&mut settings.theme, theme, name).changed();
+            }
+        });
+        ui.end_row();
+
+        ui.label("Frame list font:");
+        changed |= ui.add(egui::DragValue::new(&mut settings.frame_font_size).clamp_range(8.0..=32.0).suffix(" pt"))
+            .changed();
+        ui.end_row();
+
+        ui.label("UI scale:");
+        // applied once dragging stops, rescaling under the pointer makes the slider jump around
+        let resp = ui.add(egui::Slider::new(&mut settings.pixels_per_point, 0.5..=3.0).step_by(0.05));
+        changed |= resp.drag_released() || (resp.changed() && !resp.dragged());
+        ui.end_row();
+    });
+
+    changed
+}
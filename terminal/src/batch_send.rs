@@ -0,0 +1,262 @@
+use std::{path::Path, sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}}, time::Duration};
+
+use eframe::egui::{self, DragValue};
+use proto_tools::capture::{Direction, Record};
+use serial_com::DeviceHandle;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use crate::{Context, Device};
+
+/// State of a single frame in the batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameStatus {
+    Queued,
+    Sent,
+    Failed(String),
+    /// not sent, because batch was aborted or previous frame failed
+    Aborted,
+    /// not sent, frame was received in the replayed session
+    Skipped,
+}
+
+/// When frames of the batch are sent
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Schedule {
+    /// fixed pause after every frame
+    Delay(Duration),
+    /// original gaps between sent frames of the capture, divided by `speed`
+    Recorded { speed: f64 },
+}
+
+/// Frames loaded from a file, sent one by one with a delay between them,
+/// or replayed with timing of the session they were captured in
+pub struct BatchSend {
+    /// file name
+    pub name: String,
+    pub records: Vec<Record>,
+    /// pause between frames, in milliseconds
+    pub delay_ms: u64,
+    /// replay sent frames with their original timing, instead of `delay_ms`
+    recorded_timing: bool,
+    /// multiplier of original timing, 2.0 replays twice as fast
+    speed: f64,
+    /// set once sending starts
+    run: Option<Arc<BatchRun>>,
+}
+
+/// progress of batch being sent in background
+struct BatchRun {
+    /// one per frame
+    status: Mutex<Vec<FrameStatus>>,
+    done: AtomicBool,
+    cancel: CancellationToken,
+}
+
+impl BatchSend {
+    /// loads frames from capture file (hex dump, JSONL, CSV, pcapng or pcap),
+    /// direction of records matters only when replaying with recorded timing
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let records = proto_tools::capture::read(path, None)?;
+
+        anyhow::ensure!(!records.is_empty(), "no frames in {}", path.display());
+
+        Ok(Self {
+            name: path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            records,
+            delay_ms: 100,
+            recorded_timing: false,
+            speed: 1.0,
+            run: None,
+        })
+    }
+
+    /// capture has timestamps and frames sent in it, hex dumps have neither
+    fn can_replay(&self) -> bool {
+        self.records.iter().any(|record| record.direction == Direction::Tx && record.timestamp_us != 0)
+    }
+
+    fn schedule(&self) -> Schedule {
+        if self.recorded_timing && self.can_replay() {
+            Schedule::Recorded { speed: self.speed }
+        } else {
+            Schedule::Delay(Duration::from_millis(self.delay_ms))
+        }
+    }
+
+    pub fn start(&mut self, ctx: &Arc<Context>, handle: DeviceHandle) {
+        let run = Arc::new(BatchRun {
+            status: Mutex::new(vec![FrameStatus::Queued; self.records.len()]),
+            done: AtomicBool::new(false),
+            cancel: CancellationToken::new(),
+        });
+
+        ctx.runtime.spawn(Self::run(ctx.clone(), handle, self.records.clone(), self.schedule(), run.clone()));
+        self.run = Some(run);
+    }
+
+    /// stops sending, remaining frames are marked as aborted
+    pub fn abort(&self) {
+        if let Some(run) = self.run.as_ref() {
+            run.cancel.cancel();
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.run.as_ref().is_some_and(|run| !run.done.load(Ordering::Relaxed))
+    }
+
+    fn status(&self) -> Vec<FrameStatus> {
+        match self.run.as_ref() {
+            Some(run) => run.status.lock().unwrap().clone(),
+            None => vec![FrameStatus::Queued; self.records.len()],
+        }
+    }
+
+    /// draws controls and the queue
+    pub fn draw(&mut self, ui: &mut egui::Ui, ctx: &Arc<Context>, handle: DeviceHandle) {
+        let status = self.status();
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{}, {} frames", self.name, self.records.len()));
+
+            if self.is_running() {
+                let sent = status.iter().filter(|s| **s == FrameStatus::Sent).count();
+                ui.label(format!("{} sent", sent));
+
+                if ui.button("Abort").clicked() {
+                    self.abort();
+                }
+            } else {
+                ui.add_enabled(self.can_replay(), egui::Checkbox::new(&mut self.recorded_timing, "recorded timing"))
+                    .on_hover_text("replay only frames sent in the capture, keeping original gaps between them")
+                    .on_disabled_hover_text("capture has no timestamps or no sent frames");
+
+                if let Schedule::Recorded { .. } = self.schedule() {
+                    ui.label("speed:");
+                    ui.add(DragValue::new(&mut self.speed).clamp_range(0.01..=100.0).speed(0.05).prefix("×"));
+                } else {
+                    ui.label("delay:");
+                    ui.add(DragValue::new(&mut self.delay_ms).suffix(" ms"));
+                }
+
+                let label = if self.run.is_some() { "Send again" } else { "Send" };
+                if ui.button(label).clicked() {
+                    self.start(ctx, handle);
+                }
+            }
+        });
+
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("batch").striped(true).show(ui, |ui| {
+                for (i, (record, status)) in self.records.iter().zip(&status).enumerate() {
+                    let frame = &record.frame;
+                    ui.label(format!("{}", i + 1));
+                    ui.label(format!("S:{} R:{}", frame.sender, frame.receiver));
+                    ui.monospace(proto_tools::bytes::format_hex(&frame.data));
+
+                    match status {
+                        FrameStatus::Queued => ui.label("queued"),
+                        FrameStatus::Sent => ui.label("sent"),
+                        FrameStatus::Failed(err) => ui.colored_label(ui.visuals().error_fg_color, "failed")
+                            .on_hover_text(err),
+                        FrameStatus::Aborted => ui.weak("aborted"),
+                        FrameStatus::Skipped => ui.weak("skipped")
+                            .on_hover_text("received in the capture"),
+                    };
+
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    async fn run(ctx: Arc<Context>, handle: DeviceHandle, records: Vec<Record>, schedule: Schedule, run: Arc<BatchRun>) {
+        let set_status = |i: usize, status: FrameStatus| {
+            run.status.lock().unwrap()[i] = status;
+            ctx.egui_ctx.request_repaint();
+        };
+
+        let replayed = |record: &Record| match schedule {
+            Schedule::Delay(_) => true,
+            Schedule::Recorded { .. } => record.direction == Direction::Tx,
+        };
+
+        // offsets are measured from the first sent frame, so replay starts right away
+        let start = Instant::now();
+        let first_us = records.iter()
+            .find(|record| replayed(record))
+            .map_or(0, |record| record.timestamp_us);
+
+        let mut failed = false;
+        let mut sent_any = false;
+
+        for (i, record) in records.into_iter().enumerate() {
+            if !replayed(&record) {
+                set_status(i, FrameStatus::Skipped);
+                continue;
+            }
+
+            if failed || run.cancel.is_cancelled() {
+                set_status(i, FrameStatus::Aborted);
+                continue;
+            }
+
+            let deadline = match schedule {
+                Schedule::Delay(delay) => sent_any.then(|| Instant::now() + delay),
+                Schedule::Recorded { speed } => {
+                    let offset = Duration::from_micros(record.timestamp_us.saturating_sub(first_us));
+                    Some(start + offset.div_f64(speed))
+                },
+            };
+
+            if let Some(deadline) = deadline {
+                tokio::select! {
+                    _ = run.cancel.cancelled() => {
+                        set_status(i, FrameStatus::Aborted);
+                        continue;
+                    },
+                    _ = tokio::time::sleep_until(deadline) => (),
+                }
+            }
+
+            sent_any = true;
+
+            match ctx.send_frame(handle, record.frame).await {
+                Ok(()) => set_status(i, FrameStatus::Sent),
+                Err(err) => {
+                    set_status(i, FrameStatus::Failed(format!("{:#}", err)));
+                    failed = true;
+                },
+            }
+        }
+
+        run.done.store(true, Ordering::Relaxed);
+        ctx.egui_ctx.request_repaint();
+    }
+}
+
+/// shows batch window of `device`, if a batch is loaded, closing it aborts sending
+pub fn show(ctx: &egui::Context, app_ctx: &Arc<Context>, device: &mut Device) {
+    let (title, handle) = (device.title(), device.handle);
+    let Some(batch) = device.batch.as_mut() else {
+        return;
+    };
+
+    let mut open = true;
+
+    egui::Window::new(format!("Batch - {}", title))
+        .id(egui::Id::new(("batch", handle)))
+        .open(&mut open)
+        .default_size([500.0, 400.0])
+        .show(ctx, |ui| batch.draw(ui, app_ctx, handle));
+
+    if !open {
+        batch.abort();
+        device.batch = None;
+    }
+}
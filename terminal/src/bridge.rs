@@ -0,0 +1,78 @@
+//! Sharing frames of published devices with other tools, see `ws_bridge`, `mqtt_bridge` and `rest_api`
+
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use proto::Frame;
+use proto_tools::capture::Record;
+use serde::{Deserialize, Serialize};
+use serial_com::DeviceHandle;
+
+use crate::Context;
+
+/// frames buffered for a slow bridge client, before it starts missing them
+pub const EVENT_CAPACITY: usize = 1024;
+
+/// Frame sent or received by a published device,
+/// serialized with the same fields as in `jsonl` captures, plus `device` with port name
+#[derive(Debug, Clone, Serialize)]
+pub struct BridgeEvent {
+    pub device: String,
+    #[serde(flatten)]
+    pub record: Record,
+}
+
+/// Frame to send, requested by bridge client
+#[derive(Debug, Deserialize)]
+pub struct FrameRequest {
+    pub sender: u8,
+    pub receiver: u8,
+    /// payload as hex
+    pub data: String,
+}
+
+/// Frame to send to device named `device`
+#[derive(Debug, Deserialize)]
+pub struct Transmit {
+    pub device: String,
+    #[serde(flatten)]
+    pub frame: FrameRequest,
+}
+
+/// Published device, as listed to bridge clients
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeviceInfo {
+    #[serde(skip)]
+    pub handle: DeviceHandle,
+    /// port name, identifies device in requests
+    pub device: String,
+    /// name given by user, empty if there is none
+    pub alias: String,
+}
+
+impl FrameRequest {
+    pub fn frame(&self) -> anyhow::Result<Frame> {
+        Ok(Frame {
+            sender: self.sender,
+            receiver: self.receiver,
+            data: proto_tools::bytes::parse_hex(&self.data)?,
+        })
+    }
+}
+
+/// devices with publishing enabled, sorted by port name
+pub fn published(ctx: &Context) -> Vec<DeviceInfo> {
+    ctx.published.borrow().clone()
+}
+
+/// sends `frame` to published device named `device`
+pub async fn transmit(ctx: &Arc<Context>, device: &str, frame: Frame) -> anyhow::Result<()> {
+    let handle = ctx.published
+        .borrow()
+        .iter()
+        .find(|dev| dev.device == device)
+        .map(|dev| dev.handle)
+        .with_context(|| format!("no published device `{}`", device))?;
+
+    ctx.send_frame(handle, frame).await
+}
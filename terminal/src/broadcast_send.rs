@@ -0,0 +1,81 @@
+use std::{collections::HashSet, sync::Arc};
+
+use eframe::egui;
+use proto::Frame;
+use serial_com::DeviceHandle;
+
+use crate::{Context, composer::Composer, settings::PortSettings};
+
+/// Window sending the same frame to many open devices at once, e.g. to synchronize boards of a test rig
+pub struct Broadcast {
+    pub open: bool,
+    composer: Composer,
+    /// devices left out, new ones are included
+    excluded: HashSet<DeviceHandle>,
+}
+
+impl Default for Broadcast {
+    fn default() -> Self {
+        Self {
+            open: false,
+            composer: Composer::new(PortSettings::default()),
+            excluded: HashSet::new(),
+        }
+    }
+}
+
+impl Broadcast {
+    /// `targets` are devices with a port, with their titles
+    pub fn show(&mut self, ctx: &egui::Context, app_ctx: &Arc<Context>, targets: &[(DeviceHandle, String)]) {
+        let mut send = None;
+
+        egui::Window::new("Broadcast")
+            .open(&mut self.open)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("to:");
+
+                    if targets.is_empty() {
+                        ui.weak("no open devices");
+                    }
+
+                    for (handle, title) in targets {
+                        let mut included = !self.excluded.contains(handle);
+                        if ui.checkbox(&mut included, title).changed() {
+                            if included {
+                                self.excluded.remove(handle);
+                            } else {
+                                self.excluded.insert(*handle);
+                            }
+                        }
+                    }
+                });
+
+                ui.separator();
+                send = self.composer.draw(ui, !targets.is_empty());
+            });
+
+        let Some(frame) = send else {
+            return;
+        };
+
+        let handles = targets
+            .iter()
+            .map(|(handle, _)| *handle)
+            .filter(|handle| !self.excluded.contains(handle))
+            .collect::<Vec<_>>();
+
+        // sent to all devices at once, instead of one after another
+        app_ctx.spawn({
+            let app_ctx = app_ctx.clone();
+            async move { send_all(&app_ctx, handles, frame).await }
+        });
+    }
+}
+
+async fn send_all(ctx: &Context, handles: Vec<DeviceHandle>, frame: Frame) -> anyhow::Result<()> {
+    futures_util::future::try_join_all(handles.into_iter().map(|handle| ctx.send_frame(handle, frame.clone()))).await?;
+
+    Ok(())
+}
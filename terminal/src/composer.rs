@@ -0,0 +1,97 @@
+use anyhow::Context as _;
+use eframe::{egui::{self, TextBuffer, TextEdit}, epaint::FontId};
+use egui_number_buffer::NumberBuffer;
+use proto::Frame;
+
+use crate::{InputMode, inspector, settings::PortSettings};
+
+/// Panel building a frame field by field, with its wire bytes shown as it's typed
+pub struct Composer {
+    pub sender: NumberBuffer<3>,
+    pub receiver: NumberBuffer<3>,
+    pub mode: InputMode,
+    pub payload: String,
+}
+
+impl Composer {
+    pub fn new(addresses: PortSettings) -> Self {
+        Self {
+            sender: NumberBuffer::new(&addresses.sender.to_string()),
+            receiver: NumberBuffer::new(&addresses.receiver.to_string()),
+            mode: InputMode::Hex,
+            payload: String::new(),
+        }
+    }
+
+    pub fn frame(&self) -> anyhow::Result<Frame> {
+        let parse = |buf: &NumberBuffer<3>, what: &str| {
+            buf.as_str()
+                .parse::<u8>()
+                .with_context(|| format!("invalid {} address `{}`, expected 0-255", what, buf.as_str()))
+        };
+
+        let data = match self.mode {
+            InputMode::Text => self.payload.clone().into_bytes(),
+            InputMode::Hex => proto_tools::bytes::parse_hex(&self.payload)?,
+        };
+
+        Ok(Frame {
+            sender: parse(&self.sender, "sender")?,
+            receiver: parse(&self.receiver, "receiver")?,
+            data,
+        })
+    }
+
+    /// inputs and preview, returns composed frame when it should be sent, only offered if `can_send`
+    pub fn draw(&mut self, ui: &mut egui::Ui, can_send: bool) -> Option<Frame> {
+        let frame = self.frame();
+        let mut send = None;
+
+        ui.horizontal(|ui| {
+            ui.label("S:");
+            ui.add(TextEdit::singleline(&mut self.sender).desired_width(24.0))
+                .on_hover_text("sender address (0-255)");
+            ui.label("R:");
+            ui.add(TextEdit::singleline(&mut self.receiver).desired_width(24.0))
+                .on_hover_text("receiver address (0-255)");
+
+            ui.separator();
+            ui.selectable_value(&mut self.mode, InputMode::Text, "Text");
+            ui.selectable_value(&mut self.mode, InputMode::Hex, "Hex");
+
+            if can_send && ui.add_enabled(frame.is_ok(), egui::Button::new("Send")).clicked() {
+                send = frame.as_ref().ok().cloned();
+            }
+        });
+
+        ui.add(TextEdit::multiline(&mut self.payload)
+            .font(egui::TextStyle::Monospace)
+            .desired_rows(3)
+            .desired_width(f32::INFINITY)
+            .hint_text(match self.mode {
+                InputMode::Text => "payload text",
+                InputMode::Hex => "payload hex bytes, e.g. 01 A0 FF",
+            }));
+
+        match frame.as_ref().map(|frame| (frame, frame.calculate_crc32(), inspector::wire_bytes(frame))) {
+            Ok((frame, Ok(crc), Ok(wire))) => {
+                ui.label(format!(
+                    "{} payload bytes, CRC32 {:08X}, {} wire bytes ({} escaped)",
+                    frame.data.len(),
+                    crc,
+                    wire.len(),
+                    wire.iter().filter(|b| b.escaped).count() / 2,
+                ));
+                ui.label(inspector::wire_layout(&wire, FontId::monospace(13.0), ui.visuals().text_color()));
+            },
+            Ok((_, Err(err), _) | (_, _, Err(err))) => {
+                ui.colored_label(ui.visuals().error_fg_color, err.to_string());
+            },
+            Err(err) => {
+                ui.colored_label(ui.visuals().error_fg_color, format!("{:#}", err));
+            },
+        }
+
+        send
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::VecDeque;
+
+use eframe::egui::{self, ComboBox};
+
+use crate::frame_list;
+
+/// received bytes kept for re-splitting, oldest are dropped first
+const MAX_BYTES: usize = 1 << 20;
+/// lines kept in the panel, oldest are dropped first
+const MAX_LINES: usize = 10_000;
+
+/// Terminator of lines sent or received in raw mode, like in classic serial terminals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    #[default]
+    None,
+    Lf,
+    CrLf,
+    Cr,
+}
+
+impl LineEnding {
+    const ALL: [LineEnding; 4] = [LineEnding::None, LineEnding::Lf, LineEnding::CrLf, LineEnding::Cr];
+
+    pub fn bytes(&self) -> &'static [u8] {
+        match self {
+            LineEnding::None => b"",
+            LineEnding::Lf => b"\n",
+            LineEnding::CrLf => b"\r\n",
+            LineEnding::Cr => b"\r",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LineEnding::None => "none",
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+            LineEnding::Cr => "CR",
+        }
+    }
+
+    /// picker of line ending, `id_source` has to be unique in the window, returns true if it changed
+    pub fn draw(&mut self, ui: &mut egui::Ui, id_source: impl std::hash::Hash) -> bool {
+        let previous = *self;
+
+        ComboBox::from_id_source(id_source)
+            .width(60.0)
+            .selected_text(self.name())
+            .show_ui(ui, |ui| {
+                for ending in LineEnding::ALL {
+                    ui.selectable_value(self, ending, ending.name());
+                }
+            });
+
+        *self != previous
+    }
+}
+
+/// received bytes, without the terminator
+struct Line {
+    /// time first byte of the line was received
+    timestamp_us: u64,
+    bytes: Vec<u8>,
+    /// terminator was received, following bytes start a new line
+    complete: bool,
+}
+
+/// Everything the device sent, framed or not, as text split on line endings
+#[derive(Default)]
+pub struct RawConsole {
+    /// bytes as they were read, with time of the read
+    chunks: VecDeque<(u64, Vec<u8>)>,
+    chunk_bytes: usize,
+    lines: VecDeque<Line>,
+    split: LineEnding,
+}
+
+impl RawConsole {
+    pub fn push(&mut self, timestamp_us: u64, data: &[u8]) {
+        self.chunks.push_back((timestamp_us, data.to_vec()));
+        self.chunk_bytes += data.len();
+
+        while self.chunk_bytes > MAX_BYTES {
+            let Some((_, chunk)) = self.chunks.pop_front() else {
+                break;
+            };
+            self.chunk_bytes -= chunk.len();
+        }
+
+        self.split_chunk(timestamp_us, data);
+    }
+
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.chunk_bytes = 0;
+        self.lines.clear();
+    }
+
+    fn split_chunk(&mut self, timestamp_us: u64, data: &[u8]) {
+        // without terminator every read is its own line
+        if self.split == LineEnding::None {
+            self.lines.push_back(Line { timestamp_us, bytes: data.to_vec(), complete: true });
+        } else {
+            let terminator = self.split.bytes();
+
+            for &byte in data {
+                let unterminated = self.lines.back().is_some_and(|line| !line.complete);
+                if !unterminated {
+                    self.lines.push_back(Line { timestamp_us, bytes: Vec::new(), complete: false });
+                }
+
+                let line = self.lines.back_mut().unwrap();
+                line.bytes.push(byte);
+
+                // checked byte by byte, CRLF can be split between reads
+                if line.bytes.ends_with(terminator) {
+                    line.bytes.truncate(line.bytes.len() - terminator.len());
+                    line.complete = true;
+                }
+            }
+        }
+
+        while self.lines.len() > MAX_LINES {
+            self.lines.pop_front();
+        }
+    }
+
+    /// splits kept bytes again, after line ending was changed
+    fn resplit(&mut self) {
+        self.lines.clear();
+
+        let chunks = std::mem::take(&mut self.chunks);
+        for (timestamp_us, data) in &chunks {
+            self.split_chunk(*timestamp_us, data);
+        }
+        self.chunks = chunks;
+    }
+
+    pub fn draw(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("split on:");
+            if self.split.draw(ui, "console split") {
+                self.resplit();
+            }
+
+            if ui.button("Clear").clicked() {
+                self.clear();
+            }
+        });
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+
+        egui::ScrollArea::both()
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .show_rows(ui, row_height, self.lines.len(), |ui, rows| {
+                for line in self.lines.range(rows) {
+                    ui.horizontal(|ui| {
+                        ui.weak(frame_list::format_time(line.timestamp_us));
+                        ui.monospace(escape(&line.bytes));
+                    });
+                }
+            });
+    }
+}
+
+/// bytes as text, control characters are escaped so they stay visible
+fn escape(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .chars()
+        .map(|c| if c.is_control() { c.escape_default().to_string() } else { c.to_string() })
+        .collect()
+}
@@ -0,0 +1,66 @@
+use base64::Engine;
+
+/// Text representation bytes are copied to clipboard in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyFormat {
+    /// `01a0ff`
+    Hex,
+    /// `01 A0 FF`
+    SpacedHex,
+    /// `uint8_t data[] = { 0x01, 0xA0, 0xFF };`
+    C,
+    /// `&[0x01, 0xA0, 0xFF]`
+    Rust,
+    /// `b'\x01\xa0\xff'`
+    Python,
+    Base64,
+}
+
+impl CopyFormat {
+    pub const ALL: [CopyFormat; 6] = [
+        CopyFormat::Hex,
+        CopyFormat::SpacedHex,
+        CopyFormat::C,
+        CopyFormat::Rust,
+        CopyFormat::Python,
+        CopyFormat::Base64,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CopyFormat::Hex => "hex",
+            CopyFormat::SpacedHex => "spaced hex",
+            CopyFormat::C => "C uint8_t[]",
+            CopyFormat::Rust => "Rust &[u8]",
+            CopyFormat::Python => "Python bytes",
+            CopyFormat::Base64 => "base64",
+        }
+    }
+
+    pub fn format(&self, bytes: &[u8]) -> String {
+        let literals = || bytes
+            .iter()
+            .map(|b| format!("0x{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        match self {
+            CopyFormat::Hex => bytes.iter().map(|b| format!("{:02x}", b)).collect(),
+            CopyFormat::SpacedHex => proto_tools::bytes::format_hex(bytes),
+            CopyFormat::C => format!("uint8_t data[] = {{ {} }};", literals()),
+            CopyFormat::Rust => format!("&[{}]", literals()),
+            CopyFormat::Python => format!("b'{}'", bytes.iter().map(|b| format!("\\x{:02x}", b)).collect::<String>()),
+            CopyFormat::Base64 => base64::engine::general_purpose::STANDARD.encode(bytes),
+        }
+    }
+}
+
+/// puts `bytes` in clipboard, formatted as `format`
+pub fn copy(bytes: &[u8], format: CopyFormat) {
+    let result = arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(format.format(bytes)));
+
+    if let Err(err) = result {
+        log::warn!("unable to copy to clipboard: {}", err);
+    }
+}
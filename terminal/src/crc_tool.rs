@@ -0,0 +1,61 @@
+use crc::{Crc, CRC_32_MPEG_2};
+use eframe::egui::{self, TextEdit};
+
+/// Window computing CRC32 of pasted bytes, the way frames are checked
+#[derive(Debug, Default)]
+pub struct CrcCalculator {
+    pub open: bool,
+    /// hex bytes
+    input: String,
+}
+
+impl CrcCalculator {
+    pub fn show(&mut self, ctx: &egui::Context) {
+        egui::Window::new("CRC calculator")
+            .open(&mut self.open)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                ui.weak("hashed fields of a frame are SENDER RECEIVER DATA_LEN (big endian) DATA");
+                ui.add(TextEdit::multiline(&mut self.input)
+                    .desired_width(f32::INFINITY)
+                    .desired_rows(3)
+                    .font(egui::TextStyle::Monospace)
+                    .hint_text("hex bytes, e.g. 01 02 00 01 FF"));
+
+                let bytes = match proto_tools::bytes::parse_hex(&self.input) {
+                    Ok(bytes) => bytes,
+                    Err(err) => {
+                        ui.colored_label(ui.visuals().error_fg_color, format!("{:#}", err));
+                        return;
+                    },
+                };
+
+                let crc = proto::crc32(&bytes);
+                let unpadded = Crc::<u32>::new(&CRC_32_MPEG_2).checksum(&bytes);
+
+                egui::Grid::new("crc").num_columns(2).show(ui, |ui| {
+                    ui.label("bytes:");
+                    ui.monospace(format!("{} + {} padding", bytes.len(), (4 - bytes.len() % 4) % 4));
+                    ui.end_row();
+
+                    ui.label("CRC32:");
+                    ui.horizontal(|ui| {
+                        ui.monospace(format!("0x{:08X}", crc));
+                        if ui.small_button("Copy").clicked() {
+                            ui.output_mut(|o| o.copied_text = format!("{:08X}", crc));
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("on the wire:");
+                    ui.monospace(proto_tools::bytes::format_hex(&crc.to_be_bytes()));
+                    ui.end_row();
+
+                    ui.label("without padding:")
+                        .on_hover_text("what CRC-32/MPEG-2 gives without zero padding to whole words, if it matches the device, its padding is off");
+                    ui.monospace(format!("0x{:08X}", unpadded));
+                    ui.end_row();
+                });
+            });
+    }
+}
@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+use eframe::egui::{self, LayerId, Order};
+use serial_com::ConnectionState;
+
+use crate::Device;
+
+/// frame rate is averaged over this many last microseconds
+const RATE_WINDOW_US: u64 = 5_000_000;
+/// payload bytes shown in preview of the last frame
+const PREVIEW_LEN: usize = 12;
+
+/// Window with one row per open device, so several boards can be supervised without all their windows in view
+#[derive(Debug, Default)]
+pub struct Dashboard {
+    pub open: bool,
+}
+
+impl Dashboard {
+    pub fn show<'a>(&mut self, ctx: &egui::Context, devices: impl Iterator<Item = &'a Device>) {
+        let mut devices = devices.collect::<Vec<_>>();
+        devices.sort_by_key(|device| device.title());
+
+        egui::Window::new("Dashboard")
+            .open(&mut self.open)
+            .default_width(700.0)
+            .show(ctx, |ui| {
+                if devices.is_empty() {
+                    ui.weak("no open devices");
+                    return;
+                }
+
+                egui::Grid::new("dashboard")
+                    .num_columns(6)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("device");
+                        ui.strong("state");
+                        ui.strong("rx frames/s").on_hover_text(format!("received in the last {} s", RATE_WINDOW_US / 1_000_000));
+                        ui.strong("tx frames/s");
+                        ui.strong("errors").on_hover_text("received frames that were discarded, see stats panel of the device");
+                        ui.strong("last received");
+                        ui.end_row();
+
+                        for device in devices {
+                            draw_row(ui, device);
+                            ui.end_row();
+                        }
+                    });
+
+                // keeps rates current while nothing is received
+                ui.ctx().request_repaint_after(Duration::from_secs(1));
+            });
+    }
+}
+
+fn draw_row(ui: &mut egui::Ui, device: &Device) {
+    let now_us = proto_tools::capture::now_us();
+
+    // clicking name brings window of the device to front
+    if ui.link(device.title()).on_hover_text("show device window").clicked() {
+        if device.detached {
+            let viewport = egui::ViewportId::from_hash_of(("device", device.handle));
+            ui.ctx().send_viewport_cmd_to(viewport, egui::ViewportCommand::Focus);
+        } else {
+            ui.ctx().move_to_top(LayerId::new(Order::Middle, egui::Id::new(device.handle)));
+        }
+    }
+
+    let error_color = ui.visuals().error_fg_color;
+    match (device.capture.is_some(), device.connection) {
+        (true, _) => ui.weak("capture"),
+        (false, ConnectionState::Connected) if device.config.monitor => ui.label("monitoring"),
+        (false, ConnectionState::Connected) => ui.label("connected"),
+        (false, ConnectionState::Reconnecting { attempt, .. }) => ui.colored_label(error_color, format!("reconnecting ({})", attempt)),
+        (false, ConnectionState::Failed) => ui.colored_label(error_color, "disconnected"),
+    };
+
+    let rate = |frames: &std::collections::VecDeque<crate::DrawableFrame>| {
+        let recent = frames
+            .iter()
+            .rev()
+            .take_while(|frame| frame.timestamp_us + RATE_WINDOW_US >= now_us)
+            .count();
+
+        format!("{:.1}", recent as f64 * 1e6 / RATE_WINDOW_US as f64)
+    };
+    ui.monospace(rate(&device.received));
+    ui.monospace(rate(&device.sent));
+
+    let discarded = device.decoder.discarded.values().sum::<u64>() + device.decoder.stats.overflows;
+    if discarded == 0 {
+        ui.monospace("0");
+    } else {
+        let hint = device.decoder.discarded
+            .iter()
+            .map(|(kind, count)| format!("{}: {}", kind, count))
+            .chain((device.decoder.stats.overflows > 0).then(|| format!("over length limit: {}", device.decoder.stats.overflows)))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        ui.colored_label(error_color, egui::RichText::new(discarded.to_string()).monospace()).on_hover_text(hint);
+    }
+
+    match device.received.iter().rev().find(|frame| frame.discarded.is_none()) {
+        Some(frame) => {
+            let data = &frame.inner.data;
+            let mut preview = format!(
+                "{} → {}  {}",
+                frame.inner.sender,
+                frame.inner.receiver,
+                proto_tools::bytes::format_hex(&data[..data.len().min(PREVIEW_LEN)]),
+            );
+
+            if data.len() > PREVIEW_LEN {
+                preview.push('…');
+            }
+
+            let age = now_us.saturating_sub(frame.timestamp_us) as f64 / 1e6;
+            ui.monospace(preview).on_hover_text(format!("{:.1} s ago", age));
+        },
+        None => {
+            ui.weak("nothing received");
+        },
+    }
+}
@@ -0,0 +1,67 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use eframe::egui;
+use proto::DecoderStats;
+
+/// State of the frame parser of a device, to tell "nothing arriving" apart from "garbage arriving"
+#[derive(Debug, Default)]
+pub struct DecoderState {
+    pub stats: DecoderStats,
+    /// bytes of unfinished frame
+    pub buffered: usize,
+    /// received frames that failed to deserialize, by `DeserializeError::kind`
+    pub discarded: BTreeMap<&'static str, u64>,
+    /// time of the last read from the port
+    pub last_read_us: Option<u64>,
+}
+
+impl DecoderState {
+    /// takes state of the decoder after it was given bytes read at `timestamp_us`
+    pub fn update(&mut self, stats: DecoderStats, buffered: usize, timestamp_us: u64) {
+        self.stats = stats;
+        self.buffered = buffered;
+        self.last_read_us = Some(timestamp_us);
+    }
+
+    pub fn discarded(&mut self, kind: &'static str) {
+        *self.discarded.entry(kind).or_default() += 1;
+    }
+
+    pub fn draw(&self, ui: &mut egui::Ui) {
+        let now_us = proto_tools::capture::now_us();
+
+        egui::Grid::new("decoder")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("last data");
+                match self.last_read_us {
+                    Some(last) => ui.monospace(format!("{:.1} s ago", now_us.saturating_sub(last) as f64 / 1e6)),
+                    None => ui.weak("nothing received"),
+                };
+                ui.end_row();
+
+                let rows = [
+                    ("waiting for end byte", self.buffered as u64, "bytes of unfinished frame"),
+                    ("bytes outside frames", self.stats.skipped_bytes, "received while no frame was started, e.g. noise or wrong baud rate"),
+                    ("resyncs", self.stats.resyncs, "unfinished frames abandoned, because another one started"),
+                    ("over length limit", self.stats.overflows, "unfinished frames dropped for exceeding maximum frame length"),
+                ];
+
+                for (name, value, hint) in rows {
+                    ui.label(name).on_hover_text(hint);
+                    ui.monospace(value.to_string());
+                    ui.end_row();
+                }
+
+                for (kind, count) in &self.discarded {
+                    ui.label(format!("discarded, {}", kind));
+                    ui.monospace(count.to_string());
+                    ui.end_row();
+                }
+            });
+
+        // keeps time since last data current
+        ui.ctx().request_repaint_after(Duration::from_secs(1));
+    }
+}
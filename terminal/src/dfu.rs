@@ -0,0 +1,236 @@
+//! Guided firmware update, streaming an image with [`proto::transfer`]
+
+use std::{path::PathBuf, sync::Arc};
+
+use eframe::egui::{self, DragValue, TextEdit};
+use proto::{Frame, transfer::{Message, Target}};
+
+use crate::{Context, Device, file_send::{FileOptions, FileSend}};
+
+/// Firmware update dialog of a device
+pub struct Dfu {
+    pub open: bool,
+    image: Option<Image>,
+    /// CRC image is expected to have, e.g. printed by the build, in hex, empty to not check it
+    expected_crc: String,
+    /// largest image the device takes, 0 for any
+    max_size: u32,
+    chunk_size: u16,
+    retries: u32,
+    send: Option<Arc<FileSend>>,
+}
+
+/// firmware image picked to be sent
+struct Image {
+    path: PathBuf,
+    data: Vec<u8>,
+    crc32: u32,
+}
+
+impl Default for Dfu {
+    fn default() -> Self {
+        Self {
+            open: false,
+            image: None,
+            expected_crc: String::new(),
+            max_size: 0,
+            chunk_size: 256,
+            retries: 3,
+            send: None,
+        }
+    }
+}
+
+impl Dfu {
+    pub fn is_running(&self) -> bool {
+        self.send.as_ref().is_some_and(|send| !send.is_done())
+    }
+
+    pub fn cancel(&self) {
+        if let Some(send) = self.send.as_ref() {
+            send.cancel();
+        }
+    }
+
+    /// passes `frame` received by the device to the update waiting for replies
+    pub fn received(&self, frame: &Frame) {
+        if let Some(send) = self.send.as_ref() {
+            send.received(frame);
+        }
+    }
+
+    fn pick_image(&mut self) -> anyhow::Result<()> {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("firmware image", &["bin"])
+            .pick_file()
+        else {
+            return Ok(());
+        };
+
+        let data = std::fs::read(&path)?;
+        self.image = Some(Image { crc32: proto::crc32(&data), path, data });
+        self.send = None;
+
+        Ok(())
+    }
+
+    /// problems found with picked image, it's not sent while there are some
+    fn check(&self, image: &Image) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if image.data.is_empty() {
+            problems.push("image is empty".to_owned());
+        }
+
+        if self.max_size != 0 && image.data.len() > self.max_size as usize {
+            problems.push(format!("image has {} bytes, device takes at most {}", image.data.len(), self.max_size));
+        }
+
+        match u32::from_str_radix(self.expected_crc.trim().trim_start_matches("0x"), 16) {
+            _ if self.expected_crc.trim().is_empty() => (),
+            Ok(expected) if expected == image.crc32 => (),
+            Ok(expected) => problems.push(format!("CRC is {:08X}, expected {:08X}", image.crc32, expected)),
+            Err(_) => problems.push(format!("expected CRC `{}` isn't a hex number", self.expected_crc)),
+        }
+
+        problems
+    }
+
+    /// starts streaming picked image to the device
+    fn start(&mut self, ctx: &Arc<Context>, device: &Device) -> anyhow::Result<()> {
+        let image = self.image.as_ref().ok_or_else(|| anyhow::anyhow!("no image picked"))?;
+        let addresses = device.port_settings()?;
+
+        let options = FileOptions {
+            chunk_size: Some(self.chunk_size as usize),
+            sender: addresses.sender,
+            receiver: addresses.receiver,
+            retries: self.retries,
+            acknowledged: true,
+            target: Target::Firmware,
+        };
+
+        let name = image.path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        self.send = Some(FileSend::start_with(ctx, device.handle, name, image.data.clone(), options)?);
+        Ok(())
+    }
+
+    /// draws steps of the update, returns true when sending was requested
+    fn draw(&mut self, ui: &mut egui::Ui, ctx: &Arc<Context>, device: &Device) -> bool {
+        let running = self.is_running();
+        let mut start = false;
+
+        ui.strong("1. Image");
+        ui.add_enabled_ui(!running, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("Pick image").clicked() {
+                    let _ = ctx.report_error(self.pick_image());
+                }
+
+                match self.image.as_ref() {
+                    Some(image) => ui.label(image.path.display().to_string()),
+                    None => ui.weak("no image picked"),
+                };
+            });
+        });
+
+        ui.separator();
+        ui.strong("2. Verify");
+        ui.add_enabled_ui(!running, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("expected CRC:");
+                ui.add(TextEdit::singleline(&mut self.expected_crc).desired_width(80.0).hint_text("any"))
+                    .on_hover_text("CRC-32/MPEG-2 of the image zero padded to whole words, in hex, as the device checks it");
+                ui.add(DragValue::new(&mut self.max_size).prefix("max size: ").suffix(" B"))
+                    .on_hover_text("size of the update slot of the device, 0 to not check it");
+            });
+        });
+
+        let problems = match self.image.as_ref() {
+            Some(image) => {
+                ui.monospace(format!("{} bytes, CRC {:08X}", image.data.len(), image.crc32));
+                self.check(image)
+            },
+            None => vec!["no image picked".to_owned()],
+        };
+
+        for problem in &problems {
+            ui.colored_label(ui.visuals().error_fg_color, problem);
+        }
+
+        ui.separator();
+        ui.strong("3. Update");
+        ui.add_enabled_ui(!running, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(DragValue::new(&mut self.chunk_size).clamp_range(1..=Message::MAX_CHUNK_LEN).prefix("chunk: ").suffix(" B"));
+                ui.add(DragValue::new(&mut self.retries).clamp_range(0..=100).prefix("retries: "));
+
+                let button = ui.add_enabled(problems.is_empty() && device.can_send(), egui::Button::new("Update"))
+                    .on_hover_text("addresses are taken from the device window");
+                start = button.clicked();
+            });
+        });
+
+        let Some(send) = self.send.clone() else {
+            return start;
+        };
+
+        if send.draw(ui) {
+            send.resume(ctx, device.handle);
+        }
+
+        if send.is_done() && send.error().is_none() {
+            ui.colored_label(egui::Color32::GREEN, format!("device verified the image, CRC {:08X}", send.crc32));
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.strong("4. Log");
+
+            if ui.button("Copy").clicked() {
+                ui.output_mut(|o| o.copied_text = send.exchange().join("\n"));
+            }
+        });
+
+        egui::ScrollArea::vertical()
+            .max_height(200.0)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in send.exchange() {
+                    ui.monospace(line);
+                }
+            });
+
+        start
+    }
+}
+
+/// shows firmware update window of `device`, closing it cancels the update
+pub fn show(ctx: &egui::Context, app_ctx: &Arc<Context>, device: &mut Device) {
+    let mut open = device.dfu.open;
+    let mut start = false;
+
+    // the dialog takes the device to read addresses from
+    let mut dfu = std::mem::take(&mut device.dfu);
+
+    egui::Window::new(format!("Firmware update - {}", device.title()))
+        .id(egui::Id::new(("dfu", device.handle)))
+        .open(&mut open)
+        .default_width(360.0)
+        .show(ctx, |ui| start = dfu.draw(ui, app_ctx, device));
+
+    if start {
+        let result = dfu.start(app_ctx, device);
+        let _ = app_ctx.report_error(result);
+    }
+
+    if !open {
+        dfu.cancel();
+    }
+
+    dfu.open = open;
+    device.dfu = dfu;
+}
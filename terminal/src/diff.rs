@@ -0,0 +1,108 @@
+use eframe::{egui::{self, RichText}, epaint::{Color32, FontId, text::{LayoutJob, TextFormat}}};
+use proto::Frame;
+
+use crate::{Device, frame_list};
+
+/// bytes per row of payload diff
+const ROW_LEN: usize = 16;
+/// background of bytes that differ, or are missing in the other frame
+const CHANGED: Color32 = Color32::from_rgb(110, 40, 40);
+
+/// shows diff window, when device has both selected and compared frame
+pub fn show(ctx: &egui::Context, device: &mut Device) {
+    let (Some(a), Some(b)) = (device.selection.selected, device.selection.compare) else {
+        return;
+    };
+
+    let frames = device.find_frame(a).zip(device.find_frame(b));
+    let Some(((_, a), (_, b))) = frames.filter(|((_, a), (_, b))| a.discarded.is_none() && b.discarded.is_none()) else {
+        return;
+    };
+
+    let times = (a.timestamp_us, b.timestamp_us);
+    let (a, b) = (a.inner.clone(), b.inner.clone());
+    let mut open = true;
+
+    egui::Window::new(format!("Diff - {}", device.title()))
+        .id(egui::Id::new(("diff", device.handle)))
+        .open(&mut open)
+        .default_width(620.0)
+        .show(ctx, |ui| draw(ui, times, &a, &b));
+
+    if !open {
+        device.selection.compare = None;
+    }
+}
+
+/// header fields side by side, and payloads compared byte by byte at the same offsets
+pub fn draw(ui: &mut egui::Ui, times: (u64, u64), a: &Frame, b: &Frame) {
+    let error_color = ui.visuals().error_fg_color;
+
+    egui::Grid::new("header diff")
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            let row = |ui: &mut egui::Ui, name: &str, a: String, b: String| {
+                let color = (a != b).then_some(error_color);
+
+                ui.label(name);
+                ui.label(RichText::new(a).monospace());
+                ui.label(RichText::new(b).monospace().color(color.unwrap_or(ui.visuals().text_color())));
+                ui.end_row();
+            };
+
+            ui.label("");
+            ui.strong("A (selected)");
+            ui.strong("B (compared)");
+            ui.end_row();
+
+            let crc = |frame: &Frame| frame.calculate_crc32()
+                .map(|crc| format!("{:08X}", crc))
+                .unwrap_or_else(|err| err.to_string());
+
+            row(ui, "time", frame_list::format_date_time(times.0), frame_list::format_date_time(times.1));
+            row(ui, "sender", a.sender.to_string(), b.sender.to_string());
+            row(ui, "receiver", a.receiver.to_string(), b.receiver.to_string());
+            row(ui, "data length", a.data.len().to_string(), b.data.len().to_string());
+            row(ui, "crc32", crc(a), crc(b));
+        });
+
+    let changed = (0..a.data.len().max(b.data.len()))
+        .filter(|&i| a.data.get(i) != b.data.get(i))
+        .count();
+
+    ui.separator();
+    ui.label(format!("payload, {} bytes differ", changed));
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        ui.label(layout(&a.data, &b.data, FontId::monospace(13.0), ui.visuals().text_color()));
+    });
+}
+
+/// rows of A and B payload interleaved, differing bytes highlighted
+fn layout(a: &[u8], b: &[u8], font_id: FontId, text_color: Color32) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    let plain = TextFormat::simple(font_id.clone(), text_color);
+    let rows = a.len().max(b.len()).div_ceil(ROW_LEN);
+
+    for row in 0..rows {
+        for (name, data, other) in [("A", a, b), ("B", b, a)] {
+            job.append(&format!("{:04X} {} ", row * ROW_LEN, name), 0.0, TextFormat::simple(font_id.clone(), Color32::DARK_GRAY));
+
+            for i in row * ROW_LEN..(row + 1) * ROW_LEN {
+                let text = data.get(i).map_or_else(|| "  ".to_owned(), |byte| format!("{:02X}", byte));
+                let format = TextFormat {
+                    background: if data.get(i).is_some() && data.get(i) != other.get(i) { CHANGED } else { Color32::TRANSPARENT },
+                    ..plain.clone()
+                };
+
+                job.append(&text, 0.0, format);
+                job.append(" ", 0.0, plain.clone());
+            }
+
+            job.append("\n", 0.0, plain.clone());
+        }
+    }
+
+    job
+}
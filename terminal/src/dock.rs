@@ -0,0 +1,179 @@
+use std::sync::Arc;
+
+use eframe::egui::{self, WidgetText};
+use egui_dock::{DockState, NodeIndex, TabViewer};
+use proto::Frame;
+use proto_tools::capture::Direction;
+
+use crate::{Context, Device, frame_list::FrameList, inspector};
+
+/// Panel of device window, can be moved, resized and stacked by dragging its tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeviceTab {
+    Sent,
+    Received,
+    Inspector,
+    Plot,
+    Stats,
+    Latency,
+    Composer,
+    Watches,
+    Throughput,
+    Console,
+}
+
+impl DeviceTab {
+    /// panels that can be closed and opened again
+    pub const OPTIONAL: [DeviceTab; 8] = [
+        DeviceTab::Inspector,
+        DeviceTab::Plot,
+        DeviceTab::Stats,
+        DeviceTab::Latency,
+        DeviceTab::Composer,
+        DeviceTab::Watches,
+        DeviceTab::Throughput,
+        DeviceTab::Console,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeviceTab::Sent => "Sent",
+            DeviceTab::Received => "Received",
+            DeviceTab::Inspector => "Inspector",
+            DeviceTab::Plot => "Plot",
+            DeviceTab::Stats => "Stats",
+            DeviceTab::Latency => "Latency",
+            DeviceTab::Composer => "Composer",
+            DeviceTab::Watches => "Watches",
+            DeviceTab::Throughput => "Throughput",
+            DeviceTab::Console => "Console",
+        }
+    }
+}
+
+/// frame lists side by side, inspector below them
+pub fn default_layout() -> DockState<DeviceTab> {
+    let mut dock = DockState::new(vec![DeviceTab::Sent]);
+    let surface = dock.main_surface_mut();
+
+    let [lists, _] = surface.split_below(NodeIndex::root(), 0.65, vec![DeviceTab::Inspector, DeviceTab::Stats]);
+    surface.split_right(lists, 0.5, vec![DeviceTab::Received]);
+
+    dock
+}
+
+/// opens `tab` (in the first panel), or closes it
+pub fn set_open(dock: &mut DockState<DeviceTab>, tab: DeviceTab, open: bool) {
+    match (dock.find_tab(&tab), open) {
+        (None, true) => dock.push_to_first_leaf(tab),
+        (Some(location), false) => {
+            dock.remove_tab(location);
+        },
+        _ => (),
+    }
+}
+
+/// checkbox for every optional panel
+pub fn draw_menu(ui: &mut egui::Ui, dock: &mut DockState<DeviceTab>) {
+    for tab in DeviceTab::OPTIONAL {
+        let mut open = dock.find_tab(&tab).is_some();
+
+        if ui.checkbox(&mut open, tab.name()).changed() {
+            set_open(dock, tab, open);
+        }
+    }
+
+    ui.separator();
+    if ui.button("Reset layout").clicked() {
+        *dock = default_layout();
+        ui.close_menu();
+    }
+}
+
+/// draws panels of `device`, its dock state has to be taken out of it for the time being
+pub struct DeviceTabs<'a> {
+    pub device: &'a mut Device,
+    pub ctx: &'a Arc<Context>,
+    pub list: &'a FrameList<'a>,
+    /// frame picked from context menu of one of the lists
+    pub resend: &'a mut Option<Frame>,
+}
+
+impl TabViewer for DeviceTabs<'_> {
+    type Tab = DeviceTab;
+
+    fn title(&mut self, tab: &mut DeviceTab) -> WidgetText {
+        tab.name().into()
+    }
+
+    fn id(&mut self, tab: &mut DeviceTab) -> egui::Id {
+        egui::Id::new((self.device.handle, *tab))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, tab: &mut DeviceTab) {
+        let device = &mut *self.device;
+        let width = ui.available_width();
+
+        match tab {
+            DeviceTab::Sent => self.list.draw(ui, Direction::Tx, &device.sent, width, &mut device.selection, self.resend),
+            DeviceTab::Received => self.list.draw(ui, Direction::Rx, &device.received, width, &mut device.selection, self.resend),
+            DeviceTab::Inspector => inspector::draw_selected(ui, self.ctx, device),
+            DeviceTab::Plot => device.plot.draw(ui, &device.received, &device.watches.list),
+            DeviceTab::Stats => draw_stats(ui, device),
+            DeviceTab::Latency => device.latency.draw(ui, &device.sent, self.list.exchanges, device.schema.as_deref()),
+            DeviceTab::Composer => {
+                if let Some(frame) = device.composer.draw(ui, self.list.can_send) {
+                    *self.resend = Some(frame);
+                }
+            },
+            DeviceTab::Watches => device.watches.draw(ui, &device.received),
+            DeviceTab::Throughput => device.throughput.draw(ui, &device.sent, &device.received),
+            DeviceTab::Console => device.console.draw(ui),
+        }
+    }
+
+    fn closeable(&mut self, tab: &mut DeviceTab) -> bool {
+        DeviceTab::OPTIONAL.contains(tab)
+    }
+
+    fn allowed_in_windows(&self, _tab: &mut DeviceTab) -> bool {
+        false
+    }
+}
+
+/// counters of both frame lists, and state of the decoder for devices with a port
+fn draw_stats(ui: &mut egui::Ui, device: &Device) {
+    egui::Grid::new("stats")
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("");
+            ui.strong("sent");
+            ui.strong("received");
+            ui.end_row();
+
+            let lists = [(&device.sent, device.dropped_sent), (&device.received, device.dropped_received)];
+            let mut row = |name: &str, value: &dyn Fn(usize) -> String| {
+                ui.label(name);
+                ui.monospace(value(0));
+                ui.monospace(value(1));
+                ui.end_row();
+            };
+
+            row("frames", &|i| lists[i].0.len().to_string());
+            row("discarded", &|i| lists[i].0.iter().filter(|frame| frame.discarded.is_some()).count().to_string());
+            row("payload bytes", &|i| lists[i].0
+                .iter()
+                .filter(|frame| frame.discarded.is_none())
+                .map(|frame| frame.inner.data.len())
+                .sum::<usize>()
+                .to_string());
+            row("dropped (limit)", &|i| lists[i].1.to_string());
+        });
+
+    if device.capture.is_none() {
+        ui.separator();
+        ui.strong("decoder");
+        device.decoder.draw(ui);
+    }
+}
@@ -0,0 +1,52 @@
+//! Changes of device state made in background
+//!
+//! Devices are owned by the UI thread. Serial handler, senders and bridges never touch them, they post events
+//! instead, which are applied before the next frame is drawn. So drawing doesn't wait for the serial backend,
+//! and a slow frame doesn't hold up reading of ports.
+
+use std::sync::Arc;
+
+use eframe::epaint::ahash::HashMap;
+use serial_com::{ConnectionState, DeviceHandle, Received, SerialComError};
+
+use crate::{Context, Device, DrawableFrame, FrameDirection, responder};
+
+pub enum DeviceEvent {
+    /// port was opened in background, device is added unless one with its handle exists already
+    Opened(Box<Device>),
+    Connection(ConnectionState),
+    Received(Received),
+    /// frame was written to the port
+    Sent(Box<DrawableFrame>),
+    /// reading of the port failed
+    Failed(SerialComError),
+}
+
+/// applies `event` of device with `handle`, events of closed devices are dropped
+pub fn apply(ctx: &Arc<Context>, devices: &mut HashMap<DeviceHandle, Device>, handle: DeviceHandle, event: DeviceEvent) {
+    if let DeviceEvent::Opened(device) = event {
+        devices.entry(handle).or_insert(*device);
+        return;
+    }
+
+    let Some(dev) = devices.get_mut(&handle) else {
+        return;
+    };
+
+    match event {
+        DeviceEvent::Opened(_) => unreachable!(),
+        DeviceEvent::Connection(state) => dev.connection = state,
+        DeviceEvent::Received(received) => {
+            // replies are sent through the handler, so the worker keeps reading meanwhile
+            for (delay, reply) in dev.receive(ctx, received) {
+                ctx.runtime.spawn(responder::send_reply(ctx.clone(), handle, delay, reply));
+            }
+        },
+        DeviceEvent::Sent(frame) => {
+            let _ = ctx.report_error(dev.push_frame(FrameDirection::Tx, *frame));
+        },
+        DeviceEvent::Failed(error) => {
+            let _ = ctx.report_error::<()>(Err(anyhow::Error::new(error).context(dev.title())));
+        },
+    }
+}
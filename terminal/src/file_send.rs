@@ -0,0 +1,380 @@
+use std::{path::Path, sync::{Arc, Mutex, atomic::{AtomicBool, AtomicUsize, Ordering}}, time::{Duration, Instant}};
+
+use anyhow::Context as _;
+use eframe::egui;
+use proto::{Frame, transfer::{Message, Target}};
+use serial_com::{Cmd, DeviceHandle, Framing, SerialComError};
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
+
+use crate::{Context, events::DeviceEvent};
+
+/// how long a reply to transfer message is waited for, before it's sent again
+const REPLY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How file is split and sent
+#[derive(Debug, Clone, Copy)]
+pub struct FileOptions {
+    /// maximum payload of a single frame, whole file in one frame if `None`
+    pub chunk_size: Option<usize>,
+    pub sender: u8,
+    pub receiver: u8,
+    /// times each frame is sent again before the transfer fails
+    pub retries: u32,
+    /// send with [`proto::transfer`], receiver acknowledges chunks and verifies the file,
+    /// otherwise frames are raw chunks of the file and only failed writes are retried
+    pub acknowledged: bool,
+    /// what acknowledged transfer is for
+    pub target: Target,
+}
+
+/// File being sent in background, split into frames
+///
+/// Failed transfer keeps what was sent, and can be resumed from the chunk it failed at, or from the
+/// offset receiver acknowledges when sent with [`proto::transfer`].
+pub struct FileSend {
+    pub name: String,
+    data: Vec<u8>,
+    chunk_size: usize,
+    options: FileOptions,
+    /// bytes written, or acknowledged by receiver
+    pub sent_bytes: AtomicUsize,
+    /// frames sent again, after a failed write or a missing reply
+    pub retries: AtomicUsize,
+    /// [`proto::crc32`] of the whole file, zero padded like frames, so devices can verify images
+    /// (e.g. firmware) with the CRC unit they check frames with
+    pub crc32: u32,
+    /// when sending was (re)started, and bytes sent before
+    started: Mutex<(Instant, usize)>,
+    /// error sending was stopped by, it can be resumed then
+    error: Mutex<Option<String>>,
+    /// transfer messages sent and received, with seconds since sending was (re)started
+    exchange: Mutex<Vec<String>>,
+    pub done: AtomicBool,
+    cancel: Mutex<CancellationToken>,
+    /// transfer messages received from the receiver
+    replies_tx: mpsc::UnboundedSender<Message>,
+    replies: tokio::sync::Mutex<mpsc::UnboundedReceiver<Message>>,
+}
+
+impl FileSend {
+    /// reads file at `path`, and starts sending it to device as `options` say
+    pub fn start(
+        ctx: &Arc<Context>,
+        handle: DeviceHandle,
+        path: &Path,
+        options: FileOptions,
+    ) -> anyhow::Result<Arc<Self>> {
+        let data = std::fs::read(path)
+            .with_context(|| format!("unable to read {}", path.display()))?;
+        let name = path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        Self::start_with(ctx, handle, name, data, options)
+    }
+
+    /// starts sending `data`, as `options` say
+    pub fn start_with(
+        ctx: &Arc<Context>,
+        handle: DeviceHandle,
+        name: String,
+        data: Vec<u8>,
+        options: FileOptions,
+    ) -> anyhow::Result<Arc<Self>> {
+        anyhow::ensure!(!data.is_empty(), "{} is empty", name);
+
+        let max_chunk_size = match options.acknowledged {
+            true => Message::MAX_CHUNK_LEN,
+            false => u16::MAX as usize,
+        };
+
+        let chunk_size = options.chunk_size.unwrap_or(data.len().min(max_chunk_size));
+        anyhow::ensure!(
+            (1..=max_chunk_size).contains(&chunk_size),
+            "frame payload has to be between 1 and {} bytes, got {}", max_chunk_size, chunk_size,
+        );
+        anyhow::ensure!(
+            options.acknowledged || options.chunk_size.is_some() || data.len() <= max_chunk_size,
+            "{} doesn't fit in one frame, set chunk size", name,
+        );
+        anyhow::ensure!(u32::try_from(data.len()).is_ok(), "{} is too big to be transferred", name);
+
+        let (replies_tx, replies) = mpsc::unbounded_channel();
+        let progress = Arc::new(Self {
+            name,
+            crc32: proto::crc32(&data),
+            data,
+            chunk_size,
+            options,
+            sent_bytes: AtomicUsize::new(0),
+            retries: AtomicUsize::new(0),
+            started: Mutex::new((Instant::now(), 0)),
+            error: Mutex::new(None),
+            exchange: Mutex::new(Vec::new()),
+            done: AtomicBool::new(false),
+            cancel: Mutex::new(CancellationToken::new()),
+            replies_tx,
+            replies: tokio::sync::Mutex::new(replies),
+        });
+
+        ctx.runtime.spawn(progress.clone().run(ctx.clone(), handle));
+        Ok(progress)
+    }
+
+    /// continues failed or cancelled transfer
+    pub fn resume(self: &Arc<Self>, ctx: &Arc<Context>, handle: DeviceHandle) {
+        *self.error.lock().unwrap() = None;
+        *self.cancel.lock().unwrap() = CancellationToken::new();
+        *self.started.lock().unwrap() = (Instant::now(), self.sent_bytes.load(Ordering::Relaxed));
+        self.done.store(false, Ordering::Relaxed);
+
+        ctx.runtime.spawn(self.clone().run(ctx.clone(), handle));
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.lock().unwrap().cancel();
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    /// error transfer was stopped by, if it failed
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().unwrap().clone()
+    }
+
+    /// transfer messages sent and received so far, one per line
+    pub fn exchange(&self) -> Vec<String> {
+        self.exchange.lock().unwrap().clone()
+    }
+
+    /// passes `frame` received by the device to transfer waiting for replies
+    pub fn received(&self, frame: &Frame) {
+        if !self.options.acknowledged || frame.sender != self.options.receiver {
+            return;
+        }
+
+        // other frames of the receiver aren't replies
+        if let Ok(message) = Message::from_payload(&frame.data) {
+            self.log("<-", &message);
+            let _ = self.replies_tx.send(message);
+        }
+    }
+
+    /// records transfer `message` going in `direction`, chunk data is left out
+    fn log(&self, direction: &str, message: &Message) {
+        let message = match message {
+            Message::Chunk { offset, data } => format!("Chunk {{ offset: {}, len: {} }}", offset, data.len()),
+            message => format!("{:?}", message),
+        };
+
+        let elapsed = self.started.lock().unwrap().0.elapsed().as_secs_f64();
+        let line = format!("{:>8.3} {} {}", elapsed, direction, message);
+
+        log::info!("{}: {}", self.name, line);
+        self.exchange.lock().unwrap().push(line);
+    }
+
+    /// payload bytes sent per second, since (re)start
+    pub fn throughput(&self) -> f64 {
+        let (started, sent_before) = *self.started.lock().unwrap();
+        let elapsed = started.elapsed().as_secs_f64();
+        let sent = self.sent_bytes.load(Ordering::Relaxed).saturating_sub(sent_before);
+
+        if elapsed > 0.0 {
+            sent as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+
+    /// draws progress bar with cancel button, or error with resume button once failed
+    ///
+    /// Returns true when resume was requested.
+    pub fn draw(&self, ui: &mut egui::Ui) -> bool {
+        let sent = self.sent_bytes.load(Ordering::Relaxed);
+        let retries = self.retries.load(Ordering::Relaxed);
+        let error = self.error();
+        let mut resume = false;
+
+        ui.horizontal(|ui| {
+            match error.is_some() {
+                true => resume = ui.button("Resume").clicked(),
+                false => if ui.button("Cancel").clicked() {
+                    self.cancel();
+                },
+            }
+
+            ui.add(egui::ProgressBar::new(sent as f32 / self.data.len() as f32)
+                .text(format!("{} {}/{} B, {} retries, {:.0} B/s", self.name, sent, self.data.len(), retries, self.throughput())))
+                .on_hover_text(format!("{} bytes, CRC32 {:08X}, to {}", self.data.len(), self.crc32, self.options.receiver));
+        });
+
+        if let Some(error) = error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+
+        resume
+    }
+
+    async fn run(self: Arc<Self>, ctx: Arc<Context>, handle: DeviceHandle) {
+        let cancel = self.cancel.lock().unwrap().clone();
+
+        let result = tokio::select! {
+            _ = cancel.cancelled() => Err(SerialComError::Cancelled.into()),
+            result = async {
+                match self.options.acknowledged {
+                    true => self.send_acknowledged(&ctx, handle).await,
+                    false => self.send_raw(&ctx, handle).await,
+                }
+            } => result,
+        };
+
+        if let Err(err) = result {
+            if self.options.acknowledged && cancel.is_cancelled() {
+                // receiver may keep what it has, to resume later
+                self.log("->", &Message::Abort);
+                let _ = self.send(&ctx, handle, Message::Abort.to_payload()).await;
+            }
+
+            *self.error.lock().unwrap() = Some(format!("{:#}", err));
+        }
+
+        self.done.store(true, Ordering::Relaxed);
+        ctx.egui_ctx.request_repaint();
+    }
+
+    /// writes raw chunks from the first one not written yet, failed writes are retried
+    async fn send_raw(&self, ctx: &Context, handle: DeviceHandle) -> anyhow::Result<()> {
+        let mut offset = self.sent_bytes.load(Ordering::Relaxed);
+
+        while offset < self.data.len() {
+            let end = (offset + self.chunk_size).min(self.data.len());
+            let mut attempt = 0;
+
+            while let Err(err) = self.send(ctx, handle, self.data[offset..end].to_vec()).await {
+                self.retry(&mut attempt, err)?;
+            }
+
+            offset = end;
+            self.sent_bytes.store(offset, Ordering::Relaxed);
+        }
+
+        Ok(())
+    }
+
+    /// sends file with [`proto::transfer`], from the offset receiver acknowledges
+    async fn send_acknowledged(&self, ctx: &Context, handle: DeviceHandle) -> anyhow::Result<()> {
+        let mut replies = self.replies.lock().await;
+        // left from the previous attempt
+        while replies.try_recv().is_ok() {}
+
+        let ack = |message| match message {
+            Message::Ack { offset } => Some(offset as usize),
+            _ => None,
+        };
+
+        let start = Message::Start {
+            target: self.options.target,
+            size: self.data.len() as u32,
+            crc32: self.crc32,
+        };
+        let mut offset = self.request(ctx, handle, &mut replies, &start, ack).await?;
+
+        while offset < self.data.len() {
+            self.sent_bytes.store(offset, Ordering::Relaxed);
+
+            let end = (offset + self.chunk_size).min(self.data.len());
+            let chunk = Message::Chunk { offset: offset as u32, data: self.data[offset..end].to_vec() };
+
+            offset = self.request(ctx, handle, &mut replies, &chunk, ack).await?;
+        }
+
+        anyhow::ensure!(offset == self.data.len(), "receiver acknowledged {} bytes of {}", offset, self.data.len());
+        self.sent_bytes.store(offset, Ordering::Relaxed);
+
+        let verified = |message| match message {
+            Message::Verified { ok } => Some(ok),
+            _ => None,
+        };
+
+        let ok = self.request(ctx, handle, &mut replies, &Message::End, verified).await?;
+        anyhow::ensure!(ok, "receiver found CRC of the file doesn't match");
+
+        Ok(())
+    }
+
+    /// sends `message` until a reply `accept` takes arrives
+    async fn request<T>(
+        &self,
+        ctx: &Context,
+        handle: DeviceHandle,
+        replies: &mut mpsc::UnboundedReceiver<Message>,
+        message: &Message,
+        accept: impl Fn(Message) -> Option<T>,
+    ) -> anyhow::Result<T> {
+        let mut attempt = 0;
+
+        loop {
+            let result = async {
+                self.log("->", message);
+                self.send(ctx, handle, message.to_payload()).await?;
+
+                let reply = async {
+                    while let Some(reply) = replies.recv().await {
+                        if let Some(reply) = accept(reply) {
+                            return Some(reply);
+                        }
+                    }
+
+                    None
+                };
+
+                tokio::time::timeout(REPLY_TIMEOUT, reply)
+                    .await
+                    .map_err(|_| anyhow::anyhow!("no reply in {} ms", REPLY_TIMEOUT.as_millis()))?
+                    .context("device was closed")
+            }.await;
+
+            match result {
+                Ok(reply) => return Ok(reply),
+                Err(err) => self.retry(&mut attempt, err)?,
+            }
+        }
+    }
+
+    /// counts retry after `err`, or returns it when no retries are left
+    fn retry(&self, attempt: &mut u32, err: anyhow::Error) -> anyhow::Result<()> {
+        if *attempt == self.options.retries {
+            return Err(err.context(format!("failed after {} retries", attempt)));
+        }
+
+        log::debug!("retrying {} after: {:#}", self.name, err);
+        *attempt += 1;
+        self.retries.fetch_add(1, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// writes frame with `data` to the device
+    async fn send(&self, ctx: &Context, handle: DeviceHandle, data: Vec<u8>) -> anyhow::Result<()> {
+        let frame = Frame {
+            sender: self.options.sender,
+            receiver: self.options.receiver,
+            data,
+        };
+
+        let (result_tx, result) = oneshot::channel();
+        ctx.cmd_tx
+            .send(Cmd::SendData { handle, data: frame.serialize()?, framing: Framing::Frame, result: result_tx })
+            .await
+            .map_err(|_| anyhow::anyhow!("serial handler stopped"))?;
+
+        result.await.unwrap_or(Err(SerialComError::WorkerStopped))?;
+
+        ctx.post(handle, DeviceEvent::Sent(Box::new(frame.into())));
+        Ok(())
+    }
+}
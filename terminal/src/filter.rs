@@ -0,0 +1,116 @@
+use eframe::egui::{self, TextEdit};
+use proto::Frame;
+
+/// Filter applied to frame lists of a device, fields are kept as entered by the user
+#[derive(Debug, Default, Clone)]
+pub struct FrameFilter {
+    /// comma separated list of sender addresses
+    pub sender: String,
+    /// comma separated list of receiver addresses
+    pub receiver: String,
+    /// text (or hex bytes if `pattern_hex`) payload has to contain
+    pub pattern: String,
+    pub pattern_hex: bool,
+    pub min_len: String,
+    pub max_len: String,
+}
+
+/// `FrameFilter` parsed into values, ready to be matched against frames
+#[derive(Debug, Default, Clone)]
+pub struct CompiledFilter {
+    senders: Vec<u8>,
+    receivers: Vec<u8>,
+    pattern: Vec<u8>,
+    min_len: Option<usize>,
+    max_len: Option<usize>,
+}
+
+impl FrameFilter {
+    pub fn compile(&self) -> anyhow::Result<CompiledFilter> {
+        Ok(CompiledFilter {
+            senders: parse_list(&self.sender, "sender")?,
+            receivers: parse_list(&self.receiver, "receiver")?,
+            pattern: if self.pattern_hex {
+                proto_tools::bytes::parse_hex(&self.pattern)?
+            } else {
+                self.pattern.clone().into_bytes()
+            },
+            min_len: parse_len(&self.min_len, "minimum length")?,
+            max_len: parse_len(&self.max_len, "maximum length")?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        [&self.sender, &self.receiver, &self.pattern, &self.min_len, &self.max_len]
+            .iter()
+            .all(|s| s.trim().is_empty())
+    }
+
+    /// draws filter controls, returns compiled filter if it's valid
+    pub fn draw(&mut self, ui: &mut egui::Ui) -> Option<CompiledFilter> {
+        let compiled = self.compile();
+
+        ui.horizontal(|ui| {
+            ui.label("sender:");
+            ui.add(TextEdit::singleline(&mut self.sender).desired_width(50.0));
+
+            ui.label("receiver:");
+            ui.add(TextEdit::singleline(&mut self.receiver).desired_width(50.0));
+
+            ui.label("payload:");
+            ui.add(TextEdit::singleline(&mut self.pattern).desired_width(120.0));
+            ui.checkbox(&mut self.pattern_hex, "hex");
+
+            ui.label("length:");
+            ui.add(TextEdit::singleline(&mut self.min_len).desired_width(35.0).hint_text("min"));
+            ui.label("-");
+            ui.add(TextEdit::singleline(&mut self.max_len).desired_width(35.0).hint_text("max"));
+
+            if ui.button("Clear").clicked() {
+                *self = Self::default();
+            }
+        });
+
+        match compiled {
+            Ok(compiled) => Some(compiled),
+            Err(err) => {
+                ui.colored_label(ui.visuals().error_fg_color, format!("{:#}", err));
+                None
+            },
+        }
+    }
+}
+
+impl CompiledFilter {
+    pub fn matches(&self, frame: &Frame) -> bool {
+        (self.senders.is_empty() || self.senders.contains(&frame.sender))
+            && (self.receivers.is_empty() || self.receivers.contains(&frame.receiver))
+            && self.min_len.is_none_or(|min| frame.data.len() >= min)
+            && self.max_len.is_none_or(|max| frame.data.len() <= max)
+            && contains(&frame.data, &self.pattern)
+    }
+}
+
+/// whether `data` contains `pattern` (empty pattern is contained in everything)
+pub fn contains(data: &[u8], pattern: &[u8]) -> bool {
+    pattern.is_empty() || data
+        .windows(pattern.len())
+        .any(|w| w == pattern)
+}
+
+fn parse_list(s: &str, what: &str) -> anyhow::Result<Vec<u8>> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().map_err(|_| anyhow::anyhow!("invalid {} address `{}`", what, s)))
+        .collect()
+}
+
+fn parse_len(s: &str, what: &str) -> anyhow::Result<Option<usize>> {
+    match s.trim() {
+        "" => Ok(None),
+        s => s.parse()
+            .map(Some)
+            .map_err(|_| anyhow::anyhow!("invalid {} `{}`", what, s)),
+    }
+}
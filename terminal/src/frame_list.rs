@@ -0,0 +1,367 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+use chrono::{DateTime, Local};
+use eframe::{egui::{self, Id, ScrollArea}, epaint::{Color32, ecolor::Hsva}};
+use proto::Frame;
+use proto_tools::capture::Direction;
+
+use crate::{DrawableFrame, appearance, filter::{self, CompiledFilter}, pairing::{self, Exchange}, render::PayloadView};
+
+/// how frame timestamps are shown in frame lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeMode {
+    /// local wall clock time
+    #[default]
+    Absolute,
+    /// time elapsed since previous frame shown in the same list
+    Delta,
+}
+
+/// which address frame rows are tinted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    #[default]
+    Off,
+    Sender,
+    Receiver,
+}
+
+impl ColorMode {
+    /// address frame is colored by, `None` if coloring is off
+    pub fn address(&self, frame: &Frame) -> Option<u8> {
+        match self {
+            ColorMode::Off => None,
+            ColorMode::Sender => Some(frame.sender),
+            ColorMode::Receiver => Some(frame.receiver),
+        }
+    }
+}
+
+/// stable color of an address, hues are spread by golden ratio so that close addresses differ
+pub fn address_color(address: u8) -> Color32 {
+    let hue = (address as f32 * 0.618_034).fract();
+
+    Hsva::new(hue, 0.55, 0.95, 1.0).into()
+}
+
+/// Frames picked in frame lists of a device
+#[derive(Debug, Default)]
+pub struct Selection {
+    /// frame shown in the inspector
+    pub selected: Option<u64>,
+    /// frame compared with the selected one
+    pub compare: Option<u64>,
+    /// frames picked with shift + click, e.g. to be exported
+    pub marked: BTreeSet<u64>,
+    /// bookmarked frames with color of their marker
+    pub bookmarks: BTreeMap<u64, Color32>,
+}
+
+/// Auto-scroll state of a single list, kept in egui memory
+#[derive(Debug, Clone, Copy, Default)]
+struct Follow {
+    /// number of visible frames when list was last scrolled to the bottom
+    seen: usize,
+    /// scroll to the bottom in the next frame
+    jump: bool,
+}
+
+/// Frame shown in a list, rows are laid out before drawing the ones in view
+struct Row<'f> {
+    frame: &'f DrawableFrame,
+    /// timestamp of the frame before, for delta times
+    previous: Option<u64>,
+    /// set on the first frame of repeated ones
+    run: Option<Run>,
+}
+
+/// Consecutive identical frames, shown as one row unless expanded
+#[derive(Debug, Clone, Copy)]
+struct Run {
+    len: usize,
+    /// timestamp of the last repeat
+    last_us: u64,
+    expanded: bool,
+    toggle_id: Id,
+}
+
+/// height of a frame row, two lines of frame font in a selectable label
+fn row_height(ui: &egui::Ui) -> f32 {
+    let font_id = appearance::frame_font(ui.style());
+    2.0 * ui.fonts(|f| f.row_height(&font_id)) + 2.0 * ui.spacing().button_padding.y
+}
+
+/// state shared by both frame lists of a device window
+pub struct FrameList<'a> {
+    pub filter: &'a CompiledFilter,
+    /// search pattern, frames containing it are highlighted
+    pub pattern: Option<&'a [u8]>,
+    pub time_mode: TimeMode,
+    /// show frames that failed to deserialize, filter doesn't apply to them
+    pub show_discarded: bool,
+    /// consecutive identical frames are shown as one row, which can be expanded
+    pub collapse_repeats: bool,
+    /// list follows new frames while it's scrolled to the bottom and not hovered
+    pub auto_scroll: bool,
+    /// id of frame list should scroll to
+    pub scroll_to: Option<u64>,
+    pub color_mode: ColorMode,
+    /// requests paired with responses, by id of both frames
+    pub exchanges: &'a HashMap<u64, Exchange>,
+    /// frames can be sent again from their context menu
+    pub can_send: bool,
+    /// show escaped wire bytes instead of decoded payload
+    pub wire: bool,
+    /// what payloads are shown as
+    pub payload: PayloadView<'a>,
+    /// received frames matching one of the filters are shown in its color, instead of `color_mode`
+    pub highlights: &'a [(CompiledFilter, Color32)],
+}
+
+impl FrameList<'_> {
+    pub fn is_visible(&self, frame: &DrawableFrame) -> bool {
+        match frame.discarded {
+            Some(_) => self.show_discarded,
+            None => self.filter.matches(&frame.inner),
+        }
+    }
+
+    /// color of the first highlight matching received `frame`
+    fn highlight(&self, direction: Direction, frame: &DrawableFrame) -> Option<Color32> {
+        if direction != Direction::Rx {
+            return None;
+        }
+
+        self.highlights
+            .iter()
+            .find(|(filter, _)| filter.matches(&frame.inner))
+            .map(|(_, color)| *color)
+    }
+
+    /// frame contains search pattern, raw bytes are searched for discarded frames
+    pub fn is_match(&self, frame: &DrawableFrame) -> bool {
+        let data = frame.discarded
+            .as_ref()
+            .map_or(&frame.inner.data, |discarded| &discarded.raw);
+
+        self.pattern.is_some_and(|p| filter::contains(data, p))
+    }
+
+    /// colors of addresses in visible `frames`
+    pub fn draw_legend<'f>(&self, ui: &mut egui::Ui, frames: impl Iterator<Item = &'f DrawableFrame>) {
+        let addresses = frames
+            .filter(|frame| frame.discarded.is_none() && self.is_visible(frame))
+            .filter_map(|frame| self.color_mode.address(&frame.inner))
+            .collect::<BTreeSet<_>>();
+
+        ui.horizontal_wrapped(|ui| {
+            for address in addresses {
+                ui.colored_label(address_color(address), format!("■ {}", address));
+            }
+        });
+    }
+
+    /// draws visible `frames`, clicking one of them changes selected frame,
+    /// ctrl + click changes compared one (second frame of a diff), shift + click marks or unmarks it,
+    /// frame picked to be sent again from context menu is put in `send`
+    ///
+    /// Only rows in view are built, so long histories don't slow drawing down.
+    pub fn draw(
+        &self,
+        ui: &mut egui::Ui,
+        direction: Direction,
+        frames: &VecDeque<DrawableFrame>,
+        width: f32,
+        selection: &mut Selection,
+        send: &mut Option<Frame>,
+    ) {
+        let send_label = self.can_send.then_some(match direction {
+            Direction::Tx => "Resend",
+            Direction::Rx => "Send copy",
+        });
+
+        let visible = frames
+            .iter()
+            .filter(|frame| self.is_visible(frame))
+            .collect::<Vec<_>>();
+
+        let mut rows = Vec::with_capacity(visible.len());
+        let mut previous = None;
+
+        for run in visible.chunk_by(|a, b| self.collapse_repeats && is_repeat(a, b)) {
+            let last = run[run.len() - 1];
+            // run is kept expanded by id of its first frame, so it stays expanded as it grows
+            let toggle_id = ui.id().with(("repeats", run[0].id));
+            let expanded = run.len() > 1 && ui.data(|d| d.get_temp(toggle_id)).unwrap_or(false);
+            let shown = if expanded { run } else { &run[..1] };
+
+            for (i, frame) in shown.iter().enumerate() {
+                rows.push(Row {
+                    frame,
+                    previous,
+                    run: (i == 0 && run.len() > 1).then_some(Run {
+                        len: run.len(),
+                        last_us: last.timestamp_us,
+                        expanded,
+                        toggle_id,
+                    }),
+                });
+                previous = Some(frame.timestamp_us);
+            }
+
+            previous = Some(last.timestamp_us);
+        }
+
+        let row_height = row_height(ui);
+        let follow_id = ui.id().with(("follow", direction));
+        let mut follow = ui.data(|d| d.get_temp::<Follow>(follow_id)).unwrap_or_default();
+        // reading pauses following, so rows don't move away under the pointer
+        let hovered = ui.ui_contains_pointer();
+
+        let mut scroll = ScrollArea::new([false, true])
+            .id_source(Id::new(direction).with(ui.id()))
+            .stick_to_bottom(self.auto_scroll && !hovered);
+
+        // rows out of view aren't built, so they can't scroll to themselves
+        if let Some(index) = self.scroll_to.and_then(|id| rows.iter().position(|row| row.frame.id == id)) {
+            let offset = index as f32 * (row_height + ui.spacing().item_spacing.y) - ui.available_height() / 2.0;
+            scroll = scroll.vertical_scroll_offset(offset.max(0.0));
+        }
+
+        if std::mem::take(&mut follow.jump) {
+            scroll = scroll.vertical_scroll_offset(f32::MAX);
+        }
+
+        let output = scroll.show_rows(ui, row_height, rows.len(), |ui, range| {
+            for row in &rows[range] {
+                self.draw_row(ui, direction, row, width, selection, send_label, send);
+            }
+        });
+
+        let at_bottom = output.state.offset.y + output.inner_rect.height() >= output.content_size.y - 1.0;
+        if at_bottom {
+            follow.seen = visible.len();
+        }
+
+        let new = visible.len().saturating_sub(follow.seen);
+        if self.auto_scroll && new > 0 {
+            let pill = egui::Rect::from_center_size(
+                output.inner_rect.center_bottom() - egui::vec2(0.0, 20.0),
+                egui::vec2(140.0, 24.0),
+            );
+
+            if ui.put(pill, egui::Button::new(format!("⬇ {} new frames", new))).clicked() {
+                follow.jump = true;
+                ui.ctx().request_repaint();
+            }
+        }
+
+        ui.data_mut(|d| d.insert_temp(follow_id, follow));
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_row(
+        &self,
+        ui: &mut egui::Ui,
+        direction: Direction,
+        row: &Row,
+        width: f32,
+        selection: &mut Selection,
+        send_label: Option<&str>,
+        send: &mut Option<Frame>,
+    ) {
+        let frame = row.frame;
+
+        let time = match (self.time_mode, row.previous) {
+            (TimeMode::Delta, Some(previous)) => format_delta(frame.timestamp_us.saturating_sub(previous)),
+            // first frame has nothing to be relative to
+            _ => format_time(frame.timestamp_us),
+        };
+
+        let time = match row.run {
+            Some(run) if !run.expanded => format!("{} ×{} last:{}", time, run.len, format_time(run.last_us)),
+            _ => time,
+        };
+
+        let exchange = self.exchanges.get(&frame.id);
+        let time = match exchange {
+            Some(exchange) => format!("{} RTT:{}", time, pairing::format_rtt(exchange.rtt_us)),
+            None => time,
+        };
+
+        // other frame of the exchange is shown as selected too, linking them
+        let is_selected = selection.selected == Some(frame.id)
+            || selection.compare == Some(frame.id)
+            || selection.marked.contains(&frame.id)
+            || exchange.is_some_and(|exchange| selection.selected == Some(exchange.other));
+        let tint = frame.discarded
+            .is_none()
+            .then(|| self.highlight(direction, frame).or_else(|| self.color_mode.address(&frame.inner).map(address_color)))
+            .flatten();
+
+        let resp = frame.draw(ui, width, &time, is_selected, self.is_match(frame), tint, self.wire, self.payload, send_label, send);
+        if resp.clicked() {
+            let modifiers = ui.input(|i| i.modifiers);
+
+            if modifiers.shift {
+                if !selection.marked.remove(&frame.id) {
+                    selection.marked.insert(frame.id);
+                }
+            } else if modifiers.command {
+                selection.compare = (selection.compare != Some(frame.id)).then_some(frame.id);
+            } else {
+                selection.selected = Some(frame.id);
+            }
+        }
+
+        if let Some(color) = selection.bookmarks.get(&frame.id) {
+            let marker = egui::Rect::from_min_size(resp.rect.left_top(), egui::vec2(4.0, resp.rect.height()));
+            ui.painter().rect_filled(marker, 0.0, *color);
+        }
+
+        // toggle sits on top of the row, rows have to keep the same height
+        if let Some(run) = row.run {
+            let label = if run.expanded { "collapse".to_owned() } else { format!("+{} repeats", run.len - 1) };
+            let rect = egui::Rect::from_min_size(resp.rect.right_top() + egui::vec2(-100.0, 2.0), egui::vec2(96.0, 18.0));
+
+            if ui.child_ui(rect, egui::Layout::right_to_left(egui::Align::Min)).small_button(label).clicked() {
+                ui.data_mut(|d| d.insert_temp(run.toggle_id, !run.expanded));
+            }
+        }
+    }
+}
+
+/// `b` has the same addresses and payload as `a`, or the same raw bytes if both were discarded
+fn is_repeat(a: &DrawableFrame, b: &DrawableFrame) -> bool {
+    match (a.discarded.as_ref(), b.discarded.as_ref()) {
+        (None, None) => a.inner == b.inner,
+        (Some(a), Some(b)) => a.raw == b.raw,
+        _ => false,
+    }
+}
+
+fn local_time(timestamp_us: u64) -> Option<DateTime<Local>> {
+    let secs = (timestamp_us / 1_000_000) as i64;
+    let nanos = (timestamp_us % 1_000_000 * 1_000) as u32;
+
+    DateTime::from_timestamp(secs, nanos).map(|utc| utc.with_timezone(&Local))
+}
+
+/// local time of day with milliseconds, e.g. `14:03:27.512`
+pub fn format_time(timestamp_us: u64) -> String {
+    local_time(timestamp_us)
+        .map(|time| time.format("%H:%M:%S%.3f").to_string())
+        .unwrap_or_default()
+}
+
+/// local date and time with microseconds
+pub fn format_date_time(timestamp_us: u64) -> String {
+    local_time(timestamp_us)
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S%.6f").to_string())
+        .unwrap_or_default()
+}
+
+/// elapsed time in seconds, e.g. `+0.012345`
+pub fn format_delta(delta_us: u64) -> String {
+    format!("+{}.{:06}", delta_us / 1_000_000, delta_us % 1_000_000)
+}
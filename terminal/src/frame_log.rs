@@ -0,0 +1,45 @@
+use std::{fs::File, io::BufWriter, path::{Path, PathBuf}};
+
+use proto_tools::capture::{CaptureWriter, Direction, Format, Record};
+
+use crate::DrawableFrame;
+
+/// Log file every frame of a device is appended to, as soon as it is sent or received
+pub struct FrameLog {
+    pub path: PathBuf,
+    writer: CaptureWriter<BufWriter<File>>,
+}
+
+impl FrameLog {
+    /// opens (or creates) log at `path`, format is picked by extension
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            path: path.to_owned(),
+            writer: CaptureWriter::append(path, None)?,
+        })
+    }
+
+    pub fn write(&mut self, direction: Direction, frame: &DrawableFrame) -> anyhow::Result<()> {
+        self.writer.write(&Record {
+            timestamp_us: frame.timestamp_us,
+            direction,
+            frame: frame.inner.clone(),
+            bookmark: None,
+        })?;
+
+        // flush every frame, so log is complete even if app crashes
+        self.writer.flush()
+    }
+}
+
+/// writes `records` to new file at `path`, format is picked by extension (pcapng by default)
+pub fn export(path: &Path, records: &[Record]) -> anyhow::Result<()> {
+    let format = Format::from_path(path).unwrap_or(Format::Pcapng);
+    let mut writer = CaptureWriter::create(path, Some(format))?;
+
+    for record in records {
+        writer.write(record)?;
+    }
+
+    writer.flush()
+}
@@ -0,0 +1,264 @@
+use std::{collections::VecDeque, sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}}, time::Duration};
+
+use clap::ValueEnum;
+use eframe::egui::{self, DragValue};
+use proto::Frame;
+use proto_tools::fuzz::Case;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serial_com::{Cmd, DeviceHandle, Framing};
+use tokio::{sync::oneshot, time::MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+
+use crate::{Context, Device, DrawableFrame, events::DeviceEvent};
+
+/// Sends random valid and malformed frames, to check firmware survives them
+pub struct Fuzzer {
+    pub open: bool,
+    /// cases frames are picked from
+    pub cases: Vec<Case>,
+    /// frames sent per second
+    pub rate: u32,
+    /// stop after this many frames, 0 to send until stopped
+    pub count: u32,
+    run: Option<Arc<FuzzRun>>,
+}
+
+/// progress of fuzzing running in background
+struct FuzzRun {
+    seed: u64,
+    /// microseconds since unix epoch, responses are counted from then
+    started_us: u64,
+    /// frames sent, per case
+    sent: Mutex<Vec<(Case, u32)>>,
+    /// error sending was stopped by
+    error: Mutex<Option<String>>,
+    done: AtomicBool,
+    cancel: CancellationToken,
+}
+
+impl Default for Fuzzer {
+    fn default() -> Self {
+        Self {
+            open: false,
+            cases: Case::value_variants().to_vec(),
+            rate: 20,
+            count: 0,
+            run: None,
+        }
+    }
+}
+
+impl Fuzzer {
+    pub fn start(&mut self, ctx: &Arc<Context>, handle: DeviceHandle, sender: u8, receiver: u8) -> anyhow::Result<()> {
+        anyhow::ensure!(!self.cases.is_empty(), "no fuzzing case selected");
+        anyhow::ensure!(self.rate > 0, "rate has to be at least 1 frame/s");
+
+        let run = Arc::new(FuzzRun {
+            seed: rand::random(),
+            started_us: proto_tools::capture::now_us(),
+            sent: Mutex::new(self.cases.iter().map(|case| (*case, 0)).collect()),
+            error: Mutex::new(None),
+            done: AtomicBool::new(false),
+            cancel: CancellationToken::new(),
+        });
+
+        let period = Duration::from_secs(1) / self.rate;
+        ctx.runtime.spawn(Self::run(ctx.clone(), handle, (sender, receiver), period, self.count, run.clone()));
+        self.run = Some(run);
+
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        if let Some(run) = self.run.as_ref() {
+            run.cancel.cancel();
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.run.as_ref().is_some_and(|run| !run.done.load(Ordering::Relaxed))
+    }
+
+    /// draws settings, and statistics of the last run, `received` are frames of the fuzzed device
+    ///
+    /// Returns true when start was requested.
+    pub fn draw(&mut self, ui: &mut egui::Ui, received: &VecDeque<DrawableFrame>) -> bool {
+        let mut start = false;
+
+        ui.add_enabled_ui(!self.is_running(), |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for case in Case::value_variants() {
+                    let mut enabled = self.cases.contains(case);
+
+                    if ui.checkbox(&mut enabled, case.name()).changed() {
+                        match enabled {
+                            true => self.cases.push(*case),
+                            false => self.cases.retain(|c| c != case),
+                        }
+                    }
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(DragValue::new(&mut self.rate).clamp_range(1..=1000).suffix(" frames/s"));
+                ui.add(DragValue::new(&mut self.count).prefix("count: "))
+                    .on_hover_text("stop after this many frames, 0 to send until stopped");
+            });
+        });
+
+        ui.horizontal(|ui| {
+            if self.is_running() {
+                if ui.button("Stop").clicked() {
+                    self.stop();
+                }
+            } else if ui.button("Start").on_hover_text("addresses are taken from the device window").clicked() {
+                start = true;
+            }
+        });
+
+        if let Some(run) = self.run.as_ref() {
+            ui.separator();
+            run.draw(ui, received);
+        }
+
+        start
+    }
+
+    async fn run(ctx: Arc<Context>, handle: DeviceHandle, addresses: (u8, u8), period: Duration, count: u32, run: Arc<FuzzRun>) {
+        let mut rng = StdRng::seed_from_u64(run.seed);
+        let cases = run.sent.lock().unwrap().iter().map(|(case, _)| *case).collect::<Vec<_>>();
+
+        let mut interval = tokio::time::interval(period);
+        // don't try to catch up after a slow send
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        for i in 0.. {
+            if count != 0 && i == count {
+                break;
+            }
+
+            tokio::select! {
+                _ = run.cancel.cancelled() => break,
+                _ = interval.tick() => (),
+            }
+
+            let case = *cases.choose(&mut rng).unwrap();
+            let wire = case.generate(&mut rng, addresses.0, addresses.1);
+
+            if let Err(err) = Self::send(&ctx, handle, case, wire).await {
+                *run.error.lock().unwrap() = Some(format!("{:#}", err));
+                break;
+            }
+
+            if let Some((_, sent)) = run.sent.lock().unwrap().iter_mut().find(|(c, _)| *c == case) {
+                *sent += 1;
+            }
+
+            ctx.egui_ctx.request_repaint();
+        }
+
+        run.done.store(true, Ordering::Relaxed);
+        ctx.egui_ctx.request_repaint();
+    }
+
+    /// writes `wire` bytes as they are, malformed ones are shown as discarded in the sent list
+    async fn send(ctx: &Context, handle: DeviceHandle, case: Case, wire: Vec<u8>) -> anyhow::Result<()> {
+        let (result_tx, result) = oneshot::channel();
+        ctx.cmd_tx
+            .send(Cmd::SendData { handle, data: wire.clone(), framing: Framing::Raw, result: result_tx })
+            .await
+            .map_err(|_| anyhow::anyhow!("serial handler stopped"))?;
+
+        result.await??;
+
+        let frame = match Frame::deserialize(&wire) {
+            Ok(frame) => frame.into(),
+            Err(err) => DrawableFrame::discarded(wire, format!("fuzz {}: {}", case.name(), err)),
+        };
+
+        ctx.post(handle, DeviceEvent::Sent(Box::new(frame)));
+        Ok(())
+    }
+}
+
+impl FuzzRun {
+    /// frames sent, and how device reacted to them
+    fn draw(&self, ui: &mut egui::Ui, received: &VecDeque<DrawableFrame>) {
+        let received = received.iter().filter(|frame| frame.timestamp_us >= self.started_us);
+        let (mut responses, mut errors, mut last_response_us) = (0, 0, None);
+
+        for frame in received {
+            match frame.discarded {
+                Some(_) => errors += 1,
+                None => {
+                    responses += 1;
+                    last_response_us = Some(frame.timestamp_us);
+                },
+            }
+        }
+
+        egui::Grid::new("fuzz stats").striped(true).show(ui, |ui| {
+            for (case, sent) in self.sent.lock().unwrap().iter() {
+                ui.label(case.name());
+                ui.label(sent.to_string());
+                ui.end_row();
+            }
+
+            ui.strong("responses");
+            ui.label(responses.to_string());
+            ui.end_row();
+
+            ui.strong("receive errors");
+            ui.label(errors.to_string())
+                .on_hover_text("received bytes that didn't form a valid frame");
+            ui.end_row();
+
+            ui.strong("last response");
+            match last_response_us {
+                Some(timestamp_us) => {
+                    let ago_us = proto_tools::capture::now_us().saturating_sub(timestamp_us);
+                    ui.label(format!("{:.1} s ago", ago_us as f64 / 1e6))
+                },
+                None => ui.weak("none"),
+            };
+            ui.end_row();
+
+            ui.strong("seed");
+            ui.monospace(self.seed.to_string());
+            ui.end_row();
+        });
+
+        if let Some(error) = self.error.lock().unwrap().as_ref() {
+            ui.colored_label(ui.visuals().error_fg_color, format!("stopped: {}", error));
+        }
+
+        // keep "last response" ticking
+        if !self.done.load(Ordering::Relaxed) {
+            ui.ctx().request_repaint_after(Duration::from_millis(200));
+        }
+    }
+}
+
+/// shows fuzzing window of `device`, closing it stops fuzzing
+pub fn show(ctx: &egui::Context, app_ctx: &Arc<Context>, device: &mut Device) {
+    let mut open = device.fuzzer.open;
+    let mut start = false;
+
+    egui::Window::new(format!("Fuzz - {}", device.title()))
+        .id(egui::Id::new(("fuzz", device.handle)))
+        .open(&mut open)
+        .default_width(320.0)
+        .show(ctx, |ui| start = device.fuzzer.draw(ui, &device.received));
+
+    if start {
+        let result = device.port_settings()
+            .and_then(|addresses| device.fuzzer.start(app_ctx, device.handle, addresses.sender, addresses.receiver));
+        let _ = app_ctx.report_error(result);
+    }
+
+    if !open {
+        device.fuzzer.stop();
+    }
+
+    device.fuzzer.open = open;
+}
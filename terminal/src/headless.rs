@@ -0,0 +1,146 @@
+//! Mode without UI, for scripts and CI machines without a display
+//!
+//! Every line on stdin is a JSON object with frame to send, e.g. `{"sender":123,"receiver":100,"data":"01A0FF"}`,
+//! where `sender` and `receiver` are optional (addresses remembered for the port are used then).
+//! Sent and received frames are written to stdout in `jsonl` capture format. Terminal exits when stdin is closed.
+
+use std::{io, sync::Arc};
+
+use anyhow::Context as _;
+use eframe::{egui, epaint::ahash::HashMap};
+use proto::Frame;
+use proto_tools::capture::{CaptureWriter, Format};
+use serde::Deserialize;
+use serial_com::{Cmd, DeviceHandle, transport::Target};
+use tokio::{io::{AsyncBufReadExt, BufReader}, sync::{mpsc, oneshot, watch}};
+
+use crate::{Context, Device, events, port_config::PortConfig, settings::{PortSettings, Settings}};
+
+/// line read from stdin
+#[derive(Debug, Deserialize)]
+struct Input {
+    sender: Option<u8>,
+    receiver: Option<u8>,
+    /// payload as hex
+    data: String,
+}
+
+/// exchanges frames with device at `port` over stdin/stdout, until stdin is closed
+pub fn run(runtime: &tokio::runtime::Runtime, port: String, config: PortConfig) -> anyhow::Result<()> {
+    runtime.block_on(run_async(port, config))
+}
+
+async fn run_async(port: String, config: PortConfig) -> anyhow::Result<()> {
+    let (cmd_tx, cmd_rx) = mpsc::channel(1);
+    let (error_tx, mut errors) = mpsc::unbounded_channel();
+    // there is no UI thread, events are applied here
+    let (events_tx, mut events) = mpsc::unbounded_channel();
+
+    let ctx = Arc::new(Context {
+        egui_ctx: egui::Context::default(),
+        runtime: tokio::runtime::Handle::current(),
+
+        events: events_tx,
+        published: watch::channel(Vec::new()).0,
+        closed: Default::default(),
+        cmd_tx,
+        error_tx,
+        plugins: Default::default(),
+    });
+
+    let ctx_cpy = ctx.clone();
+    tokio::spawn(async move {
+        serial_com::SerialHandler::new(ctx_cpy, cmd_rx)
+            .run().await
+    });
+
+    let defaults = Settings::load()
+        .ports
+        .get(&port)
+        .copied()
+        .unwrap_or_default();
+
+    let handle = open(&ctx, &port, config).await?;
+    let mut devices = HashMap::default();
+    devices.insert(handle, Device::new(port, handle, config, defaults));
+
+    let mut out = CaptureWriter::new(io::stdout(), Format::Jsonl)?;
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let Some(line) = line? else {
+                    break;
+                };
+
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                if let Err(err) = send(&ctx, handle, &line, defaults).await {
+                    log::error!("{:?}", err);
+                }
+            },
+            Some((_, err)) = errors.recv() => log::error!("{}", err),
+            Some((device, event)) = events.recv() => events::apply(&ctx, &mut devices, device, event),
+        }
+
+        if let Some(dev) = devices.get_mut(&handle) {
+            write_frames(dev, &mut out)?;
+        }
+    }
+
+    ctx.closed.lock().unwrap().insert(handle);
+    ctx.cmd_tx
+        .send(Cmd::CloseDevice { handle })
+        .await
+        .ok()
+        .context("serial handler stopped")?;
+
+    Ok(())
+}
+
+async fn open(ctx: &Arc<Context>, port: &str, config: PortConfig) -> anyhow::Result<DeviceHandle> {
+    let target = Target::new(port, config.builder(port));
+    let device = target.open().await?;
+
+    let (result_tx, result) = oneshot::channel();
+    ctx.cmd_tx
+        .send(Cmd::RegisterDevice { device, target, queue: Default::default(), traffic: Default::default(), read_only: config.monitor, result: result_tx })
+        .await
+        .ok()
+        .context("serial handler stopped")?;
+
+    Ok(result.await?)
+}
+
+async fn send(ctx: &Arc<Context>, handle: DeviceHandle, line: &str, defaults: PortSettings) -> anyhow::Result<()> {
+    let input: Input = serde_json::from_str(line)
+        .with_context(|| format!("invalid input line `{}`", line))?;
+
+    let frame = Frame {
+        sender: input.sender.unwrap_or(defaults.sender),
+        receiver: input.receiver.unwrap_or(defaults.receiver),
+        data: proto_tools::bytes::parse_hex(&input.data)?,
+    };
+
+    ctx.send_frame(handle, frame).await
+}
+
+/// moves frames collected by the device to stdout, so they don't pile up in memory
+fn write_frames(dev: &mut Device, out: &mut CaptureWriter<io::Stdout>) -> anyhow::Result<()> {
+    for discarded in dev.received.iter().filter_map(|frame| frame.discarded.as_ref()) {
+        log::warn!("discarded frame, reason `{}`", discarded.reason);
+    }
+
+    let records = dev.records();
+    dev.sent.clear();
+    dev.received.clear();
+
+    for record in &records {
+        out.write(record)?;
+    }
+
+    out.flush()
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+use crate::InputMode;
+
+/// number of commands remembered per port
+const MAX_ENTRIES: usize = 100;
+
+/// Previously sent command input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub input: String,
+    #[serde(default)]
+    pub mode: InputMode,
+}
+
+/// Commands sent from a device window, recalled with arrow keys like shell history
+#[derive(Debug, Default)]
+pub struct History {
+    /// oldest first
+    entries: Vec<HistoryEntry>,
+    /// index of recalled entry, `None` while editing new command
+    position: Option<usize>,
+    /// input that was being edited before recalling started
+    draft: Option<HistoryEntry>,
+}
+
+impl History {
+    pub fn new(mut entries: Vec<HistoryEntry>) -> Self {
+        let excess = entries.len().saturating_sub(MAX_ENTRIES);
+        entries.drain(..excess);
+
+        Self {
+            entries,
+            position: None,
+            draft: None,
+        }
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// adds sent command, and stops recalling
+    pub fn push(&mut self, entry: HistoryEntry) {
+        self.position = None;
+        self.draft = None;
+
+        if entry.input.is_empty() || self.entries.last() == Some(&entry) {
+            return;
+        }
+
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+
+        self.entries.push(entry);
+    }
+
+    /// entry before currently recalled one, `current` is kept to be restored by `next`
+    pub fn previous(&mut self, current: HistoryEntry) -> Option<HistoryEntry> {
+        let position = match self.position {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = Some(current);
+                self.entries.len() - 1
+            },
+            Some(0) => return None,
+            Some(position) => position - 1,
+        };
+
+        self.position = Some(position);
+        Some(self.entries[position].clone())
+    }
+
+    /// entry after currently recalled one, or input from before recalling started
+    pub fn next(&mut self) -> Option<HistoryEntry> {
+        let position = self.position? + 1;
+
+        if position < self.entries.len() {
+            self.position = Some(position);
+            Some(self.entries[position].clone())
+        } else {
+            self.position = None;
+            self.draft.take()
+        }
+    }
+}
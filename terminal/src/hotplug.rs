@@ -0,0 +1,90 @@
+use std::{sync::Arc, time::Duration};
+
+use proto_tools::discovery::{self, Bridge};
+#[cfg(feature = "ble")]
+use serial_com::ble::{self, BleDevice};
+use tokio::{sync::watch, time::MissedTickBehavior};
+use tokio_serial::SerialPortInfo;
+
+use crate::Context;
+
+/// enumeration is slow on some platforms, so it's done rarely and off the UI thread
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// how long Bluetooth scan started from the device picker takes
+#[cfg(feature = "ble")]
+const BLE_SCAN_DURATION: Duration = Duration::from_secs(5);
+
+/// starts enumerating serial ports in background, receiver changes only when a port is plugged or unplugged
+pub fn watch(ctx: &Arc<Context>) -> watch::Receiver<Vec<SerialPortInfo>> {
+    let (tx, rx) = watch::channel(Vec::new());
+    ctx.runtime.spawn(poll(ctx.clone(), tx));
+
+    rx
+}
+
+async fn poll(ctx: Arc<Context>, tx: watch::Sender<Vec<SerialPortInfo>>) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    // stops once UI is gone
+    while !tx.is_closed() {
+        interval.tick().await;
+
+        let ports = match tokio::task::spawn_blocking(tokio_serial::available_ports).await {
+            Ok(Ok(ports)) => ports,
+            Ok(Err(err)) => {
+                log::warn!("unable to enumerate serial ports: {}", err);
+                continue;
+            },
+            // runtime is shutting down
+            Err(_) => break,
+        };
+
+        let changed = tx.send_if_modified(|current| {
+            let changed = *current != ports;
+            *current = ports;
+            changed
+        });
+
+        if changed {
+            ctx.egui_ctx.request_repaint();
+        }
+    }
+}
+
+/// looks for Bluetooth LE boards in background, found ones replace previous ones in `found`,
+/// it's done only on request, scanning keeps the radio busy
+#[cfg(feature = "ble")]
+pub fn scan_ble(ctx: &Arc<Context>, found: &Arc<watch::Sender<Vec<BleDevice>>>) {
+    let (app_ctx, found) = (ctx.clone(), found.clone());
+
+    ctx.spawn(async move {
+        let devices = ble::scan(BLE_SCAN_DURATION).await?;
+        log::info!("Bluetooth scan found {} devices", devices.len());
+
+        found.send_replace(devices);
+        app_ctx.egui_ctx.request_repaint();
+        Ok(())
+    });
+}
+
+/// proto-bridge instances announced on the LAN over mDNS, receiver changes when one appears or disappears
+pub fn discover_bridges(ctx: &Arc<Context>) -> watch::Receiver<Vec<Bridge>> {
+    let (tx, rx) = watch::channel(Vec::new());
+    let egui_ctx = ctx.egui_ctx.clone();
+
+    let result = discovery::browse(move |bridges| {
+        log::debug!("bridges on the network: {:?}", bridges);
+        tx.send_replace(bridges);
+        egui_ctx.request_repaint();
+
+        // stops once UI is gone
+        !tx.is_closed()
+    });
+
+    if let Err(err) = result {
+        log::warn!("bridges won't be discovered: {:#}", err);
+    }
+
+    rx
+}
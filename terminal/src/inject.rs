@@ -0,0 +1,109 @@
+use std::{sync::Arc, time::Duration};
+
+use eframe::egui::{self, DragValue};
+use proto::Frame;
+use rand::Rng;
+use serial_com::{DeviceHandle, Framing};
+
+use crate::{Context, DrawableFrame, events::DeviceEvent};
+
+/// How outgoing frames are deliberately broken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Injection {
+    #[default]
+    Off,
+    /// random bit of CRC flipped
+    FlipCrc,
+    /// end byte left out
+    DropEnd,
+    /// frame written in two halves, with a pause between them
+    Split,
+}
+
+/// Debug option corrupting frames sent from device window, to exercise error handling of the firmware
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorInjection {
+    pub injection: Injection,
+    /// pause between halves of a split frame
+    pub split_delay_ms: u64,
+}
+
+impl Default for ErrorInjection {
+    fn default() -> Self {
+        Self {
+            injection: Injection::Off,
+            split_delay_ms: 50,
+        }
+    }
+}
+
+impl Injection {
+    const ALL: [Injection; 4] = [Injection::Off, Injection::FlipCrc, Injection::DropEnd, Injection::Split];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Injection::Off => "off",
+            Injection::FlipCrc => "flip CRC bit",
+            Injection::DropEnd => "drop end byte",
+            Injection::Split => "split across delayed writes",
+        }
+    }
+}
+
+impl ErrorInjection {
+    pub fn is_on(&self) -> bool {
+        self.injection != Injection::Off
+    }
+
+    /// entries of debug menu
+    pub fn draw_menu(&mut self, ui: &mut egui::Ui) {
+        ui.label("break frames sent from this window:");
+
+        for injection in Injection::ALL {
+            ui.radio_value(&mut self.injection, injection, injection.name());
+        }
+
+        ui.add_enabled_ui(self.injection == Injection::Split, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("delay:");
+                ui.add(DragValue::new(&mut self.split_delay_ms).clamp_range(0..=10_000).suffix(" ms"));
+            });
+        });
+    }
+}
+
+/// writes `frame` broken as `injection` says, frames that don't arrive intact are shown as invalid in sent list
+pub async fn send(ctx: Arc<Context>, handle: DeviceHandle, frame: Frame, injection: ErrorInjection) -> anyhow::Result<()> {
+    let wire = frame.serialize()?;
+
+    let (writes, broken) = match injection.injection {
+        Injection::Off => (vec![wire], None),
+        Injection::FlipCrc => {
+            let bit = rand::thread_rng().gen_range(0..32);
+            (vec![proto_tools::fuzz::flip_crc_bit(&frame, bit)?], Some(format!("injected: CRC bit {} flipped", bit)))
+        },
+        Injection::DropEnd => (vec![wire[..wire.len() - 1].to_vec()], Some("injected: end byte dropped".to_owned())),
+        Injection::Split => {
+            let (first, second) = wire.split_at(wire.len() / 2);
+            (vec![first.to_vec(), second.to_vec()], None)
+        },
+    };
+
+    for (i, part) in writes.iter().enumerate() {
+        if i > 0 {
+            tokio::time::sleep(Duration::from_millis(injection.split_delay_ms)).await;
+        }
+
+        // later parts continue the frame started by the first one
+        let framing = if i == 0 { Framing::Frame } else { Framing::Continuation };
+        ctx.write(handle, part.clone(), framing).await?;
+    }
+
+    let sent = match broken {
+        Some(reason) => DrawableFrame::discarded(writes.concat(), reason),
+        None => frame.into(),
+    };
+
+    ctx.post(handle, DeviceEvent::Sent(Box::new(sent)));
+    Ok(())
+}
@@ -0,0 +1,356 @@
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use base64::Engine;
+use eframe::{egui::{self, RichText, TextBuffer, TextEdit}, epaint::{Color32, FontId, text::{LayoutJob, TextFormat}}};
+use egui_number_buffer::NumberBuffer;
+use proto::{Frame, SerializeError, encoding::Encoding};
+use proto_tools::{capture::Direction, schema::Decoded};
+
+use crate::{Context, Device, Discarded, frame_list, plugin::PluginDecode};
+
+/// Field of the frame single wire byte belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Begin,
+    Sender,
+    Receiver,
+    Length,
+    Data,
+    Crc,
+    End,
+}
+
+/// single byte of frame in wire format
+#[derive(Debug, Clone, Copy)]
+pub struct WireByte {
+    pub byte: u8,
+    pub field: Field,
+    /// byte is part of an escape sequence
+    pub escaped: bool,
+}
+
+/// Copy of inspected frame being modified, before it's sent
+pub struct FrameEdit {
+    /// id of the frame edit started from
+    pub source: u64,
+    pub sender: NumberBuffer<3>,
+    pub receiver: NumberBuffer<3>,
+    /// hex bytes, e.g. `01 A0 FF`
+    pub payload: String,
+}
+
+impl FrameEdit {
+    pub fn new(source: u64, frame: &Frame) -> Self {
+        Self {
+            source,
+            sender: NumberBuffer::new(&frame.sender.to_string()),
+            receiver: NumberBuffer::new(&frame.receiver.to_string()),
+            payload: proto_tools::bytes::format_hex(&frame.data),
+        }
+    }
+
+    /// edited frame, its CRC is calculated when it's serialized
+    pub fn frame(&self) -> anyhow::Result<Frame> {
+        let parse = |buf: &NumberBuffer<3>, what: &str| {
+            buf.as_str()
+                .parse::<u8>()
+                .with_context(|| format!("invalid {} address `{}`, expected 0-255", what, buf.as_str()))
+        };
+
+        Ok(Frame {
+            sender: parse(&self.sender, "sender")?,
+            receiver: parse(&self.receiver, "receiver")?,
+            data: proto_tools::bytes::parse_hex(&self.payload)?,
+        })
+    }
+
+    /// addresses and payload inputs, with preview of edited frame
+    ///
+    /// Returns edited frame when it should be sent.
+    fn draw(&mut self, ui: &mut egui::Ui) -> Option<Frame> {
+        let frame = self.frame();
+        let mut send = None;
+
+        ui.horizontal(|ui| {
+            ui.label("S:");
+            ui.add(TextEdit::singleline(&mut self.sender).desired_width(24.0));
+            ui.label("R:");
+            ui.add(TextEdit::singleline(&mut self.receiver).desired_width(24.0));
+
+            if ui.add_enabled(frame.is_ok(), egui::Button::new("Send")).clicked() {
+                send = frame.as_ref().ok().cloned();
+            }
+        });
+
+        ui.add(TextEdit::multiline(&mut self.payload)
+            .font(egui::TextStyle::Monospace)
+            .desired_rows(3)
+            .desired_width(f32::INFINITY))
+            .on_hover_text("payload, hex bytes");
+
+        match frame.as_ref().map(|frame| (frame, wire_bytes(frame))) {
+            Ok((frame, Ok(wire))) => {
+                ui.label(format!("{} bytes, wire bytes with recalculated CRC:", frame.data.len()));
+                ui.label(wire_layout(&wire, FontId::monospace(13.0), ui.visuals().text_color()));
+            },
+            Ok((_, Err(err))) => {
+                ui.colored_label(ui.visuals().error_fg_color, err.to_string());
+            },
+            Err(err) => {
+                ui.colored_label(ui.visuals().error_fg_color, format!("{:#}", err));
+            },
+        }
+
+        send
+    }
+}
+
+/// inspector panel of frame selected in `device`
+pub fn draw_selected(ui: &mut egui::Ui, app_ctx: &Arc<Context>, device: &mut Device) {
+    let Some((direction, frame)) = device.selected_frame() else {
+        ui.weak("click a frame to inspect it");
+        return;
+    };
+
+    let id = frame.id;
+    let plugin = frame.plugin_decode(&app_ctx.plugins).cloned();
+    let timestamp_us = frame.timestamp_us;
+    let discarded = frame.discarded.clone();
+    let frame = frame.inner.clone();
+
+    // edit follows selection
+    if device.edit.as_ref().is_some_and(|edit| edit.source != id) {
+        device.edit = None;
+    }
+
+    let mut send = None;
+
+    egui::ScrollArea::vertical().show(ui, |ui| match discarded.as_ref() {
+        Some(discarded) => draw_discarded(ui, timestamp_us, discarded),
+        None => {
+            draw(ui, direction, timestamp_us, &frame);
+
+            if let Some(decoded) = device.schema.as_ref().and_then(|schema| schema.decode(&frame)) {
+                ui.separator();
+                draw_decoded(ui, &decoded);
+            }
+
+            if let Some((name, decoded)) = plugin.as_ref() {
+                ui.separator();
+                draw_plugin(ui, name, decoded);
+            }
+
+            ui.separator();
+            ui.weak("ctrl + click another frame to compare it with this one, shift + click frames to mark them for export");
+
+            if device.capture.is_some() {
+                return;
+            }
+
+            ui.separator();
+            match device.edit.as_mut() {
+                Some(edit) => send = edit.draw(ui),
+                None => if ui.button("Edit and send").on_hover_text("send modified copy of this frame").clicked() {
+                    device.edit = Some(FrameEdit::new(id, &frame));
+                },
+            }
+        },
+    });
+
+    if let Some(frame) = send {
+        device.send(app_ctx, frame);
+    }
+}
+
+pub fn draw(ui: &mut egui::Ui, direction: Direction, timestamp_us: u64, frame: &Frame) {
+    let wire = wire_bytes(frame);
+
+    egui::Grid::new("header")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            let mut row = |name: &str, value: String| {
+                ui.label(name);
+                ui.label(RichText::new(value).monospace());
+                ui.end_row();
+            };
+
+            row("direction", match direction {
+                Direction::Tx => "sent".into(),
+                Direction::Rx => "received".into(),
+            });
+            row("time", frame_list::format_date_time(timestamp_us));
+            row("sender", format!("{} (0x{:02X})", frame.sender, frame.sender));
+            row("receiver", format!("{} (0x{:02X})", frame.receiver, frame.receiver));
+            row("data length", format!("{} bytes", frame.data.len()));
+            row("crc32", frame.calculate_crc32()
+                .map(|crc| format!("{:08X}", crc))
+                .unwrap_or_else(|err| err.to_string()));
+            row("wire length", match &wire {
+                Ok(wire) => format!("{} bytes ({} escaped)", wire.len(), wire.iter().filter(|b| b.escaped).count() / 2),
+                Err(err) => err.to_string(),
+            });
+        });
+
+    ui.separator();
+    ui.label("wire bytes");
+    match &wire {
+        Ok(wire) => ui.label(wire_layout(wire, FontId::monospace(13.0), ui.visuals().text_color())),
+        Err(_) => ui.weak("none, frame can't be serialized"),
+    };
+
+    ui.separator();
+    ui.label("payload");
+
+    egui::Grid::new("payload")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            let mut row = |name: &str, value: String| {
+                ui.label(name);
+                ui.add(egui::Label::new(RichText::new(value).monospace()).wrap(true));
+                ui.end_row();
+            };
+
+            row("utf-8", String::from_utf8_lossy(&frame.data).into_owned());
+            row("ascii", frame.data.escape_ascii().to_string());
+            row("hex", frame.data.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" "));
+            row("decimal", frame.data.iter().map(|b| b.to_string()).collect::<Vec<_>>().join(" "));
+            row("base64", base64::engine::general_purpose::STANDARD.encode(&frame.data));
+        });
+}
+
+/// fields of payload decoded by user schema
+pub fn draw_decoded(ui: &mut egui::Ui, decoded: &Decoded) {
+    ui.label(format!("fields ({})", decoded.message.name));
+
+    egui::Grid::new("fields")
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            for (field, value) in &decoded.values {
+                ui.label(&field.name);
+                ui.label(RichText::new(field.format(*value)).monospace());
+                ui.weak(format!("{:?} at {}", field.ty, field.offset).to_lowercase());
+                ui.end_row();
+            }
+        });
+}
+
+/// label and fields from decoder plugin called `name`
+fn draw_plugin(ui: &mut egui::Ui, name: &str, decoded: &PluginDecode) {
+    ui.label(format!("decoded by {}", name));
+    ui.label(RichText::new(&decoded.label).strong());
+
+    egui::Grid::new("plugin fields")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            for field in &decoded.fields {
+                ui.label(&field.name);
+                ui.label(RichText::new(&field.value).monospace());
+                ui.end_row();
+            }
+        });
+}
+
+/// shows why received bytes were discarded, and the bytes themselves
+pub fn draw_discarded(ui: &mut egui::Ui, timestamp_us: u64, discarded: &Discarded) {
+    egui::Grid::new("header")
+        .num_columns(2)
+        .striped(true)
+        .show(ui, |ui| {
+            let mut row = |name: &str, value: String| {
+                ui.label(name);
+                ui.label(RichText::new(value).monospace());
+                ui.end_row();
+            };
+
+            row("direction", "received (discarded)".into());
+            row("time", frame_list::format_date_time(timestamp_us));
+            row("error", discarded.reason.clone());
+            row("wire length", format!("{} bytes", discarded.raw.len()));
+        });
+
+    ui.separator();
+    ui.label("wire bytes");
+
+    let dump = discarded.raw
+        .chunks(16)
+        .map(proto_tools::bytes::format_hex)
+        .collect::<Vec<_>>()
+        .join("\n");
+    ui.label(RichText::new(dump).monospace());
+
+    ui.separator();
+    ui.label("ascii");
+    ui.add(egui::Label::new(RichText::new(discarded.raw.escape_ascii().to_string()).monospace()).wrap(true));
+}
+
+/// serializes frame, remembering field every byte belongs to, fails like `Frame::serialize`
+/// (keep in sync with it)
+pub fn wire_bytes(frame: &Frame) -> Result<Vec<WireByte>, SerializeError> {
+    let mut out = vec![WireByte { byte: Frame::BEGIN_FRAME_BYTE, field: Field::Begin, escaped: false }];
+
+    let len = frame.get_command_len()?.to_be_bytes();
+    let crc = frame.calculate_crc32()?.to_be_bytes();
+    let fields = [
+        (Field::Sender, std::slice::from_ref(&frame.sender)),
+        (Field::Receiver, std::slice::from_ref(&frame.receiver)),
+        (Field::Length, len.as_slice()),
+        (Field::Data, frame.data.as_slice()),
+        (Field::Crc, crc.as_slice()),
+    ];
+
+    let mut buf = Vec::with_capacity(2);
+    for (field, bytes) in fields {
+        for byte in bytes {
+            buf.clear();
+            buf.encode(std::slice::from_ref(byte)).unwrap();
+
+            let escaped = buf.len() > 1;
+            out.extend(buf.iter().map(|&byte| WireByte { byte, field, escaped }));
+        }
+    }
+
+    out.push(WireByte { byte: Frame::END_FRAME_BYTE, field: Field::End, escaped: false });
+    Ok(out)
+}
+
+/// hex dump of wire bytes, 16 per row, colored by field, with escape sequences highlighted
+pub fn wire_layout(wire: &[WireByte], font_id: FontId, text_color: Color32) -> LayoutJob {
+    let mut job = LayoutJob::default();
+
+    for (row, bytes) in wire.chunks(16).enumerate() {
+        if row != 0 {
+            job.append("\n", 0.0, TextFormat::simple(font_id.clone(), text_color));
+        }
+
+        job.append(&format!("{:04X}  ", row * 16), 0.0, TextFormat::simple(font_id.clone(), Color32::DARK_GRAY));
+
+        for b in bytes {
+            let format = TextFormat {
+                background: if b.escaped { Color32::from_rgb(110, 40, 40) } else { Color32::TRANSPARENT },
+                ..TextFormat::simple(font_id.clone(), b.field.color(text_color))
+            };
+
+            job.append(&format!("{:02X}", b.byte), 0.0, format);
+            job.append(" ", 0.0, TextFormat::simple(font_id.clone(), text_color));
+        }
+    }
+
+    job
+}
+
+impl Field {
+    pub fn color(&self, text_color: Color32) -> Color32 {
+        match self {
+            Field::Begin | Field::End => Color32::LIGHT_BLUE,
+            Field::Sender | Field::Receiver => Color32::from_rgb(230, 200, 90),
+            Field::Length => Color32::LIGHT_GREEN,
+            Field::Data => text_color,
+            Field::Crc => Color32::from_rgb(230, 130, 130),
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context as _;
+use eframe::egui::{self, Key, KeyboardShortcut, Modifiers, TextEdit};
+use serde::{Deserialize, Serialize};
+
+/// Something done from the keyboard, in the device window in front
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// send command input, only while the input is focused
+    Send,
+    Clear,
+    Search,
+    Palette,
+    /// bookmark selected frame, or remove its bookmark
+    Bookmark,
+    NextBookmark,
+    PreviousBookmark,
+}
+
+impl Action {
+    pub const ALL: [Action; 7] = [
+        Action::Send,
+        Action::Clear,
+        Action::Search,
+        Action::Palette,
+        Action::Bookmark,
+        Action::NextBookmark,
+        Action::PreviousBookmark,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Send => "send input",
+            Action::Clear => "clear frame lists",
+            Action::Search => "search",
+            Action::Palette => "command palette",
+            Action::Bookmark => "bookmark selected frame",
+            Action::NextBookmark => "next bookmark",
+            Action::PreviousBookmark => "previous bookmark",
+        }
+    }
+
+    fn default_shortcut(&self) -> &'static str {
+        match self {
+            Action::Send => "Enter",
+            Action::Clear => "Ctrl+L",
+            Action::Search => "Ctrl+F",
+            Action::Palette => "Ctrl+K",
+            Action::Bookmark => "Ctrl+B",
+            Action::NextBookmark => "F2",
+            Action::PreviousBookmark => "Shift+F2",
+        }
+    }
+}
+
+/// Shortcuts changed by user, as text like `Ctrl+Shift+K`, so that settings file stays editable by hand
+///
+/// Actions missing here use their default shortcut, empty text unbinds them.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Keybindings {
+    bindings: BTreeMap<Action, String>,
+}
+
+impl Keybindings {
+    /// shortcut of `action`, `None` if it's unbound or invalid
+    pub fn shortcut(&self, action: Action) -> Option<KeyboardShortcut> {
+        parse(self.text(action)).ok().flatten()
+    }
+
+    /// true if shortcut of `action` was pressed, it's consumed so that nothing else reacts to it
+    pub fn pressed(&self, ctx: &egui::Context, action: Action) -> bool {
+        self.shortcut(action)
+            .is_some_and(|shortcut| ctx.input_mut(|i| i.consume_shortcut(&shortcut)))
+    }
+
+    /// shortcut of `action` formatted for the current platform, empty if unbound
+    pub fn format(&self, ctx: &egui::Context, action: Action) -> String {
+        self.shortcut(action)
+            .map(|shortcut| ctx.format_shortcut(&shortcut))
+            .unwrap_or_default()
+    }
+
+    fn text(&self, action: Action) -> &str {
+        self.bindings
+            .get(&action)
+            .map_or(action.default_shortcut(), String::as_str)
+    }
+
+    /// editor of all shortcuts, returns true if any of them changed
+    pub fn draw(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+
+        egui::Grid::new("keybindings").num_columns(3).show(ui, |ui| {
+            for action in Action::ALL {
+                ui.label(action.name());
+
+                let mut text = self.text(action).to_owned();
+                let error = parse(&text).err();
+                let resp = ui.add(TextEdit::singleline(&mut text)
+                    .desired_width(100.0)
+                    .hint_text("unbound")
+                    .text_color_opt(error.is_some().then_some(ui.visuals().error_fg_color)));
+
+                if let Some(error) = error {
+                    resp.on_hover_text(format!("{:#}", error));
+                }
+
+                // stored even if it doesn't parse yet, while it is being typed
+                if text != self.text(action) {
+                    self.bindings.insert(action, text);
+                    changed = true;
+                }
+
+                if ui.add_enabled(self.bindings.contains_key(&action), egui::Button::new("⟲"))
+                    .on_hover_text(format!("reset to {}", action.default_shortcut()))
+                    .clicked()
+                {
+                    self.bindings.remove(&action);
+                    changed = true;
+                }
+
+                ui.end_row();
+            }
+        });
+
+        ui.weak("e.g. Ctrl+Shift+K, Ctrl is Cmd on macOS");
+
+        changed
+    }
+}
+
+/// parses shortcut like `Ctrl+Shift+K`, empty text is no shortcut
+fn parse(text: &str) -> anyhow::Result<Option<KeyboardShortcut>> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    let mut parts = text.split('+').map(str::trim).collect::<Vec<_>>();
+    let key = parts.pop().unwrap_or_default();
+
+    let mut modifiers = Modifiers::NONE;
+    for part in parts {
+        modifiers = modifiers | match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "cmd" => Modifiers::COMMAND,
+            "shift" => Modifiers::SHIFT,
+            "alt" => Modifiers::ALT,
+            _ => anyhow::bail!("unknown modifier {:?}", part),
+        };
+    }
+
+    let key = Key::from_name(key)
+        .with_context(|| format!("unknown key {:?}", key))?;
+
+    Ok(Some(KeyboardShortcut::new(modifiers, key)))
+}
@@ -0,0 +1,140 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use eframe::egui;
+use egui_plot::{Bar, BarChart, Plot};
+use proto_tools::schema::Schema;
+
+use crate::{DrawableFrame, pairing::{self, Exchange}};
+
+/// number of bars of the histogram
+const BINS: u64 = 30;
+
+/// Response latency per command type, with histogram of the picked one
+#[derive(Debug, Default)]
+pub struct LatencyView {
+    /// command type the histogram is shown for, all of them if `None`
+    selected: Option<String>,
+}
+
+/// name of command `request` is, its schema message if there is one, otherwise first payload byte
+fn command(request: &DrawableFrame, schema: Option<&Schema>) -> String {
+    if let Some(decoded) = schema.and_then(|schema| schema.decode(&request.inner)) {
+        return decoded.message.name.clone();
+    }
+
+    match request.inner.data.first() {
+        Some(byte) => format!("0x{:02X}", byte),
+        None => "(empty)".into(),
+    }
+}
+
+/// round trip times of answered requests in `sent`, sorted, by command type
+fn collect(sent: &VecDeque<DrawableFrame>, exchanges: &HashMap<u64, Exchange>, schema: Option<&Schema>) -> BTreeMap<String, Vec<u64>> {
+    let mut commands = BTreeMap::<_, Vec<_>>::new();
+
+    for request in sent {
+        if let Some(exchange) = exchanges.get(&request.id) {
+            commands.entry(command(request, schema)).or_default().push(exchange.rtt_us);
+        }
+    }
+
+    for rtts in commands.values_mut() {
+        rtts.sort_unstable();
+    }
+
+    commands
+}
+
+/// nearest-rank percentile of non empty `sorted`, `p` in 0..=100
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+impl LatencyView {
+    pub fn draw(
+        &mut self,
+        ui: &mut egui::Ui,
+        sent: &VecDeque<DrawableFrame>,
+        exchanges: &HashMap<u64, Exchange>,
+        schema: Option<&Schema>,
+    ) {
+        let commands = collect(sent, exchanges, schema);
+
+        if commands.is_empty() {
+            ui.weak("no answered requests");
+            return;
+        }
+
+        if self.selected.as_ref().is_some_and(|selected| !commands.contains_key(selected)) {
+            self.selected = None;
+        }
+
+        egui::Grid::new("latency")
+            .num_columns(6)
+            .striped(true)
+            .show(ui, |ui| {
+                for header in ["command", "count", "p50", "p95", "p99", "max"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+
+                for (command, rtts) in &commands {
+                    let selected = self.selected.as_ref() == Some(command);
+                    if ui.selectable_label(selected, command).on_hover_text("show histogram of this command").clicked() {
+                        self.selected = if selected { None } else { Some(command.clone()) };
+                    }
+
+                    ui.monospace(rtts.len().to_string());
+                    for p in [50.0, 95.0, 99.0] {
+                        ui.monospace(pairing::format_rtt(percentile(rtts, p)));
+                    }
+                    ui.monospace(pairing::format_rtt(*rtts.last().unwrap()));
+                    ui.end_row();
+                }
+            });
+
+        let rtts = match self.selected.as_ref() {
+            Some(selected) => commands[selected].clone(),
+            None => {
+                let mut rtts = commands.into_values().flatten().collect::<Vec<_>>();
+                rtts.sort_unstable();
+                rtts
+            },
+        };
+
+        Self::draw_histogram(ui, &rtts, self.selected.as_deref().unwrap_or("all commands"));
+    }
+
+    /// counts of `rtts` (sorted) in equally wide bins, in milliseconds
+    fn draw_histogram(ui: &mut egui::Ui, rtts: &[u64], name: &str) {
+        let (min, max) = (rtts[0], *rtts.last().unwrap());
+        let width = ((max - min) / BINS).max(1);
+
+        let mut counts = vec![0u32; BINS as usize + 1];
+        for rtt in rtts {
+            counts[((rtt - min) / width) as usize] += 1;
+        }
+
+        let bars = counts
+            .into_iter()
+            .enumerate()
+            .filter(|(_, count)| *count > 0)
+            .map(|(i, count)| {
+                let start = min + i as u64 * width;
+                Bar::new((start as f64 + width as f64 / 2.0) / 1000.0, count as f64)
+                    .width(width as f64 / 1000.0)
+                    .name(format!("{} - {}", pairing::format_rtt(start), pairing::format_rtt(start + width)))
+            })
+            .collect();
+
+        ui.separator();
+        Plot::new("latency histogram")
+            .height(ui.available_height().max(150.0))
+            .x_axis_label("RTT [ms]")
+            .y_axis_label("responses")
+            .show(ui, |plot_ui| {
+                plot_ui.bar_chart(BarChart::new(bars).name(name));
+            });
+    }
+}
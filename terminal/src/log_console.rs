@@ -0,0 +1,165 @@
+//! Log output kept in memory, shown in the log window, in addition to stderr
+
+use std::{collections::VecDeque, sync::Mutex, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+use eframe::egui::{self, ComboBox, ScrollArea};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::frame_list;
+
+/// oldest lines are dropped above this
+const CAPACITY: usize = 5000;
+
+static LINES: Mutex<VecDeque<Line>> = Mutex::new(VecDeque::new());
+
+struct Line {
+    timestamp_us: u64,
+    level: Level,
+    target: String,
+    message: String,
+}
+
+/// writes to stderr as `RUST_LOG` says, and keeps everything up to info level for the window
+struct Logger {
+    stderr: env_logger::Logger,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Info || self.stderr.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.stderr.matches(record) {
+            self.stderr.log(record);
+        }
+
+        if record.level() > Level::Info {
+            return;
+        }
+
+        let timestamp_us = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_micros() as u64);
+
+        let mut lines = LINES.lock().unwrap();
+        if lines.len() == CAPACITY {
+            lines.pop_front();
+        }
+
+        lines.push_back(Line {
+            timestamp_us,
+            level: record.level(),
+            target: record.target().to_owned(),
+            message: record.args().to_string(),
+        });
+    }
+
+    fn flush(&self) {
+        self.stderr.flush();
+    }
+}
+
+/// installs logger, stderr output defaults to info level
+pub fn init() {
+    let stderr = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+    let max_level = stderr.filter().max(LevelFilter::Info);
+
+    log::set_boxed_logger(Box::new(Logger { stderr })).expect("logger is installed once");
+    log::set_max_level(max_level);
+}
+
+/// Window with captured log lines
+#[derive(Debug)]
+pub struct LogConsole {
+    pub open: bool,
+    /// most verbose level shown
+    level: LevelFilter,
+}
+
+impl Default for LogConsole {
+    fn default() -> Self {
+        Self {
+            open: false,
+            level: LevelFilter::Info,
+        }
+    }
+}
+
+impl LogConsole {
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let mut open = self.open;
+
+        egui::Window::new("Log")
+            .open(&mut open)
+            .default_size([700.0, 300.0])
+            .show(ctx, |ui| self.draw(ui));
+
+        self.open = open;
+
+        // logger can't repaint itself, egui may log while its context is locked
+        if self.open {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+    }
+
+    fn draw(&mut self, ui: &mut egui::Ui) {
+        let (mut copy, mut clear) = (false, false);
+
+        ui.horizontal(|ui| {
+            ui.label("Level:");
+            ComboBox::from_id_source("log level")
+                .selected_text(self.level.as_str().to_lowercase())
+                .show_ui(ui, |ui| {
+                    for level in [LevelFilter::Error, LevelFilter::Warn, LevelFilter::Info] {
+                        ui.selectable_value(&mut self.level, level, level.as_str().to_lowercase());
+                    }
+                });
+
+            copy = ui.button("Copy").on_hover_text("copy shown lines to clipboard").clicked();
+            clear = ui.button("Clear").clicked();
+        });
+
+        // lock isn't held while drawing, anything logging from UI code would deadlock
+        let shown = {
+            let mut lines = LINES.lock().unwrap();
+            if clear {
+                lines.clear();
+            }
+
+            lines
+                .iter()
+                .filter(|line| line.level <= self.level)
+                .map(|line| (line.level, format_line(line)))
+                .collect::<Vec<_>>()
+        };
+
+        if copy {
+            let text = shown.iter().map(|(_, text)| text.as_str()).collect::<Vec<_>>().join("\n");
+            ui.output_mut(|o| o.copied_text = text);
+        }
+
+        ui.separator();
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        ScrollArea::both()
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .show_rows(ui, row_height, shown.len(), |ui, rows| {
+                for (level, text) in &shown[rows] {
+                    let color = match level {
+                        Level::Error => ui.visuals().error_fg_color,
+                        Level::Warn => ui.visuals().warn_fg_color,
+                        _ => ui.visuals().text_color(),
+                    };
+
+                    ui.colored_label(color, egui::RichText::new(text).monospace());
+                }
+            });
+    }
+}
+
+/// e.g. `14:03:27.512 INFO terminal::serial_com: SENDING FRAME: ...`
+fn format_line(line: &Line) -> String {
+    format!("{} {:<5} {}: {}", frame_list::format_time(line.timestamp_us), line.level, line.target, line.message)
+}
@@ -1,54 +1,372 @@
-use std::{time::Duration, sync::Arc};
-
+use std::{cell::{Cell, OnceCell, RefCell}, collections::{HashSet, VecDeque}, future::Future, path::{Path, PathBuf}, time::{Duration, SystemTime}, sync::{Arc, Mutex, atomic::{AtomicU64, Ordering}}};
+
+use activity::Activity;
+use appearance::Theme;
+use batch_send::BatchSend;
+use composer::Composer;
+use console::{LineEnding, RawConsole};
+use copy_format::CopyFormat;
+use crc_tool::CrcCalculator;
+use dashboard::Dashboard;
+use decoder::DecoderState;
+use bridge::{BridgeEvent, DeviceInfo};
+use broadcast_send::Broadcast;
+use dock::{DeviceTab, DeviceTabs};
+use dfu::Dfu;
+use events::DeviceEvent;
+use file_send::{FileOptions, FileSend};
+use periodic_send::PeriodicSend;
+use filter::FrameFilter;
+use frame_log::FrameLog;
+use frame_list::{ColorMode, FrameList, Selection, TimeMode};
+use fuzz::Fuzzer;
+use history::{History, HistoryEntry};
+use inject::ErrorInjection;
+use inspector::FrameEdit;
+use keybindings::Action;
+use latency::LatencyView;
+use log_console::LogConsole;
+use mqtt_bridge::{MqttBridge, MqttConfig};
+use notify::Notifier;
+use palette::{Command, Palette};
+use plot::PayloadPlot;
+use project::{Project, ProjectDevice};
+use port_config::{BaudSelector, PortConfig};
+use plugin::{PluginDecode, Plugins};
+use render::{PayloadFormat, PayloadView};
+use responder::AutoResponder;
+use rest_api::RestApi;
+use search::{Search, SearchAction};
+use session::{Session, DeviceSession};
+use templates::Template;
+use throughput::ThroughputView;
+use trigger::TriggerCapture;
+use views::DeviceView;
+use text_import::TextImport;
+use watches::Watches;
+use ws_bridge::WsBridge;
+
+use anyhow::Context as _;
+use clap::Parser;
+use egui_dock::{DockArea, DockState};
 use egui_number_buffer::NumberBuffer;
-use egui_toast::{Toast, Toasts, ToastOptions};
-use proto::Frame;
-use eframe::{egui::{self, Direction, ComboBox, TextEdit, Response, ScrollArea, Id}, epaint::{ahash::HashMap, Color32, FontId, text::LayoutJob}, emath::Align2};
-use serial_com::Cmd;
-use tokio::sync::{mpsc::{Sender, UnboundedReceiver, unbounded_channel, UnboundedSender, error::TryRecvError}, oneshot};
-
-mod serial_com;
-use serial_com::DeviceHandle;
+use egui_toast::{Toast, ToastKind, Toasts, ToastOptions};
+use proto::{Frame, transfer};
+use proto_tools::{capture::{Direction as FrameDirection, Record}, discovery::Bridge, schema::Schema};
+use eframe::{egui::{self, Direction, ComboBox, TextEdit, Response, TextBuffer, Key, text::CCursor, text_edit::CCursorRange}, epaint::{ahash::HashMap, Color32, FontId, text::{LayoutJob, TextFormat}}, emath::Align2};
+#[cfg(feature = "ble")]
+use serial_com::ble::BleDevice;
+use serial_com::{Cmd, ConnectionState, DeviceHandle, DirectionLine, Framing, LineControl, Pacing, QueuePolicy, Received, ReconnectPolicy, Rs485, SerialComError, Timeouts, Traffic, TxQueue, transport::{self, Target}};
+use settings::{Settings, PortSettings};
+use tokio::sync::{broadcast, mpsc::{Sender, UnboundedReceiver, unbounded_channel, UnboundedSender, error::TryRecvError}, oneshot, watch};
+use tokio_serial::SerialPortInfo;
+
+mod activity;
+mod appearance;
+mod batch_send;
+mod bridge;
+mod broadcast_send;
+mod composer;
+mod console;
+mod copy_format;
+mod crc_tool;
+mod dashboard;
+mod decoder;
+mod dfu;
+mod diff;
+mod dock;
+mod events;
+mod file_send;
+mod filter;
+mod frame_log;
+mod frame_list;
+mod fuzz;
+mod headless;
+mod history;
+mod hotplug;
+mod inject;
+mod inspector;
+mod keybindings;
+mod latency;
+mod log_console;
+mod mqtt_bridge;
+mod notify;
+mod pairing;
+mod palette;
+mod paste;
+mod periodic_send;
+mod plot;
+mod plugin;
+mod port_config;
+mod profiles;
+mod project;
+mod render;
+mod responder;
+mod rest_api;
+mod search;
+mod session;
+mod settings;
+mod templates;
+mod text_import;
+mod throughput;
+mod transcript;
+mod trigger;
+mod views;
+mod watches;
+mod ws_bridge;
+
+static FRAME_COUNTER: AtomicU64 = AtomicU64::new(0);
+/// bumped whenever a schema is loaded, reloaded or unloaded, so layouts made with the previous one are redone
+static SCHEMA_GENERATION: AtomicU64 = AtomicU64::new(0);
+/// frames kept in each list of a newly opened device
+const DEFAULT_FRAME_LIMIT: usize = 100_000;
 
 /// Wrapper around `Frame`, so it can be displayed in the UI
 pub struct DrawableFrame {
+    /// unique across all devices, stays the same when lists are modified
+    pub id: u64,
     inner: Frame,
+    /// microseconds since unix epoch, taken when frame was sent or received
+    pub timestamp_us: u64,
     /// cached
     crc32: Option<u32>,
     /// cached
     frame_length: Option<usize>,
+    /// set if received bytes failed to deserialize, `inner` is empty then
+    pub discarded: Option<Discarded>,
+    /// overrides payload format of the list, picked from context menu
+    pub format: Cell<Option<PayloadFormat>>,
+    /// result of the first plugin recognizing the frame, with its name, decoded when it's first needed
+    plugin: OnceCell<Option<(String, PluginDecode)>>,
+    /// text of the last drawn row, rebuilt only when something it's made of changes
+    layout: RefCell<Option<(LayoutKey, LayoutJob)>>,
+}
+
+/// Everything a frame row layout depends on, besides the frame itself
+#[derive(PartialEq)]
+struct LayoutKey {
+    width: f32,
+    time: String,
+    color: Color32,
+    font_size: f32,
+    wire: bool,
+    format: PayloadFormat,
+    frame_format: Option<PayloadFormat>,
+    schema_generation: u64,
+}
+
+/// bytes received between frame delimiters, that didn't form a valid frame
+#[derive(Debug, Clone)]
+pub struct Discarded {
+    pub reason: String,
+    /// wire bytes, including delimiters
+    pub raw: Vec<u8>,
 }
 
 /// shared context between gui and background thread
 pub struct Context {
     pub egui_ctx: egui::Context,
     pub runtime: tokio::runtime::Handle,
-    pub devices: tokio::sync::Mutex<HashMap<DeviceHandle, Device>>,
+    /// changes of devices made in background, devices themselves are owned by the UI thread
+    pub events: UnboundedSender<(DeviceHandle, DeviceEvent)>,
+    /// devices with publishing enabled, updated by the UI thread every frame
+    pub published: watch::Sender<Vec<DeviceInfo>>,
+    /// devices closed by the user, their workers keep reading until the handler stops them
+    pub closed: Mutex<HashSet<DeviceHandle>>,
 
     pub cmd_tx: Sender<Cmd>,
-    pub error_tx: UnboundedSender<String>,
+    /// messages shown as toasts of given kind
+    pub error_tx: UnboundedSender<(ToastKind, String)>,
+    /// decoders of frames, loaded at startup
+    pub plugins: Plugins,
 }
 
 /// represents connected (and selected) device
 pub struct Device {
     pub name: String,
+    /// name given by user, e.g. `Motor controller A`, empty if there is none
+    pub alias: String,
+    /// what `alias` is saved under in settings, `None` for devices without a port
+    pub alias_key: Option<String>,
+    pub config: PortConfig,
     pub cmd_input: String,
+    /// previously sent inputs, recalled with arrow keys
+    pub history: History,
+    pub input_mode: InputMode,
+    /// command input is written as is, without framing, e.g. for bootloaders or AT commands
+    pub raw_send: bool,
+    /// appended to command input sent in raw mode
+    pub line_ending: LineEnding,
+    pub sender: NumberBuffer<3>,
+    pub receiver: NumberBuffer<3>,
     pub handle: DeviceHandle,
-    pub received: Vec<DrawableFrame>,
-    pub sent: Vec<DrawableFrame>,
+    pub received: VecDeque<DrawableFrame>,
+    pub sent: VecDeque<DrawableFrame>,
+    /// maximum number of frames kept in each of the lists, oldest are dropped first
+    pub frame_limit: usize,
+    /// frames dropped from sent list because of `frame_limit`
+    pub dropped_sent: u64,
+    /// frames dropped from received list because of `frame_limit`
+    pub dropped_received: u64,
+    /// maximum payload size of frames file is split into, empty to send file as one frame
+    pub file_chunk_size: NumberBuffer<5>,
+    /// address file is sent to, empty to send it to the receiver of the device
+    pub file_receiver: NumberBuffer<3>,
+    /// times a frame of file is sent again before sending fails
+    pub file_retries: u32,
+    /// send file with `proto::transfer`, acknowledged by the receiver
+    pub file_acknowledged: bool,
+    pub file_send: Option<Arc<FileSend>>,
+    /// period of repeated sending, in milliseconds
+    pub repeat_period: NumberBuffer<6>,
+    pub periodic_send: Option<Arc<PeriodicSend>>,
+    /// frames loaded from file, shown in a separate window
+    pub batch: Option<BatchSend>,
+    /// frame decoded from clipboard, waiting to be sent
+    pub pasted: Option<Frame>,
+    /// number of templates sent, for `{seq}` placeholder
+    template_seq: u64,
+    /// frames picked in the lists, by their ids
+    pub selection: Selection,
+    /// modified copy of the selected frame, shown in the inspector
+    pub edit: Option<FrameEdit>,
+    pub filter: FrameFilter,
+    pub search: Search,
+    /// move keyboard focus to search query in the next frame
+    pub focus_search: bool,
+    pub palette: Palette,
+    /// id of frame list should scroll to in the next frame
+    pub scroll_to: Option<u64>,
+    pub time_mode: TimeMode,
+    /// tint frames by sender or receiver address
+    pub color_mode: ColorMode,
+    /// file all traffic is appended to
+    pub log: Option<FrameLog>,
+    /// top left corner of device window, as it was last drawn
+    pub window_pos: Option<egui::Pos2>,
+    /// shown in its own OS window (viewport), instead of inside the main one
+    pub detached: bool,
+    /// file frames were loaded from, if device is a read-only capture viewer
+    pub capture: Option<PathBuf>,
+    /// show frames that failed to deserialize in received list
+    pub show_discarded: bool,
+    /// color of new bookmarks
+    pub bookmark_color: Color32,
+    /// consecutive identical frames are shown as one row
+    pub collapse_repeats: bool,
+    /// frame lists follow new frames
+    pub auto_scroll: bool,
+    /// show escaped wire bytes of frames instead of their payload
+    pub show_wire: bool,
+    /// how payloads are shown, unless frame overrides it
+    pub payload_format: PayloadFormat,
+    /// user defined payload layouts, matching payloads are shown as fields
+    pub schema: Option<Arc<Schema>>,
+    /// value of `SCHEMA_GENERATION` when `schema` was set
+    pub schema_generation: u64,
+    /// file `schema` was loaded from
+    pub schema_path: Option<PathBuf>,
+    pub plot: PayloadPlot,
+    /// response latency of requests, shown in its panel
+    pub latency: LatencyView,
+    /// frame built field by field in its panel
+    pub composer: Composer,
+    /// payload values followed live in their panel
+    pub watches: Watches,
+    /// frame and byte rates over time, shown in their panel
+    pub throughput: ThroughputView,
+    /// all received bytes as text, shown in its panel
+    pub console: RawConsole,
+    /// additional windows onto the device
+    pub views: Vec<DeviceView>,
+    /// state of frame parser, shown in stats panel
+    pub decoder: DecoderState,
+    /// arrangement of panels in device window
+    pub dock: DockState<DeviceTab>,
+    pub responder: AutoResponder,
+    pub fuzzer: Fuzzer,
+    /// firmware update dialog
+    pub dfu: Dfu,
+    pub notifier: Notifier,
+    /// records frames around a matching received one
+    pub trigger: TriggerCapture,
+    /// corrupts sent frames on purpose
+    pub injection: ErrorInjection,
+    /// `serial_com` reopens port after it's unplugged, as `reconnect` says
+    pub connection: ConnectionState,
+    pub reconnect: ReconnectPolicy,
+    /// `rs485` direction control is used, it's kept while disabled
+    pub rs485_enabled: bool,
+    pub rs485: Rs485,
+    /// requested state of DTR line
+    pub dtr: bool,
+    /// requested state of RTS line
+    pub rts: bool,
+    /// received frames longer than this (in wire bytes) are dropped, enforced by `serial_com`
+    pub max_frame_len: usize,
+    /// limits of transmit rate, enforced by `serial_com`
+    pub pacing: Pacing,
+    /// limits of how long the device may not respond, enforced by `serial_com`
+    pub timeouts: Timeouts,
+    /// writes waiting to be done by `serial_com`
+    pub tx_queue: Arc<TxQueue>,
+    /// bytes and frames counted by `serial_com`, shown at the top of device window
+    pub activity: Activity,
+    /// frames are shared through running bridges (WebSocket, MQTT)
+    pub publish: bool,
+    /// set while device is published
+    pub bridge: Option<broadcast::Sender<BridgeEvent>>,
+}
+
+/// how contents of the command input are turned into payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum InputMode {
+    /// UTF-8 bytes of the input
+    #[default]
+    Text,
+    /// hex bytes, e.g. `01 A0 FF`
+    Hex,
+}
+
+#[derive(Debug, Parser)]
+#[command(about = "Terminal for devices speaking the framed protocol")]
+struct Args {
+    /// run without window, sending JSON lines from stdin and writing sent and received frames to stdout
+    #[arg(long, requires = "port")]
+    headless: bool,
+
+    /// serial port (or network device, e.g. `tcp://host:port`) opened in headless mode
+    port: Option<String>,
+
+    /// baud rate used in headless mode
+    #[arg(short, long, default_value_t = 115200)]
+    baud_rate: u32,
 }
 
 fn main() -> anyhow::Result<()> {
-    // setup logging
-    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
+    let args = Args::parse();
+
+    // setup logging, to stderr and log window
+    log_console::init();
 
     // create tokio runtime (for serial port communication)
     let runtime = create_runtime();
 
+    if let (true, Some(port)) = (args.headless, args.port) {
+        let config = PortConfig { baud_rate: args.baud_rate, ..Default::default() };
+        let result = headless::run(&runtime, port, config);
+
+        runtime.shutdown_timeout(Duration::from_secs(1));
+        return result;
+    }
+
+    let settings = Settings::load();
+
     // basic settings for window
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1280.0, 720.0]),
+        follow_system_theme: settings.theme == Theme::System,
 
         ..Default::default()
     };
@@ -59,20 +377,24 @@ fn main() -> anyhow::Result<()> {
         "terminal",
         options,
         Box::new(move |cctx| {
-            cctx.egui_ctx.set_pixels_per_point(0.9 as _);
+            appearance::apply(&cctx.egui_ctx, &settings, cctx.integration_info.system_theme);
             
             // spsc channel for communication with `serial_com` task
             let (cmd_tx, cmd_rx) = tokio::sync::mpsc::channel(1);
             let (err_tx, err_rx) = unbounded_channel();
+            let (events_tx, events_rx) = unbounded_channel();
 
             // context shared between UI and COM threads
             let ctx = Arc::new(Context {
                 egui_ctx: cctx.egui_ctx.clone(),
                 runtime: handle,
 
-                devices: Default::default(),
+                events: events_tx,
+                published: watch::channel(Vec::new()).0,
+                closed: Default::default(),
                 cmd_tx,
                 error_tx: err_tx,
+                plugins: Plugins::load(),
             });
 
             // spawn thread for COM communication
@@ -81,15 +403,40 @@ fn main() -> anyhow::Result<()> {
                 .spawn(async move {
                     serial_com::SerialHandler::new(ctx_cpy, cmd_rx)
                         .run().await
-                        .unwrap()
                 });
 
+            let ports = hotplug::watch(&ctx);
+            let bridges = hotplug::discover_bridges(&ctx);
+
             // UI window
             Box::new(
                 App {
                     ctx,
-                    new_device_selection: Default::default(),
-                    baud_rate: NumberBuffer::new("115200"),
+                    devices: Default::default(),
+                    events: events_rx,
+                    new_device_selection: settings.last_port.clone(),
+                    ports,
+                    #[cfg(feature = "ble")]
+                    ble_devices: Arc::new(watch::channel(Vec::new()).0),
+                    bridges,
+                    baud_rate: BaudSelector::new(settings.port_config.baud_rate),
+                    port_config: settings.port_config,
+                    settings,
+                    pending_session: Session::load(),
+                    templates_open: false,
+                    appearance_open: false,
+                    crc_calculator: Default::default(),
+                    text_import: Default::default(),
+                    broadcast: Default::default(),
+                    dashboard: Default::default(),
+                    log_console: Default::default(),
+                    ws_addr: "127.0.0.1:9001".into(),
+                    ws_bridge: None,
+                    mqtt_config: Default::default(),
+                    mqtt_bridge: None,
+                    api_addr: "127.0.0.1:8080".into(),
+                    rest_api: None,
+                    bridge_events: broadcast::channel(bridge::EVENT_CAPACITY).0,
 
                     toasts: Toasts::new()
                         .direction(Direction::BottomUp)
@@ -113,90 +460,429 @@ fn create_runtime() -> tokio::runtime::Runtime {
 
 struct App {
     ctx: Arc<Context>,
+    devices: HashMap<DeviceHandle, Device>,
+    /// changes of `devices` posted by background tasks
+    events: UnboundedReceiver<(DeviceHandle, DeviceEvent)>,
     new_device_selection: String,
-    baud_rate: NumberBuffer<6>,
+    /// serial ports present in the system, enumerated in background
+    ports: watch::Receiver<Vec<SerialPortInfo>>,
+    /// Bluetooth LE boards found by the last scan
+    #[cfg(feature = "ble")]
+    ble_devices: Arc<watch::Sender<Vec<BleDevice>>>,
+    /// proto-bridge instances announced on the LAN
+    bridges: watch::Receiver<Vec<Bridge>>,
+    baud_rate: BaudSelector,
+    /// line parameters for newly opened ports, baud rate is taken from `baud_rate`
+    port_config: PortConfig,
+    settings: Settings,
+    /// previous session, until user decides whether to restore it
+    pending_session: Option<Session>,
+    templates_open: bool,
+    appearance_open: bool,
+    crc_calculator: CrcCalculator,
+    text_import: TextImport,
+    broadcast: Broadcast,
+    dashboard: Dashboard,
+    log_console: LogConsole,
+    /// address WebSocket bridge listens on
+    ws_addr: String,
+    ws_bridge: Option<WsBridge>,
+    mqtt_config: MqttConfig,
+    mqtt_bridge: Option<MqttBridge>,
+    /// address REST API listens on
+    api_addr: String,
+    rest_api: Option<RestApi>,
+    /// frames of published devices, for all running bridges
+    bridge_events: broadcast::Sender<BridgeEvent>,
 
     toasts: Toasts,
-    errors: UnboundedReceiver<String>,
+    errors: UnboundedReceiver<(ToastKind, String)>,
 }
 
 impl eframe::App for App {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        let devices = tokio_serial::available_ports().unwrap();
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        let devices = self.ports.borrow().clone();
         
         // draw main window
         egui::Window::new(format!("{} devices connected", devices.len()))
             .id(egui::Id::new("main window"))
             .show(ctx, |ui| {
+                egui::menu::bar(ui, |ui| {
+                    ui.menu_button("File", |ui| {
+                        if ui.button("Open capture…").clicked() {
+                            ui.close_menu();
+
+                            let path = rfd::FileDialog::new()
+                                .add_filter("captures", &["jsonl", "json", "csv", "pcapng", "pcap", "hex", "txt"])
+                                .pick_file();
+
+                            if let Some(path) = path {
+                                self.open_capture(path);
+                            }
+                        }
+
+                        ui.separator();
+
+                        if ui.button("Open project…").on_hover_text("open devices of a saved bench configuration").clicked() {
+                            ui.close_menu();
+
+                            let path = rfd::FileDialog::new()
+                                .add_filter("projects", &[project::EXTENSION])
+                                .pick_file();
+
+                            if let Some(path) = path {
+                                self.open_project(&path);
+                            }
+                        }
+
+                        if ui.button("Save project…").on_hover_text("save open devices with their settings and templates, e.g. to keep it in git").clicked() {
+                            ui.close_menu();
+
+                            let path = rfd::FileDialog::new()
+                                .add_filter("projects", &[project::EXTENSION])
+                                .set_file_name(format!("bench.{}", project::EXTENSION))
+                                .save_file();
+
+                            if let Some(path) = path {
+                                self.save_project(&path);
+                            }
+                        }
+
+                        ui.separator();
+
+                        if ui.button("Templates…").clicked() {
+                            ui.close_menu();
+                            self.templates_open = true;
+                        }
+                    });
+
+                    ui.menu_button("View", |ui| {
+                        if ui.button("Appearance…").clicked() {
+                            ui.close_menu();
+                            self.appearance_open = true;
+                        }
+
+                        if ui.button("Log").clicked() {
+                            ui.close_menu();
+                            self.log_console.open = true;
+                        }
+
+                        if ui.button("Dashboard").on_hover_text("state and traffic of all open devices").clicked() {
+                            ui.close_menu();
+                            self.dashboard.open = true;
+                        }
+                    });
+
+                    ui.menu_button("Tools", |ui| {
+                        if ui.button("CRC calculator…").clicked() {
+                            ui.close_menu();
+                            self.crc_calculator.open = true;
+                        }
+
+                        if ui.button("Import from text…").clicked() {
+                            ui.close_menu();
+                            self.text_import.open = true;
+                        }
+
+                        if ui.button("Broadcast…").clicked() {
+                            ui.close_menu();
+                            self.broadcast.open = true;
+                        }
+                    });
+                });
+
                 ui.horizontal_top(|ui| {
+                    let previous = self.new_device_selection.clone();
+
+                    ui.add(TextEdit::singleline(&mut self.new_device_selection)
+                        .desired_width(ui.available_width() * 0.4)
+                        .hint_text("port, tcp://host:port, udp://host:port, ble://name or can://can0"));
+
                     ComboBox::from_id_source("device")
-                        .width(ui.available_width() * 0.8)
-                        .selected_text(&self.new_device_selection)
+                        .width(ui.available_width() * 0.3)
+                        .selected_text("ports")
                         .show_ui(ui, |ui| {
                             for dev in devices {
-                                ui.selectable_value(
-                                    &mut self.new_device_selection,
-                                    dev.port_name.clone(),
-                                    dev.port_name.clone(),
-                                );
+                                // identical adapters are told apart by their serial numbers
+                                let label = match port_config::usb_description(&dev) {
+                                    Some(usb) => format!("{}  {}", dev.port_name, usb),
+                                    None => dev.port_name.clone(),
+                                };
+
+                                ui.selectable_value(&mut self.new_device_selection, dev.port_name, label);
+                            }
+
+                            ui.separator();
+                            ui.selectable_value(
+                                &mut self.new_device_selection,
+                                transport::LOOPBACK.to_owned(),
+                                transport::LOOPBACK,
+                            )
+                            .on_hover_text("echoes every sent frame back, no hardware needed");
+
+                            ui.separator();
+                            for bridge in self.bridges.borrow().iter() {
+                                let hint = match (&bridge.device, &bridge.receivers) {
+                                    (Some(device), Some(receivers)) => format!("proto-bridge of {}, frames to {}", device, receivers),
+                                    (Some(device), None) => format!("proto-bridge of {}", device),
+                                    _ => "proto-bridge".to_owned(),
+                                };
+
+                                ui.selectable_value(&mut self.new_device_selection, bridge.target_name(), format!("{}  {}", bridge.name, bridge.addr))
+                                    .on_hover_text(hint);
+                            }
+
+                            #[cfg(feature = "ble")]
+                            {
+                                ui.separator();
+                                for dev in self.ble_devices.borrow().iter() {
+                                    let name = dev.target_name();
+                                    ui.selectable_value(&mut self.new_device_selection, name.clone(), name)
+                                        .on_hover_text(&dev.id);
+                                }
+
+                                if ui.button("Scan Bluetooth").on_hover_text("look for boards with Nordic UART service for a few seconds").clicked() {
+                                    hotplug::scan_ble(&self.ctx, &self.ble_devices);
+                                }
                             }
                         });
 
-                    ui.text_edit_singleline(&mut self.baud_rate);
+                    // use rate this port was opened with last time
+                    if previous != self.new_device_selection {
+                        if let Some(&baud_rate) = self.settings.baud_rates.get(&self.new_device_selection) {
+                            self.baud_rate.set(baud_rate);
+                        }
+                    }
+
+                    self.baud_rate.draw(ui);
                 });
 
+                ui.horizontal(|ui| self.port_config.draw_line(ui));
+                ui.checkbox(&mut self.port_config.monitor, "monitor only")
+                    .on_hover_text("never write to the port, to sniff a bus between two other nodes without interfering");
+
                 if ui.add_sized([ui.available_width(), 0.0], |ui: &mut egui::Ui| {
                     ui.button("open")
                 }).clicked() {
-                    let result = self.open_device(
-                        self.new_device_selection.clone(),
-                        self.baud_rate.get_u64().unwrap_or_default() as u32,
-                    );
+                    let baud_rate = self.baud_rate
+                        .value()
+                        .context("invalid baud rate");
 
-                    let _ = self.ctx.report_error(result);
+                    if let Some(baud_rate) = self.ctx.report_error(baud_rate) {
+                        let config = PortConfig { baud_rate, ..self.port_config };
+                        self.open_device(self.new_device_selection.clone(), config, |_| ());
+                    }
                 }
+
+                egui::CollapsingHeader::new("Bridges")
+                    .show(ui, |ui| self.draw_bridges(ui));
+
+                egui::CollapsingHeader::new(format!("Plugins ({})", self.ctx.plugins.loaded.len()))
+                    .id_source("plugins")
+                    .show(ui, |ui| self.ctx.plugins.draw(ui));
+
+                egui::CollapsingHeader::new("Keyboard shortcuts")
+                    .show(ui, |ui| {
+                        if self.settings.keybindings.draw(ui) {
+                            let _ = self.ctx.report_error(self.settings.save());
+                        }
+                    });
             });
 
-        let app_ctx = self.ctx.clone();
-        let mut guard = app_ctx.devices.blocking_lock();
+        self.draw_session_prompt(ctx);
+        self.draw_templates(ctx);
+        self.crc_calculator.show(ctx);
+        self.log_console.show(ctx);
+        self.draw_appearance(ctx, frame.info().system_theme);
+
+        // everything received since the last frame
+        while let Ok((handle, event)) = self.events.try_recv() {
+            events::apply(&self.ctx, &mut self.devices, handle, event);
+        }
+
+        // taken out, so windows can be drawn with the rest of `self` borrowed
+        let mut devices = std::mem::take(&mut self.devices);
 
         // draw device windows
-        guard.retain(|_, device| {
+        devices.retain(|_, device| {
             let mut open = true;
 
-            egui::Window::new(format!("{}", device.name))
-                .id(egui::Id::new(device.handle))
-                .fixed_size([800.0, 600.0])
-                .open(&mut open)
-                .show(ctx, |ui| {
-                    device.draw(ui, &self.ctx);
+            if device.detached {
+                self.draw_detached(ctx, device);
+            } else {
+                let mut window = egui::Window::new(device.title())
+                    .id(egui::Id::new(device.handle))
+                    .default_size([800.0, 600.0])
+                    .open(&mut open);
+
+                if let Some(pos) = device.window_pos {
+                    window = window.default_pos(pos);
+                }
+
+                let response = window.show(ctx, |ui| {
+                    device.draw(ui, &self.ctx, &mut self.settings);
 
                     // ui.allocate_space(ui.available_size());
                 });
 
+                if let Some(response) = response {
+                    device.window_pos = Some(response.response.rect.min);
+                }
+            }
+
+            // connect devices with publishing enabled to bridges
+            match (device.publish, &device.bridge) {
+                (true, None) => device.bridge = Some(self.bridge_events.clone()),
+                (false, Some(_)) => device.bridge = None,
+                _ => (),
+            }
+
+            views::show(ctx, &self.ctx, device);
+            diff::show(ctx, device);
+            responder::show(ctx, device);
+            fuzz::show(ctx, &self.ctx, device);
+            dfu::show(ctx, &self.ctx, device);
+            notify::show(ctx, device);
+            trigger::show(ctx, &self.ctx, device, &self.settings);
+            batch_send::show(ctx, &self.ctx, device);
+            paste::show(ctx, &self.ctx, device);
+
+            // frames matching notification rules
+            let title = device.title();
+            for text in device.notifier.pending.drain(..) {
+                self.toasts.add(Toast {
+                    text: format!("{}: {}", title, text).into(),
+                    kind: ToastKind::Info,
+                    options: ToastOptions::default()
+                        .show_icon(true)
+                        .show_progress(true)
+                        .duration_in_seconds(8.0),
+                });
+            }
+
+            // remember valid addresses for the next time this port is opened
+            if let (None, Ok(port_settings)) = (&device.capture, device.port_settings()) {
+                if self.settings.ports.get(&device.name) != Some(&port_settings) {
+                    self.settings.ports.insert(device.name.clone(), port_settings);
+                    let _ = self.ctx.report_error(self.settings.save());
+                }
+            }
+
+            // aliases are saved as they are typed, there is no confirm button
+            if let Some(key) = device.alias_key.as_ref() {
+                let saved = self.settings.aliases.get(key).map_or("", String::as_str);
+                if saved != device.alias {
+                    if device.alias.is_empty() {
+                        self.settings.aliases.remove(key);
+                    } else {
+                        self.settings.aliases.insert(key.clone(), device.alias.clone());
+                    }
+                    let _ = self.ctx.report_error(self.settings.save());
+                }
+            }
+
+            // remember sent commands, like shell history
+            if device.capture.is_none() {
+                let saved = self.settings.history.get(&device.name).map_or(&[][..], Vec::as_slice);
+                if saved != device.history.entries() {
+                    self.settings.history.insert(device.name.clone(), device.history.entries().to_vec());
+                    let _ = self.ctx.report_error(self.settings.save());
+                }
+            }
+
             if !open {
-                self.ctx
-                    .cmd_tx
-                    .blocking_send(Cmd::CloseDevice {
-                        handle: device.handle
-                    }).unwrap();
+                if let Some(periodic_send) = device.periodic_send.as_ref() {
+                    periodic_send.stop();
+                }
+
+                if let Some(batch) = device.batch.as_ref() {
+                    batch.abort();
+                }
+
+                device.fuzzer.stop();
+                device.dfu.cancel();
+
+                let handle = device.handle;
+                self.ctx.closed.lock().unwrap().insert(handle);
+                self.ctx.spawn({
+                    let ctx = self.ctx.clone();
+                    async move { ctx.command(Cmd::CloseDevice { handle }).await }
+                });
             }
 
             open
         });
 
+        // files dropped outside of windows of devices sending them are opened as captures
+        let (dropped, pos) = ctx.input(|i| (i.raw.dropped_files.clone(), i.pointer.latest_pos()));
+        let layer = pos.and_then(|pos| ctx.layer_id_at(pos));
+        let over_device = layer.is_some_and(|layer| {
+            devices.values().any(|device| device.can_send() && !device.detached && layer.id == egui::Id::new(device.handle))
+        });
+
+        if !over_device {
+            for path in dropped.into_iter().filter_map(|file| file.path) {
+                self.open_capture(path);
+            }
+        }
+
+        if ctx.input(|i| !i.raw.hovered_files.is_empty()) {
+            egui::show_tooltip_text(ctx, egui::Id::new("drop hint"), "drop onto a device window to send the file, anywhere else to open it as a capture");
+        }
+
+        // devices with a port, frames can be imported into or broadcast to
+        let targets = devices
+            .values()
+            .filter(|device| device.can_send())
+            .map(|device| (device.handle, device.title()))
+            .collect::<Vec<_>>();
+
+        self.broadcast.show(ctx, &self.ctx, &targets);
+        self.dashboard.show(ctx, devices.values());
+
+        // pasted frames go to a new viewer, or received list of an open device
+        if let Some((target, frames)) = self.text_import.show(ctx, &targets) {
+            match target.and_then(|handle| devices.get_mut(&handle)) {
+                Some(device) => device.import(frames),
+                None => {
+                    let mut device = Device::new("pasted text (import)".into(), DeviceHandle::detached(), PortConfig::default(), PortSettings::default());
+                    device.capture = Some(PathBuf::from("pasted text"));
+                    device.import(frames);
+                    devices.insert(device.handle, device);
+                },
+            }
+        }
+
+        // bridges look devices up in this snapshot
+        let mut published = devices
+            .values()
+            .filter(|device| device.bridge.is_some())
+            .map(|device| DeviceInfo { handle: device.handle, device: device.name.clone(), alias: device.alias.clone() })
+            .collect::<Vec<_>>();
+        published.sort_by(|a, b| a.device.cmp(&b.device));
+        self.ctx.published.send_if_modified(|current| {
+            let modified = *current != published;
+            *current = published;
+            modified
+        });
+
+        self.devices = devices;
+
         // push new toast messages
         loop {
             match self.errors.try_recv() {
-                Ok(v) => {
+                Ok((kind, text)) => {
+                    // errors stay longer, they may need reading
+                    let duration = if matches!(kind, ToastKind::Error) { 15.0 } else { 5.0 };
+
                     self.toasts
                         .add(Toast {
-                            text: v.into(),
-                            kind: egui_toast::ToastKind::Error,
+                            text: text.into(),
+                            kind,
                             options: ToastOptions::default()
                                 .show_icon(true)
                                 .show_progress(true)
-                                .duration_in_seconds(15.0)
+                                .duration_in_seconds(duration)
                         });
                 },
                 Err(TryRecvError::Empty) => break,
@@ -207,232 +893,2200 @@ impl eframe::App for App {
         // show toasts
         self.toasts.show(ctx);
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // previous session is kept if user didn't decide what to do with it
+        if self.pending_session.is_some() {
+            return;
+        }
+
+        let session = Session {
+            devices: self.devices
+                .values()
+                .filter(|device| device.capture.is_none())
+                .map(Device::session)
+                .collect(),
+        };
+
+        if let Err(err) = session.save() {
+            log::error!("{:?}", err);
+        }
+    }
 }
 
 impl App {
-    // try to open COM device, at `path`, with provided baud_rate
-    // on success device will be appended to `self.ctx.device`
-    fn open_device(&mut self, path: String, baud_rate: u32) -> anyhow::Result<()> {
-        let _guard = self.ctx
-            .runtime
-            .enter();
-
-        let device = tokio_serial::SerialStream::open(
-            &tokio_serial::new(&path, baud_rate)
-        )?;
-
-        let (tx, rx) = oneshot::channel();
-
-        self.ctx
-            .cmd_tx
-            .blocking_send(Cmd::RegisterDevice {
-                device, result: tx,
-            }).unwrap();
-
-        let handle = rx.blocking_recv().unwrap();
-        self.ctx
-            .devices
-            .blocking_lock()
-            .entry(handle)
-            .or_insert(Device {
-                name: path,
-                cmd_input: Default::default(),
-                handle,
-                received: Default::default(),
-                sent: Default::default(),
-            });
+    // try to open COM device at `path` (or network device, see `Target::new`), with provided config
+    // port is opened in background, on success device will be appended to `self.ctx.device`
+    // and passed to `setup` first, errors are reported as toasts
+    fn open_device<F>(&mut self, path: String, config: PortConfig, setup: F)
+    where
+        F: FnOnce(&mut Device) + Send + 'static,
+    {
+        let baud_rate_changed = self.settings.baud_rates.insert(path.clone(), config.baud_rate) != Some(config.baud_rate);
+        if baud_rate_changed || self.settings.last_port != path || self.settings.port_config != config {
+            self.settings.last_port = path.clone();
+            self.settings.port_config = config;
+            let _ = self.ctx.report_error(self.settings.save());
+        }
 
-        Ok(())
+        let port_settings = self.settings
+            .ports
+            .get(&path)
+            .copied()
+            .unwrap_or_default();
+
+        let history = self.settings
+            .history
+            .get(&path)
+            .cloned()
+            .unwrap_or_default();
+
+        let alias_key = port_config::alias_key(&path, &self.ports.borrow());
+        let alias = self.settings.aliases.get(&alias_key).cloned().unwrap_or_default();
+
+        let ctx = self.ctx.clone();
+        self.ctx.spawn(async move {
+            let target = Target::new(&path, config.builder(&path));
+            let device = target.open().await?;
+
+            let queue = Arc::new(TxQueue::default());
+            let traffic = Arc::new(Traffic::default());
+            let (tx, rx) = oneshot::channel();
+            ctx.command(Cmd::RegisterDevice { device, target, queue: queue.clone(), traffic: traffic.clone(), read_only: config.monitor, result: tx }).await?;
+            let handle = rx.await.context("serial handler stopped")?;
+
+            let mut device = Device::new(path, handle, config, port_settings);
+            device.history = History::new(history);
+            device.tx_queue = queue;
+            device.activity = Activity::new(traffic);
+            device.alias = alias;
+            device.alias_key = Some(alias_key);
+            setup(&mut device);
+
+            ctx.post(handle, DeviceEvent::Opened(Box::new(device)));
+            Ok(())
+        });
     }
-}
 
+    /// loads capture file into a read-only device window, file is read in background
+    fn open_capture(&mut self, path: PathBuf) {
+        let ctx = self.ctx.clone();
+        self.ctx.spawn(async move {
+            let device = tokio::task::spawn_blocking(move || Self::load_capture(path)).await??;
+            ctx.post(device.handle, DeviceEvent::Opened(Box::new(device)));
+            Ok(())
+        });
+    }
 
-// ***************************************
-// *                 *                   *
-// *                 *                   *
-// *                 *                   *
-// *   SENT FRAMES   *  RECEIVED FRAMES  *
-// *                 *                   *
-// *                 *                   *
-// *                 *                   *
-// ***************************************
-// *           COMMAND INPUT             *
-// ***************************************
-// *            SEND BUTTON              *
-// ***************************************
-/// draw device window
-impl Device {
-    fn draw(&mut self, ui: &mut egui::Ui, ctx: &Arc<Context>) {
-        ui.style_mut().wrap = Some(false);
+    fn load_capture(path: PathBuf) -> anyhow::Result<Device> {
+        let records = proto_tools::capture::read(&path, None)?;
 
-        ui.horizontal_top(|ui: &mut egui::Ui| {
-            let space = ui.available_width() / 2.0 - 1.0;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
 
-            ui.vertical(|ui| {
-                ScrollArea::new([false, true])
-                    .id_source(Id::new("left").with(ui.id()))
-                    .show(ui, |ui| {
-                        self.sent
-                            .iter()
-                            .for_each(|frame| {
-                                frame.draw(ui, space);
-                            });
-                    });
+        // not registered in `serial_com`, there is no port behind it
+        let handle = DeviceHandle::detached();
+        let mut device = Device::new(format!("{} (capture)", name), handle, PortConfig::default(), PortSettings::default());
+        device.capture = Some(path);
+        device.load_records(records);
 
-                ui.allocate_space([space, 0.0].into());
-            });
+        Ok(device)
+    }
 
-            ui.add_sized([0.0, ui.available_height() - 30.0], |ui: &mut egui::Ui| {
-                ui.add(egui::Separator::default())
-            });
+    fn draw_session_prompt(&mut self, ctx: &egui::Context) {
+        let Some(session) = self.pending_session.as_ref() else {
+            return;
+        };
 
-            ui.vertical_centered(|ui| {
-                let space = ui.available_width();
+        let mut restore = None;
 
-                ScrollArea::new([false, true])
-                    .id_source(Id::new("right").with(ui.id()))
-                    .show(ui, |ui| {
-                        self.received
-                            .iter()
-                            .for_each(|frame| {
-                                frame.draw(ui, space);
-                            });
-                    });
+        egui::Window::new("Restore session")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("Devices open when terminal was closed:");
+
+                for device in &session.devices {
+                    ui.label(format!(
+                        "{} @ {}, {} frames",
+                        device.port,
+                        device.config,
+                        device.frames.len(),
+                    ));
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("Restore").clicked() {
+                        restore = Some(true);
+                    }
+
+                    if ui.button("Discard").clicked() {
+                        restore = Some(false);
+                    }
+                });
             });
 
-            // ui.vertical();
+        match restore {
+            Some(true) => {
+                let session = self.pending_session.take().unwrap();
+                for device in session.devices {
+                    self.restore_device(device);
+                }
+            },
+            Some(false) => self.pending_session = None,
+            None => (),
+        }
+    }
+
+    /// device shown in its own OS window, closing it brings device back into the main one
+    fn draw_detached(&mut self, ctx: &egui::Context, device: &mut Device) {
+        let viewport = egui::ViewportBuilder::default()
+            .with_title(device.title())
+            .with_inner_size([800.0, 600.0]);
 
-            ()
+        ctx.show_viewport_immediate(egui::ViewportId::from_hash_of(("device", device.handle)), viewport, |ctx, _| {
+            egui::CentralPanel::default().show(ctx, |ui| device.draw(ui, &self.ctx, &mut self.settings));
+
+            if ctx.input(|i| i.viewport().close_requested()) {
+                device.detached = false;
+            }
         });
+    }
 
-        ui.horizontal_top(|ui: &mut egui::Ui| {
-            ui.add(TextEdit::singleline(&mut self.cmd_input).desired_width(ui.available_width() * 0.8));
-            
-            if ui.add_sized([ui.available_width(), 0.0], |ui: &mut egui::Ui| ui.button("Send")).clicked() {
-                let frame = Frame {
-                    sender: 123,
-                    receiver: 100,
-                    data: self.cmd_input.clone().into_bytes(),
-                };
-                self.cmd_input.clear();
+    /// servers sharing published devices with other tools
+    fn draw_bridges(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("WebSocket:");
 
-                if let Some(data) = ctx.report_error((|| anyhow::Ok(frame.serialize()?))()) {
-                    let (result_tx, result) = oneshot::channel();
-                    ctx.cmd_tx
-                        .blocking_send(Cmd::SendData { handle: self.handle, data, result: result_tx })
-                        .unwrap();
+            if let Some(bridge) = self.ws_bridge.as_ref() {
+                ui.label(format!("ws://{}", bridge.addr));
 
-                    if let Some(_) = ctx.report_error(result.blocking_recv().unwrap()) {
-                        self.sent.push(frame.into());
-                    }
+                if ui.button("Stop").clicked() {
+                    self.ws_bridge = None;
                 }
 
+                return;
+            }
+
+            ui.add(TextEdit::singleline(&mut self.ws_addr).desired_width(120.0));
+
+            if ui.button("Start").on_hover_text("share frames of devices with \"Publish\" checked").clicked() {
+                self.ws_bridge = self.ctx.report_error(WsBridge::start(&self.ctx, &self.ws_addr, &self.bridge_events));
             }
         });
-    }
-}
 
-impl Context {
-    #[must_use]
-    pub fn report_error<T>(&self, result: anyhow::Result<T>) -> Option<T> {
-        match result {
-            Ok(v) => Some(v),
-            Err(err) => {
-                self.error_tx
-                    .send(format!("{:?}", err))
-                    .unwrap();
+        ui.horizontal(|ui| {
+            ui.label("REST API:");
 
-                None
+            if let Some(api) = self.rest_api.as_ref() {
+                ui.label(format!("http://{}", api.addr));
+
+                if ui.button("Stop").clicked() {
+                    self.rest_api = None;
+                }
+
+                return;
             }
-        }
-    }
-}
 
-impl DrawableFrame {
-    fn draw(&self, ui: &mut egui::Ui, aval: f32) -> Response {
-        let free_chars = (aval / 9.0) as usize;
+            ui.add(TextEdit::singleline(&mut self.api_addr).desired_width(120.0));
 
-        let crc32 = Self::format_crc32(self.crc32);
-        let len = Self::format_length(self.frame_length);
+            if ui.button("Start").on_hover_text("let scripts list, drive and watch devices with \"Publish\" checked").clicked() {
+                self.rest_api = self.ctx.report_error(RestApi::start(&self.ctx, &self.api_addr, &self.bridge_events));
+            }
+        });
 
-        let cmd = Self::format_name(&String::from_utf8_lossy(&self.inner.data), free_chars.saturating_sub(6));
+        ui.horizontal(|ui| {
+            ui.label("MQTT:");
 
-        let layout = LayoutJob::simple(
-            format!(
-                "[CMD] {}\nR:{:0<3} S:{:0<3} CRC32:{crc32} LEN:{len}",
-                cmd,
-                self.inner.receiver,
-                self.inner.sender,
-            ),
-            FontId::monospace(14.0),
-            Color32::GRAY,
-            aval,
-        );
+            if let Some(bridge) = self.mqtt_bridge.as_ref() {
+                ui.label(format!("mqtt://{}", bridge.broker));
 
-        let resp = ui.add_sized([aval, 0.0],
-            egui::SelectableLabel::new(
-                false,
-                layout,
-            )
-        );
+                if ui.button("Stop").clicked() {
+                    self.mqtt_bridge = None;
+                }
+
+                return;
+            }
 
-        if resp.secondary_clicked() {
-            // copy hex to keyboard
-            let serialized = self.inner.serialize().unwrap();
-            let hex = serialized.iter()
-                .map(|c| format!("{:02x}", c))
-                .collect::<Vec<_>>()
-                .join("");
+            ui.add(TextEdit::singleline(&mut self.mqtt_config.broker).desired_width(120.0))
+                .on_hover_text("broker, host:port");
 
-            let mut clipboard = arboard::Clipboard::new().unwrap();
-            clipboard.set_text(&hex).unwrap()
-        }
+            if ui.button("Start").on_hover_text("share frames of devices with \"Publish\" checked").clicked() {
+                self.mqtt_bridge = self.ctx.report_error(MqttBridge::start(&self.ctx, &self.mqtt_config, &self.bridge_events));
+            }
+        });
 
-        resp
+        // topics can't be changed while connected
+        ui.add_enabled_ui(self.mqtt_bridge.is_none(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("received:");
+                ui.add(TextEdit::singleline(&mut self.mqtt_config.rx_topic).desired_width(150.0));
+                ui.label("commands:");
+                ui.add(TextEdit::singleline(&mut self.mqtt_config.command_topic).desired_width(150.0));
+            })
+            .response
+            .on_hover_text("{device} is replaced with port name");
+        });
     }
 
-    fn format_name(name: &str, space: usize) -> String {
-        let space = space.max(3);
+    /// template editor window, changes are saved immediately
+    fn draw_templates(&mut self, ctx: &egui::Context) {
+        let previous = self.settings.templates.clone();
 
-        let len = name.chars().count();
-        if len > space {
-            let (pos,_) = name.char_indices().skip(space-2).next().unwrap();
-            format!("{:.<space$}", &name[..pos])
-        } else {
-            format!("{: <space$}", name)
+        egui::Window::new("Templates")
+            .open(&mut self.templates_open)
+            .show(ctx, |ui| templates::draw_editor(ui, &mut self.settings.templates));
+
+        if previous != self.settings.templates {
+            let _ = self.ctx.report_error(self.settings.save());
         }
     }
 
-    fn format_crc32(crc: Option<u32>) -> String {
-        if let Some(n) = crc {
-            format!("{:0>8x}", n)
-        } else {
-            format!("{: >8}", "")
+    /// theme, font and scale settings window, changes are applied and saved immediately
+    fn draw_appearance(&mut self, ctx: &egui::Context, system_theme: Option<eframe::Theme>) {
+        let mut changed = false;
+
+        egui::Window::new("Appearance")
+            .open(&mut self.appearance_open)
+            .resizable(false)
+            .show(ctx, |ui| changed = appearance::draw(ui, &mut self.settings));
+
+        if changed {
+            appearance::apply(ctx, &self.settings, system_theme);
+            let _ = self.ctx.report_error(self.settings.save());
         }
     }
 
-    fn format_length(len: Option<usize>) -> String {
-        if let Some(n) = len {
-            format!("{: <4}", n)
-        } else {
-            format!("{: <4}", "")
+    /// opens devices of project at `path`, its templates are added to saved ones, replacing ones of the same name
+    fn open_project(&mut self, path: &Path) {
+        let Some(project) = self.ctx.report_error(Project::load(path)) else {
+            return;
+        };
+
+        for template in project.templates {
+            match self.settings.templates.iter_mut().find(|saved| saved.name == template.name) {
+                Some(saved) => *saved = template,
+                None => self.settings.templates.push(template),
+            }
+        }
+        let _ = self.ctx.report_error(self.settings.save());
+
+        for device in project.devices {
+            // loaded here, so that errors are reported
+            let schema = device.schema
+                .as_ref()
+                .and_then(|path| self.ctx.report_error(Schema::load(path)));
+
+            self.open_device(device.port.clone(), device.config, move |dev| {
+                dev.sender = NumberBuffer::new(&device.addresses.sender.to_string());
+                dev.receiver = NumberBuffer::new(&device.addresses.receiver.to_string());
+                dev.window_pos = device.window_pos.map(egui::Pos2::from);
+                dev.detached = device.detached;
+                device.apply_panels(dev);
+
+                if !device.alias.is_empty() {
+                    dev.alias = device.alias;
+                }
+
+                if schema.is_some() {
+                    dev.set_schema(schema);
+                    dev.schema_path = device.schema;
+                }
+            });
         }
     }
+
+    /// saves devices with a port, and all templates, as a project at `path`
+    fn save_project(&mut self, path: &Path) {
+        let mut devices = self.devices
+            .values()
+            .filter(|device| device.capture.is_none())
+            .map(ProjectDevice::new)
+            .collect::<Vec<_>>();
+
+        // stable order keeps diffs of versioned projects small
+        devices.sort_by(|a, b| a.port.cmp(&b.port));
+
+        let project = Project { devices, templates: self.settings.templates.clone() };
+        let _ = self.ctx.report_error(project.save(path));
+    }
+
+    /// reopens port of device from previous session, and brings back its history
+    fn restore_device(&mut self, session: DeviceSession) {
+        let DeviceSession { port, config, addresses, window_pos, frames } = session;
+
+        self.open_device(port, config, move |device| {
+            device.sender = NumberBuffer::new(&addresses.sender.to_string());
+            device.receiver = NumberBuffer::new(&addresses.receiver.to_string());
+            device.window_pos = window_pos.map(egui::Pos2::from);
+            device.load_records(frames);
+        });
+    }
 }
 
-impl From<Frame> for DrawableFrame {
-    fn from(value: Frame) -> Self {
-        let crc32 = value.calculate_crc32()
-            .ok();
 
-        let frame_length = value.serialize()
-            .map(|v| v.len())
-            .ok();
+// ***************************************
+// *POP OUT * DISCONNECTED (if unplugged)*
+// ***************************************
+// *              FILTER                 *
+// ***************************************
+// * TIME*SHOW*COLOR*DISCARD*PANELS*TOOLS*
+// ***************************************
+// *     ADDRESS COLORS (if enabled)     *
+// ***************************************
+// *              SEARCH                 *
+// ***************************************
+// *    FRAME LIMIT * DROPPED * CLEAR    *
+// ***************************************
+// *                                     *
+// *   SENT FRAMES  |  RECEIVED FRAMES   *
+// *                                     *
+// *    -----------------------------    *
+// *    INSPECTOR / PLOT / STATS tabs    *
+// *                                     *
+// *   (docked panels, tabs draggable)   *
+// ***************************************
+// *S * R * INPUT * PASTE * REPEAT * SEND*
+// ***************************************
+// *     PARSED PAYLOAD (hex mode)       *
+// ***************************************
+// *     TEMPLATE BUTTONS (if any)       *
+// ***************************************
+// * SEND FILE * CHUNK SIZE * FRAMES FILE*
+// ***************************************
+// *            SEND BUTTON              *
+// ***************************************
+// * PACING * DTR * RTS * BREAK (serial) *
+// ***************************************
+// *EXPORT * PUBLISH * LOG TO FILE / PATH*
+// ***************************************
+/// draw device window
+impl Device {
+    fn draw(&mut self, ui: &mut egui::Ui, ctx: &Arc<Context>, settings: &mut Settings) {
+        ui.style_mut().wrap = Some(false);
+        let keys = &settings.keybindings;
 
-        Self {
-            inner: value,
-            crc32,
-            frame_length,
-        }
+        // shortcuts go to the device window in front, a detached one only gets input while it's focused
+        if self.detached || ui.ctx().top_layer_id() == Some(ui.layer_id()) {
+            if keys.pressed(ui.ctx(), Action::Clear) {
+                self.run_command(ctx, Command::Clear);
+            }
+
+            if keys.pressed(ui.ctx(), Action::Search) {
+                self.run_command(ctx, Command::Search);
+            }
+
+            if keys.pressed(ui.ctx(), Action::Palette) {
+                self.palette.toggle();
+            }
+
+            if keys.pressed(ui.ctx(), Action::Bookmark) {
+                self.run_command(ctx, Command::Bookmark);
+            }
+
+            // checked first, shift + key would also count as the key alone
+            if keys.pressed(ui.ctx(), Action::PreviousBookmark) {
+                self.run_command(ctx, Command::PreviousBookmark);
+            }
+
+            if keys.pressed(ui.ctx(), Action::NextBookmark) {
+                self.run_command(ctx, Command::NextBookmark);
+            }
+        }
+
+        // files dropped onto the window are sent, main window opens ones dropped elsewhere as captures
+        if self.can_send() {
+            let (dropped, pos) = ui.ctx().input(|i| (i.raw.dropped_files.clone(), i.pointer.latest_pos()));
+            let over = self.detached || pos.is_some_and(|pos| ui.ctx().layer_id_at(pos) == Some(ui.layer_id()));
+
+            for path in dropped.into_iter().filter_map(|file| file.path).filter(|_| over) {
+                self.send_file(ctx, &path);
+            }
+        }
+
+        let palette_id = egui::Id::new(("palette", self.handle));
+        if let Some(command) = self.palette.show(ui.ctx(), palette_id, keys, self.capture.is_none(), self.can_send()) {
+            self.run_command(ctx, command);
+        }
+
+        // laid out first, so that panels get whatever space is left between controls
+        egui::TopBottomPanel::bottom(egui::Id::new(("controls", self.handle)))
+            .show_inside(ui, |ui| {
+                // captures opened from file and monitored ports are read-only
+                if self.can_send() {
+                    self.draw_send(ui, ctx, settings);
+                    self.draw_templates(ui, ctx, &settings.templates);
+                    self.draw_file_send(ui, ctx);
+                }
+
+                if self.capture.is_none() {
+                    self.draw_link(ui, ctx);
+                }
+
+                self.draw_log(ui, ctx, settings);
+            });
+
+        ui.horizontal(|ui| {
+            // without multiple viewports support, detached window would be embedded anyway
+            if !ui.ctx().embed_viewports() {
+                let label = if self.detached { "⮌ Dock back" } else { "🗗 Pop out" };
+                if ui.button(label).on_hover_text("show device in its own window, e.g. to move it to another monitor").clicked() {
+                    self.detached = !self.detached;
+                }
+            }
+
+            ui.menu_button("New view", |ui| views::draw_menu(ui, self))
+                .response
+                .on_hover_text("another window onto this device, e.g. raw bytes or frames of one sender");
+
+            if self.alias_key.is_some() {
+                ui.add(TextEdit::singleline(&mut self.alias).desired_width(150.0).hint_text("alias"))
+                    .on_hover_text("name shown in window titles and offered for exports, USB adapters keep it on any port");
+            }
+
+            if self.config.monitor {
+                ui.weak("monitor only").on_hover_text("port was opened for listening, nothing is sent to it");
+            }
+
+            match self.connection {
+                ConnectionState::Connected => (),
+                ConnectionState::Reconnecting { attempt, delay } => {
+                    let text = format!("disconnected, reopening in {:.1} s (attempt {})…", delay.as_secs_f32(), attempt);
+                    ui.colored_label(ui.visuals().error_fg_color, text);
+                },
+                ConnectionState::Failed => {
+                    ui.colored_label(ui.visuals().error_fg_color, "disconnected, gave up reopening")
+                        .on_hover_text("see reconnect settings, close the device and open it again");
+                },
+            }
+
+            if self.capture.is_none() {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| self.activity.draw(ui));
+            }
+        });
+
+        let filter = egui::CollapsingHeader::new(if self.filter.is_empty() { "Filter" } else { "Filter (active)" })
+            .id_source("filter")
+            .show(ui, |ui| self.filter.draw(ui))
+            .body_returned
+            .flatten()
+            .or_else(|| self.filter.compile().ok())
+            .unwrap_or_default();
+
+        ui.horizontal(|ui| {
+            ui.label("Time:");
+            ui.selectable_value(&mut self.time_mode, TimeMode::Absolute, "absolute");
+            ui.selectable_value(&mut self.time_mode, TimeMode::Delta, "delta")
+                .on_hover_text("time since previous frame in the list");
+
+            ui.separator();
+            ui.label("Show:");
+            ui.selectable_value(&mut self.show_wire, false, "payload");
+            ui.selectable_value(&mut self.show_wire, true, "wire")
+                .on_hover_text("bytes as they went over the line, escape sequences are marked");
+            ui.add_enabled_ui(!self.show_wire, |ui| self.payload_format.draw(ui));
+
+            ui.separator();
+            ui.label("Color:");
+            ComboBox::from_id_source("color mode")
+                .width(70.0)
+                .selected_text(match self.color_mode {
+                    ColorMode::Off => "off",
+                    ColorMode::Sender => "sender",
+                    ColorMode::Receiver => "receiver",
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.color_mode, ColorMode::Off, "off");
+                    ui.selectable_value(&mut self.color_mode, ColorMode::Sender, "sender");
+                    ui.selectable_value(&mut self.color_mode, ColorMode::Receiver, "receiver");
+                })
+                .response
+                .on_hover_text("tint frames by address, to tell apart nodes on a shared bus");
+
+            ui.separator();
+            ui.checkbox(&mut self.show_discarded, "show discarded")
+                .on_hover_text("show received frames that failed to deserialize (e.g. CRC mismatch)");
+            ui.checkbox(&mut self.collapse_repeats, "collapse repeats")
+                .on_hover_text("show consecutive identical frames as one row with a counter");
+            ui.checkbox(&mut self.auto_scroll, "follow")
+                .on_hover_text("scroll to new frames, paused while the list is hovered or scrolled up");
+
+            ui.separator();
+            ui.menu_button("Panels", |ui| dock::draw_menu(ui, &mut self.dock))
+                .response
+                .on_hover_text("inspector, plot of received values and statistics, tabs can be dragged around");
+
+            let label = match self.schema_path.as_ref().and_then(|path| path.file_name()) {
+                Some(name) => format!("Schema: {}", name.to_string_lossy()),
+                None => "Schema".into(),
+            };
+            ui.menu_button(label, |ui| self.draw_schema_menu(ui, ctx))
+                .response
+                .on_hover_text("TOML or JSON file with payload layouts, matching payloads are shown as fields");
+
+            if self.can_send() {
+                let label = if self.responder.enabled { "Responder (on)" } else { "Responder" };
+                ui.toggle_value(&mut self.responder.open, label)
+                    .on_hover_text("reply to received frames automatically");
+            }
+
+            if self.capture.is_none() {
+                let label = if self.notifier.rules.iter().any(|rule| rule.enabled) { "Alerts (on)" } else { "Alerts" };
+                ui.toggle_value(&mut self.notifier.open, label)
+                    .on_hover_text("toast, sound or highlight when matching frame is received");
+
+                let label = if self.trigger.is_armed() { "Trigger (armed)" } else { "Trigger" };
+                ui.toggle_value(&mut self.trigger.open, label)
+                    .on_hover_text("record frames around a matching received frame to a file");
+            }
+
+            if self.can_send() {
+                let label = if self.fuzzer.is_running() { "Fuzz (running)" } else { "Fuzz" };
+                ui.toggle_value(&mut self.fuzzer.open, label)
+                    .on_hover_text("send random valid and malformed frames, watching how device responds");
+
+                let label = if self.dfu.is_running() { "Update (running)" } else { "Update" };
+                ui.toggle_value(&mut self.dfu.open, label)
+                    .on_hover_text("update firmware of the device with a verified image");
+
+                let label = if self.injection.is_on() { "Debug (injecting)" } else { "Debug" };
+                ui.menu_button(label, |ui| self.injection.draw_menu(ui))
+                    .response
+                    .on_hover_text("corrupt sent frames on purpose, to check how firmware handles errors");
+            }
+        });
+
+        let pattern = self.search.pattern().ok().flatten();
+        let exchanges = pairing::pair(&self.sent, &self.received);
+        let highlights = self.notifier.highlights();
+        // shared with the list, which lives while the rest of the device is drawn
+        let schema = self.schema.clone();
+        let mut list = FrameList {
+            filter: &filter,
+            pattern: pattern.as_deref(),
+            time_mode: self.time_mode,
+            show_discarded: self.show_discarded,
+            collapse_repeats: self.collapse_repeats,
+            auto_scroll: self.auto_scroll,
+            scroll_to: self.scroll_to.take(),
+            color_mode: self.color_mode,
+            exchanges: &exchanges,
+            can_send: self.can_send(),
+            wire: self.show_wire,
+            payload: PayloadView {
+                format: self.payload_format,
+                schema: schema.as_deref(),
+                schema_generation: self.schema_generation,
+                plugins: &ctx.plugins,
+            },
+            highlights: &highlights,
+        };
+
+        if self.color_mode != ColorMode::Off {
+            list.draw_legend(ui, self.sent.iter().chain(&self.received));
+        }
+
+        // ids of visible frames matching search query, ascending (so also chronological)
+        let mut matches = self.sent
+            .iter()
+            .chain(self.received.iter())
+            .filter(|frame| list.is_visible(frame) && list.is_match(frame))
+            .map(|frame| frame.id)
+            .collect::<Vec<_>>();
+        matches.sort_unstable();
+
+        if let Some(action) = self.search.draw(ui, matches.len(), std::mem::take(&mut self.focus_search)) {
+            if let Some(id) = action.target(&matches, self.selection.selected) {
+                self.selection.selected = Some(id);
+                list.scroll_to = Some(id);
+            }
+        }
+
+        self.draw_frame_limit(ui);
+
+        // frame picked from context menu of one of the lists
+        let mut resend = None;
+
+        // tabs need the rest of the device, so dock state is taken out while they are drawn
+        let mut dock = std::mem::replace(&mut self.dock, DockState::new(Vec::new()));
+        DockArea::new(&mut dock)
+            .id(egui::Id::new(("dock", self.handle)))
+            .style(egui_dock::Style::from_egui(ui.style()))
+            .show_inside(ui, &mut DeviceTabs { device: self, ctx, list: &list, resend: &mut resend });
+        self.dock = dock;
+
+        if let Some(frame) = resend {
+            self.send(ctx, frame);
+        }
+    }
+
+    fn draw_schema_menu(&mut self, ui: &mut egui::Ui, ctx: &Arc<Context>) {
+        if ui.button("Load…").clicked() {
+            ui.close_menu();
+
+            let path = rfd::FileDialog::new()
+                .add_filter("schema", &["toml", "json"])
+                .pick_file();
+
+            if let Some(path) = path {
+                if let Some(schema) = ctx.report_error(Schema::load(&path)) {
+                    self.set_schema(Some(schema));
+                    self.schema_path = Some(path);
+                }
+            }
+        }
+
+        let Some(path) = self.schema_path.clone() else {
+            return;
+        };
+
+        if ui.button("Reload").on_hover_text(path.display().to_string()).clicked() {
+            ui.close_menu();
+
+            if let Some(schema) = ctx.report_error(Schema::load(&path)) {
+                self.set_schema(Some(schema));
+            }
+        }
+
+        if ui.button("Unload").clicked() {
+            ui.close_menu();
+            self.set_schema(None);
+            self.schema_path = None;
+        }
+    }
+
+    fn set_schema(&mut self, schema: Option<Schema>) {
+        self.schema = schema.map(Arc::new);
+        self.schema_generation = SCHEMA_GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+    }
+
+    /// size of the lists, and controls to empty them
+    fn draw_frame_limit(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("keep last");
+            let changed = ui.add(egui::DragValue::new(&mut self.frame_limit).clamp_range(1..=10_000_000).speed(100.0))
+                .on_hover_text("maximum number of frames in each list, oldest are dropped first")
+                .changed();
+            ui.label("frames");
+
+            if changed {
+                self.trim();
+            }
+
+            if self.dropped_sent + self.dropped_received > 0 {
+                ui.colored_label(ui.visuals().warn_fg_color, format!(
+                    "dropped {} sent, {} received",
+                    self.dropped_sent,
+                    self.dropped_received,
+                ))
+                .on_hover_text("frames removed from lists because of the limit, log to file to keep them all");
+            }
+
+            ui.separator();
+            if ui.button("Clear sent").clicked() {
+                Self::unmark(&mut self.selection, &self.sent);
+                self.sent.clear();
+                self.dropped_sent = 0;
+            }
+
+            if ui.button("Clear received").clicked() {
+                Self::unmark(&mut self.selection, &self.received);
+                self.received.clear();
+                self.dropped_received = 0;
+            }
+
+            if ui.button("Clear all").clicked() {
+                self.clear();
+            }
+        });
+    }
+
+    fn draw_send(&mut self, ui: &mut egui::Ui, ctx: &Arc<Context>, settings: &mut Settings) {
+        let keys = &settings.keybindings;
+        let mut profiles_changed = false;
+
+        ui.horizontal_top(|ui: &mut egui::Ui| {
+            let error_color = ui.visuals().error_fg_color;
+            let sender_valid = self.sender.as_str().parse::<u8>().is_ok();
+            let receiver_valid = self.receiver.as_str().parse::<u8>().is_ok();
+
+            ui.label("S:");
+            ui.add(TextEdit::singleline(&mut self.sender)
+                .desired_width(24.0)
+                .text_color_opt((!sender_valid).then_some(error_color)))
+                .on_hover_text("sender address (0-255)");
+
+            let profiles_id = egui::Id::new(("sender profiles", self.handle));
+            profiles_changed = profiles::draw(ui, profiles_id, &mut settings.sender_profiles, &mut self.sender);
+
+            ui.label("R:");
+            ui.add(TextEdit::singleline(&mut self.receiver)
+                .desired_width(24.0)
+                .text_color_opt((!receiver_valid).then_some(error_color)))
+                .on_hover_text("receiver address (0-255)");
+
+            ui.selectable_value(&mut self.input_mode, InputMode::Text, "Text");
+            ui.selectable_value(&mut self.input_mode, InputMode::Hex, "Hex");
+            ui.checkbox(&mut self.raw_send, "Raw")
+                .on_hover_text("send input bytes without framing (no header, escaping or CRC), e.g. to bootloaders or AT command modules");
+            ui.add_enabled_ui(self.raw_send, |ui| self.line_ending.draw(ui, ("line ending", self.handle)))
+                .response
+                .on_hover_text("line ending appended to raw input");
+
+            let payload_valid = self.payload().is_ok();
+            let input = ui.add(TextEdit::singleline(&mut self.cmd_input)
+                .desired_width(ui.available_width() * 0.6)
+                .text_color_opt((!payload_valid).then_some(error_color)))
+                .on_hover_text("up/down arrows recall previously sent inputs, names of commands in the schema are completed");
+
+            if input.has_focus() {
+                self.recall_history(ui, input.id);
+            }
+
+            self.draw_completions(ui, &input);
+
+            // single line input loses focus on Enter, it's given back to send another one right away
+            let submitted = (input.has_focus() || input.lost_focus()) && keys.pressed(ui.ctx(), Action::Send);
+            if submitted {
+                input.request_focus();
+            }
+
+            if ui.button("📋").on_hover_text("decode frame from hex dump in clipboard").clicked() {
+                self.pasted = ctx.report_error(paste::from_clipboard());
+            }
+
+            self.draw_periodic_send(ui, ctx);
+            
+            let shortcut = keys.format(ui.ctx(), Action::Send);
+            let send = ui.add_sized([ui.available_width(), 0.0], |ui: &mut egui::Ui| ui.button("Send"))
+                .on_hover_text(if shortcut.is_empty() { "no shortcut".to_owned() } else { shortcut });
+
+            if send.clicked() || submitted {
+                self.send_input(ctx);
+            }
+        });
+
+        if self.input_mode == InputMode::Hex {
+            match self.payload() {
+                Ok(payload) => ui.label(format!(
+                    "{} bytes: {}",
+                    payload.len(),
+                    payload.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" "),
+                )),
+                Err(err) => ui.colored_label(ui.visuals().error_fg_color, format!("{:#}", err)),
+            };
+        }
+
+        if profiles_changed {
+            let _ = ctx.report_error(settings.save());
+        }
+    }
+
+    /// sends frame from command input (or its bytes as they are, if `raw_send` is set),
+    /// remembering the input in history
+    fn send_input(&mut self, ctx: &Arc<Context>) {
+        if self.raw_send {
+            let Some(mut data) = ctx.report_error(self.payload()) else {
+                return;
+            };
+            data.extend_from_slice(self.line_ending.bytes());
+            self.push_history();
+
+            let handle = self.handle;
+            ctx.spawn({
+                let ctx = ctx.clone();
+                async move { ctx.write_raw(handle, data).await }
+            });
+
+            return;
+        }
+
+        let Some(frame) = ctx.report_error(self.frame()) else {
+            return;
+        };
+        self.push_history();
+
+        self.send(ctx, frame);
+    }
+
+    /// moves command input to history
+    fn push_history(&mut self) {
+        self.history.push(HistoryEntry {
+            input: std::mem::take(&mut self.cmd_input),
+            mode: self.input_mode,
+        });
+    }
+
+    /// command picked from palette, or triggered by a shortcut
+    fn run_command(&mut self, ctx: &Arc<Context>, command: Command) {
+        match command {
+            Command::Send => self.send_input(ctx),
+            Command::Clear => self.clear(),
+            Command::Search => self.focus_search = true,
+            Command::ToggleWire => self.show_wire = !self.show_wire,
+            Command::Bookmark => {
+                if let Some(id) = self.selection.selected {
+                    if self.selection.bookmarks.remove(&id).is_none() {
+                        self.selection.bookmarks.insert(id, self.bookmark_color);
+                    }
+                }
+            },
+            Command::NextBookmark | Command::PreviousBookmark => {
+                let action = if command == Command::NextBookmark { SearchAction::Next } else { SearchAction::Previous };
+                let bookmarks = self.selection.bookmarks.keys().copied().collect::<Vec<_>>();
+
+                if let Some(id) = action.target(&bookmarks, self.selection.selected) {
+                    self.selection.selected = Some(id);
+                    self.scroll_to = Some(id);
+                }
+            },
+            Command::TogglePanel(tab) => {
+                let open = self.dock.find_tab(&tab).is_none();
+                dock::set_open(&mut self.dock, tab, open);
+            },
+            Command::ResetLayout => self.dock = dock::default_layout(),
+            Command::Paste => self.pasted = ctx.report_error(paste::from_clipboard()),
+            Command::Responder => self.responder.open = true,
+            Command::Alerts => self.notifier.open = true,
+            Command::Trigger => self.trigger.open = true,
+            Command::Fuzz => self.fuzzer.open = true,
+            Command::FirmwareUpdate => self.dfu.open = true,
+        }
+    }
+
+    /// replaces command input with history entry on up/down arrow
+    fn recall_history(&mut self, ui: &mut egui::Ui, input_id: egui::Id) {
+        let (up, down) = ui.input(|i| (i.key_pressed(Key::ArrowUp), i.key_pressed(Key::ArrowDown)));
+
+        let entry = if up {
+            self.history.previous(HistoryEntry { input: self.cmd_input.clone(), mode: self.input_mode })
+        } else if down {
+            self.history.next()
+        } else {
+            None
+        };
+
+        let Some(entry) = entry else {
+            return;
+        };
+
+        self.cmd_input = entry.input;
+        self.input_mode = entry.mode;
+
+        // keep cursor at the end, like in a shell
+        self.move_cursor_to_end(ui.ctx(), input_id);
+    }
+
+    fn move_cursor_to_end(&self, ctx: &egui::Context, input_id: egui::Id) {
+        if let Some(mut state) = TextEdit::load_state(ctx, input_id) {
+            let end = CCursor::new(self.cmd_input.chars().count());
+            state.set_ccursor_range(Some(CCursorRange::one(end)));
+            state.store(ctx, input_id);
+        }
+    }
+
+    /// dropdown below command input, with schema commands named like the input,
+    /// picked one replaces the input with its payload
+    fn draw_completions(&mut self, ui: &mut egui::Ui, input: &Response) {
+        let Some(schema) = self.schema.as_ref() else {
+            return;
+        };
+
+        let popup_id = input.id.with("completions");
+        let input_text = self.cmd_input.as_str();
+
+        let commands = schema
+            .complete(input_text)
+            .into_iter()
+            // nothing left to complete once payload is in the input
+            .filter(|command| command.payload.as_deref() != Some(input_text) && command.payload_hex.as_deref() != Some(input_text))
+            .take(10)
+            .collect::<Vec<_>>();
+
+        if commands.is_empty() {
+            if ui.memory(|m| m.is_popup_open(popup_id)) {
+                ui.memory_mut(|m| m.close_popup());
+            }
+
+            return;
+        }
+
+        if input.has_focus() {
+            ui.memory_mut(|m| m.open_popup(popup_id));
+        }
+
+        let picked = egui::popup_below_widget(ui, popup_id, input, |ui| {
+            let mut picked = None;
+
+            for command in &commands {
+                ui.horizontal(|ui| {
+                    if ui.selectable_label(false, &command.name).clicked() {
+                        picked = Some(*command);
+                    }
+
+                    ui.weak(&command.description);
+                });
+            }
+
+            picked
+        })
+        .flatten()
+        .cloned();
+
+        let Some(command) = picked else {
+            return;
+        };
+
+        if let Some(text) = command.payload {
+            self.input_mode = InputMode::Text;
+            self.cmd_input = text;
+        } else if let Some(hex) = command.payload_hex {
+            self.input_mode = InputMode::Hex;
+            self.cmd_input = hex;
+        }
+
+        if let Some(receiver) = command.receiver {
+            self.receiver = NumberBuffer::new(&receiver.to_string());
+        }
+
+        input.request_focus();
+        self.move_cursor_to_end(ui.ctx(), input.id);
+    }
+
+    /// one button per template, sending it right away
+    fn draw_templates(&mut self, ui: &mut egui::Ui, ctx: &Arc<Context>, templates: &[Template]) {
+        if templates.is_empty() {
+            return;
+        }
+
+        ui.horizontal_wrapped(|ui| {
+            for template in templates {
+                if ui.button(&template.name).on_hover_text(&template.payload).clicked() {
+                    if let Some(frame) = ctx.report_error(self.template_frame(template)) {
+                        self.template_seq += 1;
+                        self.send(ctx, frame);
+                    }
+                }
+            }
+        });
+    }
+
+    /// repeat period with start button, or stop button while frame is being repeated
+    fn draw_periodic_send(&mut self, ui: &mut egui::Ui, ctx: &Arc<Context>) {
+        if self.periodic_send.as_ref().is_some_and(|p| p.is_done()) {
+            self.periodic_send = None;
+        }
+
+        if let Some(periodic_send) = self.periodic_send.as_ref() {
+            periodic_send.draw(ui);
+            return;
+        }
+
+        ui.label("every");
+        ui.add(TextEdit::singleline(&mut self.repeat_period).desired_width(40.0));
+        ui.label("ms");
+
+        if ui.button("Repeat").on_hover_text("send frame from the input periodically").clicked() {
+            let result = (|| {
+                let frame = self.frame()?;
+                let period = self.repeat_period
+                    .as_str()
+                    .parse::<u64>()
+                    .with_context(|| format!("invalid repeat period `{}`", self.repeat_period.as_str()))?;
+
+                PeriodicSend::start(ctx, self.handle, frame, Duration::from_millis(period))
+            })();
+
+            self.periodic_send = ctx.report_error(result);
+        }
+    }
+
+    fn draw_file_send(&mut self, ui: &mut egui::Ui, ctx: &Arc<Context>) {
+        // failed ones are kept to be resumed
+        if self.file_send.as_ref().is_some_and(|f| f.is_done() && f.error().is_none()) {
+            self.file_send = None;
+        }
+
+        if let Some(file_send) = self.file_send.clone() {
+            if file_send.draw(ui) {
+                file_send.resume(ctx, self.handle);
+            }
+
+            if file_send.is_done() && ui.button("Close").clicked() {
+                self.file_send = None;
+            }
+
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            let clicked = ui.button("Send file").clicked();
+
+            ui.label("chunk size:");
+            ui.add(TextEdit::singleline(&mut self.file_chunk_size).desired_width(40.0))
+                .on_hover_text("maximum payload size of a single frame, leave empty to send whole file as one frame");
+            ui.label("to:");
+            ui.add(TextEdit::singleline(&mut self.file_receiver).desired_width(30.0))
+                .on_hover_text("address file is sent to, leave empty to send it to the receiver above");
+            ui.add(egui::DragValue::new(&mut self.file_retries).clamp_range(0..=100).prefix("retries: "))
+                .on_hover_text("times a frame is sent again after a failed write, or a missing acknowledgement");
+            ui.checkbox(&mut self.file_acknowledged, "acknowledged")
+                .on_hover_text("send with the transfer protocol of proto, receiver acknowledges chunks and checks CRC \
+                    of the file, otherwise frames carry raw chunks of the file");
+
+            ui.separator();
+
+            if ui.button("Send frames from file").on_hover_text("load frames from hex dump, JSONL, CSV, pcapng or pcap, and send them one by one, or replay them with their original timing").clicked() {
+                let path = rfd::FileDialog::new()
+                    .add_filter("frames", &["hex", "txt", "jsonl", "json", "csv", "pcapng", "pcap"])
+                    .pick_file();
+
+                if let Some(path) = path {
+                    if let Some(previous) = self.batch.as_ref() {
+                        previous.abort();
+                    }
+
+                    self.batch = ctx.report_error(BatchSend::load(&path));
+                }
+            }
+
+            if !clicked {
+                return;
+            }
+
+            if let Some(path) = rfd::FileDialog::new().pick_file() {
+                self.send_file(ctx, &path);
+            }
+        });
+    }
+
+    /// starts sending file at `path`, split as chunk size says
+    fn send_file(&mut self, ctx: &Arc<Context>, path: &Path) {
+        let result = (|| {
+            anyhow::ensure!(self.file_send.is_none(), "another file is being sent");
+
+            let PortSettings { sender, receiver } = self.port_settings()?;
+            let chunk_size = match self.file_chunk_size.as_str() {
+                "" => None,
+                size => Some(size.parse()?),
+            };
+            let receiver = match self.file_receiver.as_str() {
+                "" => receiver,
+                address => address
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("invalid file receiver address `{}`, expected 0-255", address))?,
+            };
+
+            let options = FileOptions {
+                chunk_size,
+                sender,
+                receiver,
+                retries: self.file_retries,
+                acknowledged: self.file_acknowledged,
+                target: transfer::Target::File,
+            };
+
+            FileSend::start(ctx, self.handle, path, options)
+        })();
+
+        if let Some(file_send) = ctx.report_error(result) {
+            self.file_send = Some(file_send);
+        }
+    }
+
+    /// serial lines (for serial ports only), transmit pacing, reconnecting and frames waiting to be sent
+    fn draw_link(&mut self, ui: &mut egui::Ui, ctx: &Arc<Context>) {
+        const BREAK_DURATION: Duration = Duration::from_millis(250);
+
+        ui.horizontal(|ui| {
+            let mut min_gap_ms = self.pacing.min_gap.as_millis() as u64;
+            let gap_changed = ui.add(egui::DragValue::new(&mut min_gap_ms).prefix("min gap: ").suffix(" ms"))
+                .on_hover_text("minimum time between sent frames")
+                .changed();
+            let rate_changed = ui.add(egui::DragValue::new(&mut self.pacing.max_rate).prefix("max: ").suffix(" frames/s"))
+                .on_hover_text("maximum number of sent frames per second, 0 for unlimited")
+                .changed();
+            let mut turnaround_ms = self.pacing.turnaround.as_millis() as u64;
+            let turnaround_changed = ui.add(egui::DragValue::new(&mut turnaround_ms).prefix("turnaround: ").suffix(" ms"))
+                .on_hover_text("minimum time between a received frame and the next sent one, \
+                    for half-duplex devices that can't receive right after transmitting")
+                .changed();
+
+            if gap_changed || rate_changed || turnaround_changed {
+                self.pacing.min_gap = Duration::from_millis(min_gap_ms);
+                self.pacing.turnaround = Duration::from_millis(turnaround_ms);
+                self.set_pacing(ctx);
+            }
+
+            let mut reject = self.tx_queue.policy() == QueuePolicy::Reject;
+            let hint = format!("when {} frames are queued, drop new ones instead of waiting for the device", serial_com::QUEUE_CAPACITY);
+            if ui.checkbox(&mut reject, "drop when full").on_hover_text(hint).changed() {
+                self.tx_queue.set_policy(if reject { QueuePolicy::Reject } else { QueuePolicy::Wait });
+            }
+
+            ui.separator();
+            let max_len_changed = ui.add(egui::DragValue::new(&mut self.max_frame_len).clamp_range(16..=1 << 17).prefix("max frame: ").suffix(" B"))
+                .on_hover_text("received frames longer than this (in wire bytes) are dropped, and shown as invalid")
+                .changed();
+
+            if max_len_changed {
+                self.set_max_frame_len(ctx);
+            }
+
+            ui.separator();
+            let mut write_ms = self.timeouts.write.map_or(0, |limit| limit.as_millis() as u64);
+            let mut read_ms = self.timeouts.read.map_or(0, |limit| limit.as_millis() as u64);
+            let write_changed = ui.add(egui::DragValue::new(&mut write_ms).prefix("write timeout: ").suffix(" ms"))
+                .on_hover_text("sending fails if one write takes longer, 0 for no limit")
+                .changed();
+            let read_changed = ui.add(egui::DragValue::new(&mut read_ms).prefix("read timeout: ").suffix(" ms"))
+                .on_hover_text("device is reopened if nothing is received for this long, 0 for no limit")
+                .changed();
+
+            if write_changed || read_changed {
+                let limit = |ms| (ms > 0).then(|| Duration::from_millis(ms));
+                self.timeouts = Timeouts { write: limit(write_ms), read: limit(read_ms) };
+                self.set_timeouts(ctx);
+            }
+
+            // monitored port is left as it is
+            if !transport::is_serial(&self.name) || self.config.monitor {
+                return;
+            }
+
+            ui.separator();
+            let mut control = None;
+
+            // line driving RS-485 transceiver is left to `serial_com`
+            let driven = self.rs485_enabled.then_some(self.rs485.line);
+
+            if ui.add_enabled(driven != Some(DirectionLine::Dtr), egui::Checkbox::new(&mut self.dtr, "DTR")).changed() {
+                control = Some(LineControl::Dtr(self.dtr));
+            }
+
+            if ui.add_enabled(driven != Some(DirectionLine::Rts), egui::Checkbox::new(&mut self.rts, "RTS")).changed() {
+                control = Some(LineControl::Rts(self.rts));
+            }
+
+            if ui.button("Send break").on_hover_text("hold TX line low for 250 ms").clicked() {
+                control = Some(LineControl::Break(BREAK_DURATION));
+            }
+
+            if let Some(control) = control {
+                self.control(ctx, control);
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let mut changed = ui.checkbox(&mut self.reconnect.enabled, "reconnect")
+                .on_hover_text("reopen the device after it disconnects, or its read or write fails")
+                .changed();
+
+            ui.add_enabled_ui(self.reconnect.enabled, |ui| {
+                let mut delay_ms = self.reconnect.initial_delay.as_millis() as u64;
+                changed |= ui.add(egui::DragValue::new(&mut delay_ms).clamp_range(10..=60_000).prefix("after: ").suffix(" ms"))
+                    .on_hover_text("wait before the first attempt")
+                    .changed();
+                changed |= ui.add(egui::DragValue::new(&mut self.reconnect.backoff).speed(0.1).clamp_range(1.0..=10.0).prefix("backoff: ×"))
+                    .on_hover_text("every following wait is this many times longer, up to a minute")
+                    .changed();
+                changed |= ui.add(egui::DragValue::new(&mut self.reconnect.max_attempts).prefix("attempts: "))
+                    .on_hover_text("give up after this many attempts, 0 for unlimited")
+                    .changed();
+
+                self.reconnect.initial_delay = Duration::from_millis(delay_ms);
+            });
+
+            if changed {
+                self.set_reconnect(ctx);
+            }
+        });
+
+        if transport::is_serial(&self.name) && !self.config.monitor {
+            ui.horizontal(|ui| {
+                let mut changed = ui.checkbox(&mut self.rs485_enabled, "RS-485")
+                    .on_hover_text("enable transmitter of half-duplex transceiver with a port line, only while sending")
+                    .changed();
+
+                ui.add_enabled_ui(self.rs485_enabled, |ui| {
+                    let name = |line| match line {
+                        DirectionLine::Rts => "RTS",
+                        DirectionLine::Dtr => "DTR",
+                    };
+
+                    ComboBox::from_id_source("rs485 line")
+                        .width(50.0)
+                        .selected_text(name(self.rs485.line))
+                        .show_ui(ui, |ui| {
+                            for line in [DirectionLine::Rts, DirectionLine::Dtr] {
+                                changed |= ui.selectable_value(&mut self.rs485.line, line, name(line)).changed();
+                            }
+                        });
+
+                    changed |= ui.checkbox(&mut self.rs485.inverted, "inverted")
+                        .on_hover_text("line is deasserted while sending")
+                        .changed();
+
+                    let mut before_ms = self.rs485.delay_before.as_millis() as u64;
+                    let mut after_ms = self.rs485.delay_after.as_millis() as u64;
+                    changed |= ui.add(egui::DragValue::new(&mut before_ms).prefix("before: ").suffix(" ms"))
+                        .on_hover_text("wait after transmitter is enabled, before the first byte")
+                        .changed();
+                    changed |= ui.add(egui::DragValue::new(&mut after_ms).prefix("turnaround: ").suffix(" ms"))
+                        .on_hover_text("wait after the last byte is sent out, before transmitter is disabled")
+                        .changed();
+
+                    self.rs485.delay_before = Duration::from_millis(before_ms);
+                    self.rs485.delay_after = Duration::from_millis(after_ms);
+                });
+
+                if changed {
+                    self.set_rs485(ctx);
+                }
+            });
+        }
+
+        let pending = self.tx_queue.pending();
+        if pending > 0 {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(format!("{} frames queued, {} bytes", pending, self.tx_queue.bytes()));
+
+                if self.tx_queue.is_full() {
+                    let hint = match self.tx_queue.policy() {
+                        QueuePolicy::Wait => "device doesn't keep up, senders wait for room",
+                        QueuePolicy::Reject => "device doesn't keep up, new frames are dropped",
+                    };
+                    ui.colored_label(ui.visuals().warn_fg_color, "queue full").on_hover_text(hint);
+                }
+
+                if ui.button("Cancel").on_hover_text("drop frames not yet written").clicked() {
+                    self.tx_queue.cancel();
+                }
+            });
+        }
+    }
+
+    fn draw_log(&mut self, ui: &mut egui::Ui, ctx: &Arc<Context>, settings: &mut Settings) {
+        ui.horizontal(|ui| {
+            if ui.button("Export").on_hover_text("save all frames to pcapng (Wireshark), pcap, CSV, JSONL or hex dump").clicked() {
+                let path = self.save_dialog(settings, "pcapng")
+                    .add_filter("pcapng", &["pcapng"])
+                    .add_filter("pcap", &["pcap"])
+                    .add_filter("CSV", &["csv"])
+                    .add_filter("JSON lines", &["jsonl"])
+                    .add_filter("hex dump", &["hex"])
+                    .save_file();
+
+                if let Some(path) = path {
+                    let _ = ctx.report_error(frame_log::export(&path, &self.records()));
+                    let _ = ctx.report_error(settings.set_log_path(&path));
+                }
+            }
+
+            ui.menu_button("Transcript", |ui| {
+                let payload = PayloadView {
+                    format: self.payload_format,
+                    schema: self.schema.as_deref(),
+                    schema_generation: self.schema_generation,
+                    plugins: &ctx.plugins,
+                };
+
+                if ui.button("Copy").on_hover_text("sent and received frames as readable text, e.g. for bug reports").clicked() {
+                    ui.output_mut(|o| o.copied_text = transcript::transcript(self, payload));
+                    ui.close_menu();
+                }
+
+                if ui.button("Save…").clicked() {
+                    ui.close_menu();
+
+                    let path = self.save_dialog(settings, "txt")
+                        .add_filter("text", &["txt"])
+                        .save_file();
+
+                    if let Some(path) = path {
+                        let result = std::fs::write(&path, transcript::transcript(self, payload))
+                            .with_context(|| format!("unable to save transcript to {}", path.display()));
+                        let _ = ctx.report_error(result);
+                    }
+                }
+            });
+
+            let bookmarks = self.selection.bookmarks.len();
+            ui.color_edit_button_srgba(&mut self.bookmark_color)
+                .on_hover_text(format!(
+                    "color of new bookmarks, {} bookmarks selected frame, {} / {} jump between {} bookmarks",
+                    settings.keybindings.format(ui.ctx(), Action::Bookmark),
+                    settings.keybindings.format(ui.ctx(), Action::PreviousBookmark),
+                    settings.keybindings.format(ui.ctx(), Action::NextBookmark),
+                    bookmarks,
+                ));
+
+            if !self.selection.marked.is_empty() {
+                let label = format!("Export selection ({})", self.selection.marked.len());
+                if ui.button(label).on_hover_text("save frames marked with shift + click").clicked() {
+                    let path = self.save_dialog(settings, "jsonl")
+                        .add_filter("JSON lines", &["jsonl"])
+                        .add_filter("CSV", &["csv"])
+                        .add_filter("hex dump", &["hex"])
+                        .add_filter("pcapng", &["pcapng"])
+                        .add_filter("pcap", &["pcap"])
+                        .save_file();
+
+                    if let Some(path) = path {
+                        let records = self.records_matching(|frame| self.selection.marked.contains(&frame.id));
+                        let _ = ctx.report_error(frame_log::export(&path, &records));
+                        let _ = ctx.report_error(settings.set_log_path(&path));
+                    }
+                }
+
+                if ui.button("Unmark").clicked() {
+                    self.selection.marked.clear();
+                }
+            }
+
+            if self.capture.is_some() {
+                return;
+            }
+
+            ui.checkbox(&mut self.publish, "Publish")
+                .on_hover_text("share frames through WebSocket, MQTT and REST bridges, and accept frames to send from them");
+
+            if let Some(log) = self.log.as_ref() {
+                ui.label(format!("logging to {}", log.path.display()));
+
+                if ui.button("Stop logging").clicked() {
+                    self.log = None;
+                }
+
+                return;
+            }
+
+            if !ui.button("Log to file").on_hover_text("append every sent and received frame to CSV or JSONL file").clicked() {
+                return;
+            }
+
+            let Some(path) = self.save_dialog(settings, "csv")
+                .add_filter("CSV", &["csv"])
+                .add_filter("JSON lines", &["jsonl"])
+                .save_file() else {
+                return;
+            };
+
+            self.log = ctx.report_error(FrameLog::open(&path));
+            let _ = ctx.report_error(settings.set_log_path(&path));
+        });
+    }
+}
+
+impl Device {
+    pub fn new(name: String, handle: DeviceHandle, config: PortConfig, port_settings: PortSettings) -> Self {
+        Self {
+            name,
+            alias: String::new(),
+            alias_key: None,
+            config,
+            cmd_input: Default::default(),
+            history: Default::default(),
+            input_mode: Default::default(),
+            raw_send: false,
+            line_ending: Default::default(),
+            sender: NumberBuffer::new(&port_settings.sender.to_string()),
+            receiver: NumberBuffer::new(&port_settings.receiver.to_string()),
+            handle,
+            received: Default::default(),
+            sent: Default::default(),
+            frame_limit: DEFAULT_FRAME_LIMIT,
+            dropped_sent: 0,
+            dropped_received: 0,
+            file_chunk_size: NumberBuffer::new("256"),
+            file_receiver: NumberBuffer::new(""),
+            file_retries: 3,
+            file_acknowledged: false,
+            file_send: None,
+            repeat_period: NumberBuffer::new("1000"),
+            periodic_send: None,
+            batch: None,
+            pasted: None,
+            template_seq: 0,
+            selection: Default::default(),
+            edit: None,
+            filter: Default::default(),
+            search: Default::default(),
+            focus_search: false,
+            palette: Default::default(),
+            scroll_to: None,
+            time_mode: Default::default(),
+            color_mode: Default::default(),
+            log: None,
+            window_pos: None,
+            detached: false,
+            capture: None,
+            show_discarded: true,
+            bookmark_color: Color32::from_rgb(255, 160, 0),
+            collapse_repeats: false,
+            auto_scroll: true,
+            show_wire: false,
+            payload_format: Default::default(),
+            schema: None,
+            schema_generation: 0,
+            schema_path: None,
+            plot: Default::default(),
+            latency: Default::default(),
+            composer: Composer::new(port_settings),
+            watches: Default::default(),
+            throughput: Default::default(),
+            console: Default::default(),
+            views: Vec::new(),
+            decoder: Default::default(),
+            dock: dock::default_layout(),
+            responder: Default::default(),
+            fuzzer: Default::default(),
+            dfu: Default::default(),
+            notifier: Default::default(),
+            trigger: Default::default(),
+            injection: Default::default(),
+            connection: ConnectionState::Connected,
+            reconnect: Default::default(),
+            rs485_enabled: false,
+            rs485: Default::default(),
+            // asserted when port is opened
+            dtr: true,
+            rts: true,
+            max_frame_len: proto::FrameBuilder::FRAME_MAX_LEN,
+            pacing: Default::default(),
+            timeouts: Default::default(),
+            tx_queue: Default::default(),
+            activity: Default::default(),
+            publish: false,
+            bridge: None,
+        }
+    }
+
+    /// device has a port, which wasn't opened for monitoring only
+    pub fn can_send(&self) -> bool {
+        self.capture.is_none() && !self.config.monitor
+    }
+
+    /// alias with port name, or just port name if device has no alias
+    pub fn title(&self) -> String {
+        if self.alias.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} ({})", self.alias, self.name)
+        }
+    }
+
+    /// dialog saving a log or export, named after alias if device has one
+    fn save_dialog(&self, settings: &Settings, extension: &str) -> rfd::FileDialog {
+        if self.alias.is_empty() {
+            settings.log_dialog()
+        } else {
+            settings.log_dialog().set_file_name(format!("{}.{}", self.alias, extension))
+        }
+    }
+
+    /// state saved when app is closed
+    fn session(&self) -> DeviceSession {
+        DeviceSession {
+            port: self.name.clone(),
+            config: self.config,
+            addresses: self.port_settings().unwrap_or_default(),
+            window_pos: self.window_pos.map(|pos| [pos.x, pos.y]),
+            frames: self.records(),
+        }
+    }
+
+    /// addresses currently entered in the device window
+    fn port_settings(&self) -> anyhow::Result<PortSettings> {
+        let parse = |buf: &NumberBuffer<3>, what: &str| {
+            buf.as_str()
+                .parse::<u8>()
+                .map_err(|_| anyhow::anyhow!("invalid {} address `{}`, expected 0-255", what, buf.as_str()))
+        };
+
+        Ok(PortSettings {
+            sender: parse(&self.sender, "sender")?,
+            receiver: parse(&self.receiver, "receiver")?,
+        })
+    }
+
+    /// frame shown in the inspector, with direction it was going
+    fn selected_frame(&self) -> Option<(FrameDirection, &DrawableFrame)> {
+        self.find_frame(self.selection.selected?)
+    }
+
+    /// frame with `id` from sent or received list
+    fn find_frame(&self, id: u64) -> Option<(FrameDirection, &DrawableFrame)> {
+        self.sent
+            .iter()
+            .find(|f| f.id == id)
+            .map(|f| (FrameDirection::Tx, f))
+            .or_else(|| self.received
+                .iter()
+                .find(|f| f.id == id)
+                .map(|f| (FrameDirection::Rx, f)))
+    }
+
+    /// stores frame in sent or received list, and appends it to the log file
+    pub fn push_frame(&mut self, direction: FrameDirection, frame: DrawableFrame) -> anyhow::Result<()> {
+        let result = match self.log.as_mut() {
+            Some(log) if frame.discarded.is_none() => log.write(direction, &frame),
+            _ => Ok(()),
+        };
+        let triggered = self.trigger.observe(direction, &frame);
+
+        if let (Some(bridge), None) = (self.bridge.as_ref(), frame.discarded.as_ref()) {
+            // fails only when no client is connected
+            let _ = bridge.send(BridgeEvent {
+                device: self.name.clone(),
+                record: Record { timestamp_us: frame.timestamp_us, direction, frame: frame.inner.clone(), bookmark: None },
+            });
+        }
+
+        match direction {
+            FrameDirection::Tx => self.sent.push_back(frame),
+            FrameDirection::Rx => self.received.push_back(frame),
+        }
+        self.trim();
+
+        if let Err(err) = result {
+            // don't report the same failure for every following frame
+            self.log = None;
+            return Err(err.context("logging stopped"));
+        }
+
+        triggered
+    }
+
+    /// stores frames and bytes read by `serial_com`, returns replies of auto responder to be sent
+    fn receive(&mut self, ctx: &Context, received: Received) -> Vec<(Duration, anyhow::Result<Frame>)> {
+        // bytes outside of frames are seen only in the console
+        for (time, data) in &received.data {
+            let timestamp_us = proto_tools::capture::timestamp_us(*time);
+            self.console.push(timestamp_us, data);
+            for view in &mut self.views {
+                view.push(timestamp_us, data);
+            }
+        }
+
+        let last_read = received.data.last().map_or_else(SystemTime::now, |(time, _)| *time);
+        self.decoder.update(received.stats, received.buffered, proto_tools::capture::timestamp_us(last_read));
+
+        let mut replies = Vec::new();
+
+        for (time, raw, result) in received.frames {
+            let timestamp_us = proto_tools::capture::timestamp_us(time);
+            let frame = match result {
+                Ok(frame) => {
+                    replies.extend(self.responder.replies(&frame));
+                    self.notifier.notify(&frame);
+                    if let Some(file_send) = self.file_send.as_ref() {
+                        file_send.received(&frame);
+                    }
+                    self.dfu.received(&frame);
+                    DrawableFrame::new(frame, timestamp_us)
+                },
+                Err(err) => {
+                    // overflows are counted in decoder stats already
+                    if let SerialComError::Decode(err) = &err {
+                        self.decoder.discarded(err.kind());
+                    }
+
+                    let mut frame = DrawableFrame::discarded(raw, err.to_string());
+                    frame.timestamp_us = timestamp_us;
+                    frame
+                },
+            };
+
+            let _ = ctx.report_error(self.push_frame(FrameDirection::Rx, frame));
+        }
+
+        replies
+    }
+
+    /// all valid frames of this device, ordered by time
+    fn records(&self) -> Vec<Record> {
+        self.records_matching(|_| true)
+    }
+
+    /// valid frames passing `keep`, ordered by time
+    fn records_matching(&self, keep: impl Fn(&DrawableFrame) -> bool) -> Vec<Record> {
+        let mut records = self.sent
+            .iter()
+            .map(|frame| (FrameDirection::Tx, frame))
+            .chain(self.received.iter().map(|frame| (FrameDirection::Rx, frame)))
+            .filter(|(_, frame)| frame.discarded.is_none() && keep(frame))
+            .map(|(direction, frame)| Record {
+                timestamp_us: frame.timestamp_us,
+                direction,
+                frame: frame.inner.clone(),
+                bookmark: self.selection.bookmarks.get(&frame.id).map(|color| [color.r(), color.g(), color.b()]),
+            })
+            .collect::<Vec<_>>();
+        records.sort_by_key(|record| record.timestamp_us);
+
+        records
+    }
+
+    /// appends previously captured frames, keeping their timestamps and bookmarks
+    fn load_records(&mut self, records: Vec<Record>) {
+        for record in records {
+            let frame = DrawableFrame::new(record.frame, record.timestamp_us);
+            if let Some([r, g, b]) = record.bookmark {
+                self.selection.bookmarks.insert(frame.id, Color32::from_rgb(r, g, b));
+            }
+
+            match record.direction {
+                FrameDirection::Tx => self.sent.push_back(frame),
+                FrameDirection::Rx => self.received.push_back(frame),
+            }
+        }
+
+        self.trim();
+    }
+
+    /// adds frames found in pasted text to received list, they aren't logged or published
+    fn import(&mut self, frames: Vec<DrawableFrame>) {
+        self.received.extend(frames);
+        self.trim();
+    }
+
+    /// drops oldest frames over `frame_limit`, counting them
+    fn trim(&mut self) {
+        let lists = [
+            (&mut self.sent, &mut self.dropped_sent),
+            (&mut self.received, &mut self.dropped_received),
+        ];
+
+        for (frames, dropped) in lists {
+            let excess = frames.len().saturating_sub(self.frame_limit);
+            for frame in frames.drain(..excess) {
+                self.selection.marked.remove(&frame.id);
+                self.selection.bookmarks.remove(&frame.id);
+            }
+
+            *dropped += excess as u64;
+        }
+    }
+
+    fn unmark(selection: &mut Selection, frames: &VecDeque<DrawableFrame>) {
+        for frame in frames {
+            selection.marked.remove(&frame.id);
+            selection.bookmarks.remove(&frame.id);
+        }
+    }
+
+    /// removes all frames of both lists
+    fn clear(&mut self) {
+        self.selection.marked.clear();
+        self.selection.bookmarks.clear();
+        self.sent.clear();
+        self.received.clear();
+        self.dropped_sent = 0;
+        self.dropped_received = 0;
+    }
+
+    /// changes state of port lines in background
+    fn control(&self, ctx: &Arc<Context>, control: LineControl) {
+        let handle = self.handle;
+        ctx.spawn({
+            let ctx = ctx.clone();
+            async move {
+                let (result_tx, result) = oneshot::channel();
+                ctx.command(Cmd::Control { handle, control, result: result_tx }).await?;
+                result.await??;
+
+                Ok(())
+            }
+        });
+    }
+
+    /// changes limits of transmit rate in background
+    fn set_pacing(&self, ctx: &Arc<Context>) {
+        let (handle, pacing) = (self.handle, self.pacing);
+        ctx.spawn({
+            let ctx = ctx.clone();
+            async move {
+                let (result_tx, result) = oneshot::channel();
+                ctx.command(Cmd::SetPacing { handle, pacing, result: result_tx }).await?;
+                result.await??;
+
+                Ok(())
+            }
+        });
+    }
+
+    /// changes RS-485 direction control in background, port lines are left as they are when it's disabled
+    fn set_rs485(&self, ctx: &Arc<Context>) {
+        let (handle, rs485) = (self.handle, self.rs485_enabled.then_some(self.rs485));
+        ctx.spawn({
+            let ctx = ctx.clone();
+            async move {
+                let (result_tx, result) = oneshot::channel();
+                ctx.command(Cmd::SetRs485 { handle, rs485, result: result_tx }).await?;
+                result.await??;
+
+                Ok(())
+            }
+        });
+    }
+
+    /// changes how device is reopened in background
+    fn set_reconnect(&self, ctx: &Arc<Context>) {
+        let (handle, policy) = (self.handle, self.reconnect);
+        ctx.spawn({
+            let ctx = ctx.clone();
+            async move {
+                let (result_tx, result) = oneshot::channel();
+                ctx.command(Cmd::SetReconnect { handle, policy, result: result_tx }).await?;
+                result.await??;
+
+                Ok(())
+            }
+        });
+    }
+
+    /// changes limits of device response times in background
+    fn set_timeouts(&self, ctx: &Arc<Context>) {
+        let (handle, timeouts) = (self.handle, self.timeouts);
+        ctx.spawn({
+            let ctx = ctx.clone();
+            async move {
+                let (result_tx, result) = oneshot::channel();
+                ctx.command(Cmd::SetTimeouts { handle, timeouts, result: result_tx }).await?;
+                result.await??;
+
+                Ok(())
+            }
+        });
+    }
+
+    /// changes limit of received frame length in background
+    fn set_max_frame_len(&self, ctx: &Arc<Context>) {
+        let (handle, max_len) = (self.handle, self.max_frame_len);
+        ctx.spawn({
+            let ctx = ctx.clone();
+            async move {
+                let (result_tx, result) = oneshot::channel();
+                ctx.command(Cmd::SetMaxFrameLen { handle, max_len, result: result_tx }).await?;
+                result.await??;
+
+                Ok(())
+            }
+        });
+    }
+
+    /// frame built from addresses and payload currently entered
+    fn frame(&self) -> anyhow::Result<Frame> {
+        let PortSettings { sender, receiver } = self.port_settings()?;
+
+        Ok(Frame {
+            sender,
+            receiver,
+            data: self.payload()?,
+        })
+    }
+
+    /// frame from `template`, missing addresses are taken from the device window
+    fn template_frame(&self, template: &Template) -> anyhow::Result<Frame> {
+        let (sender, receiver) = match (template.sender, template.receiver) {
+            (Some(sender), Some(receiver)) => (sender, receiver),
+            (sender, receiver) => {
+                let addresses = self.port_settings()?;
+                (sender.unwrap_or(addresses.sender), receiver.unwrap_or(addresses.receiver))
+            },
+        };
+
+        Ok(Frame {
+            sender,
+            receiver,
+            data: template.expand(self.template_seq, &self.cmd_input)?,
+        })
+    }
+
+    /// sends `frame` in background, it's added to sent list once written
+    fn send(&mut self, ctx: &Arc<Context>, frame: Frame) {
+        let handle = self.handle;
+        let injection = self.injection;
+        ctx.spawn({
+            let ctx = ctx.clone();
+            async move {
+                if injection.is_on() {
+                    inject::send(ctx, handle, frame, injection).await
+                } else {
+                    ctx.send_frame(handle, frame).await
+                }
+            }
+        });
+    }
+
+    /// payload currently entered in the command input
+    fn payload(&self) -> anyhow::Result<Vec<u8>> {
+        match self.input_mode {
+            InputMode::Text => Ok(self.cmd_input.clone().into_bytes()),
+            InputMode::Hex => proto_tools::bytes::parse_hex(&self.cmd_input),
+        }
+    }
+}
+
+impl Context {
+    /// sends `frame` to device with `handle`, and adds it to device's sent list
+    pub async fn send_frame(&self, handle: DeviceHandle, frame: Frame) -> anyhow::Result<()> {
+        let (result_tx, result) = oneshot::channel();
+        self.command(Cmd::SendData { handle, data: frame.serialize()?, framing: Framing::Frame, result: result_tx }).await?;
+
+        result.await??;
+
+        self.post(handle, DeviceEvent::Sent(Box::new(frame.into())));
+        Ok(())
+    }
+
+    /// passes `event` to the UI thread, which owns devices, false once it's gone
+    pub fn post(&self, handle: DeviceHandle, event: DeviceEvent) -> bool {
+        let posted = self.events.send((handle, event)).is_ok();
+        self.egui_ctx.request_repaint();
+        posted
+    }
+
+    /// writes `data` to device with `handle` as it is, without framing, it's not added to sent list
+    pub async fn write_raw(&self, handle: DeviceHandle, data: Vec<u8>) -> anyhow::Result<()> {
+        self.write(handle, data, Framing::Raw).await
+    }
+
+    /// writes `data` to device with `handle`, `framing` tells whether it's a (part of) frame, it's not added to sent list
+    pub async fn write(&self, handle: DeviceHandle, data: Vec<u8>, framing: Framing) -> anyhow::Result<()> {
+        let (result_tx, result) = oneshot::channel();
+        self.command(Cmd::SendData { handle, data: data.clone(), framing, result: result_tx }).await?;
+        result.await??;
+
+        log::info!("{:?} write: {}", framing, display_bytes::display_bytes(&data));
+        Ok(())
+    }
+
+    /// passes `cmd` to serial handler
+    pub async fn command(&self, cmd: Cmd) -> anyhow::Result<()> {
+        self.cmd_tx
+            .send(cmd)
+            .await
+            .ok()
+            .context("serial handler stopped")
+    }
+
+    /// runs `task` on the runtime, so UI thread doesn't wait for it, its error is shown as a toast
+    pub fn spawn<F>(self: &Arc<Self>, task: F)
+    where
+        F: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        let ctx = self.clone();
+        self.runtime.spawn(async move {
+            let _ = ctx.report_error(task.await);
+        });
+    }
+
+    #[must_use]
+    pub fn report_error<T>(&self, result: anyhow::Result<T>) -> Option<T> {
+        match result {
+            Ok(v) => Some(v),
+            Err(err) => {
+                let kind = match err.downcast_ref::<SerialComError>() {
+                    // user cancelled it, nothing to show
+                    Some(SerialComError::Cancelled) => return None,
+                    // device was closed while something was still using it
+                    Some(SerialComError::InvalidHandle) => {
+                        log::debug!("{:?}", err);
+                        return None;
+                    },
+                    // device list shows it already, it's reopened once it reappears
+                    Some(SerialComError::Disconnected) => ToastKind::Warning,
+                    // queue indicator of the device shows it already
+                    Some(SerialComError::QueueFull) => ToastKind::Warning,
+                    // device is reopened, it may be just quiet
+                    Some(SerialComError::ReadTimeout(_)) => ToastKind::Warning,
+                    _ => ToastKind::Error,
+                };
+
+                self.error_tx
+                    .send((kind, format!("{:?}", err)))
+                    .unwrap();
+
+                None
+            }
+        }
+    }
+}
+
+impl serial_com::Listener for Context {
+    async fn connection(self: &Arc<Self>, handle: DeviceHandle, state: ConnectionState) {
+        self.post(handle, DeviceEvent::Connection(state));
+    }
+
+    async fn received(self: &Arc<Self>, handle: DeviceHandle, received: Received) -> bool {
+        if self.closed.lock().unwrap().contains(&handle) {
+            return false;
+        }
+
+        self.post(handle, DeviceEvent::Received(received))
+    }
+
+    fn request_done(self: &Arc<Self>, _handle: DeviceHandle) {
+        self.egui_ctx.request_repaint();
+    }
+
+    async fn failed(self: &Arc<Self>, handle: DeviceHandle, error: SerialComError) {
+        self.post(handle, DeviceEvent::Failed(error));
+    }
+}
+
+impl DrawableFrame {
+    /// `tint` is text color of valid, not highlighted frame, `wire` shows escaped wire bytes instead of payload,
+    /// otherwise it's shown as `payload` view says, `send` is set to this frame when it's picked from context menu, offered only with `send_label`
+    #[allow(clippy::too_many_arguments)]
+    fn draw(
+        &self,
+        ui: &mut egui::Ui,
+        aval: f32,
+        time: &str,
+        selected: bool,
+        highlighted: bool,
+        tint: Option<Color32>,
+        wire: bool,
+        payload: PayloadView,
+        send_label: Option<&str>,
+        send: &mut Option<Frame>,
+    ) -> Response {
+        let font_id = appearance::frame_font(ui.style());
+
+        let color = if highlighted {
+            Color32::from_rgb(240, 200, 80)
+        } else if self.discarded.is_some() {
+            ui.visuals().error_fg_color
+        } else {
+            tint.unwrap_or(Color32::GRAY)
+        };
+
+        let key = LayoutKey {
+            width: aval,
+            time: time.to_owned(),
+            color,
+            font_size: font_id.size,
+            wire,
+            format: payload.format,
+            frame_format: self.format.get(),
+            schema_generation: payload.schema_generation,
+        };
+
+        let mut cached = self.layout.borrow_mut();
+        let layout = match cached.as_ref() {
+            Some((cached_key, layout)) if *cached_key == key => layout.clone(),
+            _ => {
+                let layout = self.layout_job(aval, time, font_id, color, wire, payload);
+                *cached = Some((key, layout.clone()));
+                layout
+            },
+        };
+        drop(cached);
+
+        let resp = ui.add_sized([aval, 0.0],
+            egui::SelectableLabel::new(
+                selected,
+                layout,
+            )
+        );
+
+        resp.context_menu(|ui| {
+            if let (Some(label), None) = (send_label, self.discarded.as_ref()) {
+                if ui.button(label).clicked() {
+                    *send = Some(self.inner.clone());
+                    ui.close_menu();
+                }
+
+                ui.separator();
+            }
+
+            let wire = match self.discarded.as_ref() {
+                Some(discarded) => Some(discarded.raw.clone()),
+                // payload too long for the length field has no wire form
+                None => self.inner.serialize().ok(),
+            };
+
+            if let Some(wire) = wire {
+                Self::copy_menu(ui, "Copy wire bytes", &wire);
+            }
+
+            if self.discarded.is_none() {
+                Self::copy_menu(ui, "Copy payload", &self.inner.data);
+
+                ui.separator();
+                let mut format = self.format.get();
+                if PayloadFormat::draw_menu(ui, &mut format) {
+                    self.format.set(format);
+                }
+            }
+        })
+    }
+
+    /// row text, two lines of `font_id` wrapped at `aval`
+    fn layout_job(&self, aval: f32, time: &str, font_id: FontId, color: Color32, wire: bool, payload: PayloadView) -> LayoutJob {
+        // roughly width of a monospace character, leaving some slack
+        let free_chars = (aval / (font_id.size * 9.0 / 14.0)) as usize;
+
+        let crc32 = Self::format_crc32(self.crc32);
+        let len = Self::format_length(self.frame_length);
+
+        let (first_line, details) = if let Some(discarded) = self.discarded.as_ref() {
+            let raw = Self::format_name(&proto_tools::bytes::format_hex(&discarded.raw), free_chars.saturating_sub(6));
+            let reason = Self::format_name(&discarded.reason, free_chars.saturating_sub(12 + time.len()));
+
+            (format!("[ERR] {}", raw), format!("{} T:{time}", reason))
+        } else {
+            let cmd = Self::format_name(&payload.text(self), free_chars.saturating_sub(6));
+
+            (format!("[CMD] {}", cmd), format!(
+                "R:{:0<3} S:{:0<3} CRC32:{crc32} LEN:{len} T:{time}",
+                self.inner.receiver,
+                self.inner.sender,
+            ))
+        };
+
+        match (wire, self.discarded.as_ref()) {
+            (true, None) => self.wire_layout(free_chars, &details, font_id, color, aval),
+            _ => LayoutJob::simple(format!("{}\n{}", first_line, details), font_id, color, aval),
+        }
+    }
+
+    /// wire bytes with escape sequences marked, as many as fit, followed by `details` line
+    fn wire_layout(&self, free_chars: usize, details: &str, font_id: FontId, color: Color32, wrap_width: f32) -> LayoutJob {
+        let plain = TextFormat::simple(font_id, color);
+        let mut job = LayoutJob::default();
+        job.wrap.max_width = wrap_width;
+        job.break_on_newline = true;
+
+        let wire = match inspector::wire_bytes(&self.inner) {
+            Ok(wire) => wire,
+            Err(err) => {
+                job.append(&format!("[WIRE] {}\n{}", err, details), 0.0, plain);
+                return job;
+            },
+        };
+
+        // every byte takes 3 characters with its separator, 1 is left for ellipsis
+        let fits = free_chars.saturating_sub(8) / 3;
+
+        job.append("[WIRE]", 0.0, plain.clone());
+        for b in wire.iter().take(fits) {
+            let format = TextFormat {
+                background: if b.escaped { Color32::from_rgb(110, 40, 40) } else { Color32::TRANSPARENT },
+                ..plain.clone()
+            };
+
+            job.append(" ", 0.0, plain.clone());
+            job.append(&format!("{:02X}", b.byte), 0.0, format);
+        }
+
+        if wire.len() > fits {
+            job.append("…", 0.0, plain.clone());
+        }
+
+        job.append(&format!("\n{}", details), 0.0, plain);
+        job
+    }
+
+    /// submenu copying `bytes` in one of `CopyFormat`s
+    fn copy_menu(ui: &mut egui::Ui, title: &str, bytes: &[u8]) {
+        ui.menu_button(title, |ui| {
+            for format in CopyFormat::ALL {
+                if ui.button(format.name()).clicked() {
+                    copy_format::copy(bytes, format);
+                    ui.close_menu();
+                }
+            }
+        });
+    }
+
+    fn format_name(name: &str, space: usize) -> String {
+        let space = space.max(3);
+
+        let len = name.chars().count();
+        if len > space {
+            let (pos,_) = name.char_indices().skip(space-2).next().unwrap();
+            format!("{:.<space$}", &name[..pos])
+        } else {
+            format!("{: <space$}", name)
+        }
+    }
+
+    fn format_crc32(crc: Option<u32>) -> String {
+        if let Some(n) = crc {
+            format!("{:0>8x}", n)
+        } else {
+            format!("{: >8}", "")
+        }
+    }
+
+    fn format_length(len: Option<usize>) -> String {
+        if let Some(n) = len {
+            format!("{: <4}", n)
+        } else {
+            format!("{: <4}", "")
+        }
+    }
+}
+
+impl DrawableFrame {
+    pub fn new(value: Frame, timestamp_us: u64) -> Self {
+        let crc32 = value.calculate_crc32()
+            .ok();
+
+        let frame_length = value.serialize()
+            .map(|v| v.len())
+            .ok();
+
+        Self {
+            id: FRAME_COUNTER.fetch_add(1, Ordering::Relaxed),
+            inner: value,
+            timestamp_us,
+            crc32,
+            frame_length,
+            discarded: None,
+            format: Cell::new(None),
+            plugin: OnceCell::new(),
+            layout: RefCell::new(None),
+        }
+    }
+
+    /// result of plugins, decoded on the first call
+    pub fn plugin_decode(&self, plugins: &Plugins) -> Option<&(String, PluginDecode)> {
+        self.plugin
+            .get_or_init(|| match self.discarded {
+                Some(_) => None,
+                None => plugins.decode(&self.inner).map(|(name, decoded)| (name.to_owned(), decoded)),
+            })
+            .as_ref()
+    }
+
+    /// bytes that failed to deserialize, timestamped with current time
+    pub fn discarded(raw: Vec<u8>, reason: String) -> Self {
+        Self {
+            id: FRAME_COUNTER.fetch_add(1, Ordering::Relaxed),
+            inner: Frame { sender: 0, receiver: 0, data: Vec::new() },
+            timestamp_us: proto_tools::capture::now_us(),
+            crc32: None,
+            frame_length: Some(raw.len()),
+            discarded: Some(Discarded { reason, raw }),
+            format: Cell::new(None),
+            plugin: OnceCell::new(),
+            layout: RefCell::new(None),
+        }
+    }
+}
+
+impl From<Frame> for DrawableFrame {
+    /// frame timestamped with current time
+    fn from(value: Frame) -> Self {
+        Self::new(value, proto_tools::capture::now_us())
     }
 }
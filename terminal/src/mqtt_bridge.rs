@@ -0,0 +1,154 @@
+//! MQTT client publishing received frames of published devices, and sending frames from command topic
+//!
+//! Messages in both directions are JSON objects, received frames are `BridgeEvent`s,
+//! commands are like `{"sender":123,"receiver":100,"data":"01A0FF"}`.
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use proto_tools::capture::Direction;
+use rumqttc::{AsyncClient, Event, EventLoop, MqttOptions, Packet, Publish, QoS};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_util::sync::CancellationToken;
+
+use crate::{Context, bridge::{self, BridgeEvent, FrameRequest}};
+
+/// placeholder in topics, replaced with port name of the device
+const DEVICE_PLACEHOLDER: &str = "{device}";
+/// delay before reconnecting to broker after connection fails
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// Broker and topics, editable in the UI
+#[derive(Debug, Clone)]
+pub struct MqttConfig {
+    /// `host:port`, port defaults to 1883
+    pub broker: String,
+    /// topic received frames are published to
+    pub rx_topic: String,
+    /// topic messages are taken from, to be sent to the device
+    pub command_topic: String,
+}
+
+/// Running client, disconnected when dropped
+pub struct MqttBridge {
+    pub broker: String,
+    cancel: CancellationToken,
+}
+
+impl MqttConfig {
+    /// `topic` with placeholder replaced, slashes in port name would add topic levels so they are replaced
+    fn topic(topic: &str, device: &str) -> String {
+        let device = device.trim_start_matches('/').replace('/', "_");
+        topic.replace(DEVICE_PLACEHOLDER, &device)
+    }
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            broker: "localhost:1883".into(),
+            rx_topic: format!("terminal/{}/rx", DEVICE_PLACEHOLDER),
+            command_topic: format!("terminal/{}/cmd", DEVICE_PLACEHOLDER),
+        }
+    }
+}
+
+impl MqttBridge {
+    pub fn start(ctx: &Arc<Context>, config: &MqttConfig, events: &broadcast::Sender<BridgeEvent>) -> anyhow::Result<Self> {
+        let (host, port) = match config.broker.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().with_context(|| format!("invalid broker port `{}`", port))?),
+            None => (config.broker.as_str(), 1883),
+        };
+
+        let mut options = MqttOptions::new(format!("terminal-{}", std::process::id()), host, port);
+        options.set_keep_alive(Duration::from_secs(10));
+
+        let (client, eventloop) = AsyncClient::new(options, 64);
+        let cancel = CancellationToken::new();
+
+        ctx.runtime.spawn(Self::run(ctx.clone(), config.clone(), client, eventloop, events.subscribe(), cancel.clone()));
+
+        Ok(Self {
+            broker: config.broker.clone(),
+            cancel,
+        })
+    }
+
+    async fn run(
+        ctx: Arc<Context>,
+        config: MqttConfig,
+        client: AsyncClient,
+        mut eventloop: EventLoop,
+        mut events: broadcast::Receiver<BridgeEvent>,
+        cancel: CancellationToken,
+    ) {
+        // one subscription covering command topics of all devices
+        let command_filter = config.command_topic.replace(DEVICE_PLACEHOLDER, "+");
+
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => {
+                    let _ = client.try_disconnect();
+                    return;
+                },
+
+                event = events.recv() => match event {
+                    Ok(event) if event.record.direction == Direction::Rx => {
+                        let topic = MqttConfig::topic(&config.rx_topic, &event.device);
+                        let payload = serde_json::to_vec(&event).unwrap();
+
+                        // event loop is polled by this task, waiting for space in its queue would deadlock
+                        if let Err(err) = client.try_publish(topic, QoS::AtMostOnce, false, payload) {
+                            log::warn!("MQTT publish failed: {}", err);
+                        }
+                    },
+                    Ok(_) => (),
+                    Err(RecvError::Lagged(missed)) => log::warn!("MQTT bridge too slow, {} frames dropped", missed),
+                    Err(RecvError::Closed) => return,
+                },
+
+                notification = eventloop.poll() => match notification {
+                    // subscriptions don't survive reconnect
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        log::info!("connected to MQTT broker {}", config.broker);
+
+                        if let Err(err) = client.try_subscribe(&command_filter, QoS::AtLeastOnce) {
+                            log::warn!("MQTT subscribe failed: {}", err);
+                        }
+                    },
+                    // sent aside, the event loop would stall until the device takes the frame
+                    Ok(Event::Incoming(Packet::Publish(publish))) => ctx.spawn({
+                        let (ctx, config) = (ctx.clone(), config.clone());
+                        async move { Self::command(&ctx, &config, &publish).await }
+                    }),
+                    Ok(_) => (),
+                    Err(err) => {
+                        log::warn!("MQTT connection to {} failed: {}", config.broker, err);
+                        tokio::time::sleep(RECONNECT_DELAY).await;
+                    },
+                },
+            }
+        }
+    }
+
+    /// sends frame from message on command topic
+    async fn command(ctx: &Arc<Context>, config: &MqttConfig, publish: &Publish) -> anyhow::Result<()> {
+        let device = ctx.published
+            .borrow()
+            .iter()
+            .find(|dev| MqttConfig::topic(&config.command_topic, &dev.device) == publish.topic)
+            .map(|dev| dev.device.clone())
+            .with_context(|| format!("no published device for MQTT topic `{}`", publish.topic))?;
+
+        let request: FrameRequest = serde_json::from_slice(&publish.payload)
+            .with_context(|| format!("invalid command on MQTT topic `{}`", publish.topic))?;
+
+        bridge::transmit(ctx, &device, request.frame()?).await
+    }
+}
+
+impl Drop for MqttBridge {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
@@ -0,0 +1,157 @@
+use std::time::{Duration, Instant};
+
+use eframe::{egui::{self, TextEdit}, epaint::Color32};
+use proto::Frame;
+use rodio::{OutputStream, Sink, Source, source::SineWave};
+
+use crate::{Device, filter::{CompiledFilter, FrameFilter}};
+
+/// sounds closer to each other are skipped, so a burst of matching frames doesn't turn into a buzz
+const SOUND_COOLDOWN: Duration = Duration::from_secs(1);
+
+/// Rule drawing attention to received frames matching `filter`
+#[derive(Debug, Clone)]
+pub struct NotifyRule {
+    pub enabled: bool,
+    /// shown in the notification
+    pub name: String,
+    pub filter: FrameFilter,
+    pub toast: bool,
+    pub sound: bool,
+    /// matching frames are shown in `color` in received list
+    pub highlight: bool,
+    pub color: Color32,
+}
+
+/// Notifies about received frames, e.g. error reports of the device
+#[derive(Debug, Default)]
+pub struct Notifier {
+    pub open: bool,
+    pub rules: Vec<NotifyRule>,
+    /// notifications not yet shown as toasts
+    pub pending: Vec<String>,
+    last_sound: Option<Instant>,
+}
+
+impl Notifier {
+    /// checks received `frame` against rules, queueing toasts and playing sound
+    pub fn notify(&mut self, frame: &Frame) {
+        let mut sound = false;
+
+        for rule in self.rules.iter().filter(|rule| rule.enabled && (rule.toast || rule.sound)) {
+            // invalid filters are shown in the editor, and never match
+            if !rule.filter.compile().is_ok_and(|filter| filter.matches(frame)) {
+                continue;
+            }
+
+            if rule.toast {
+                self.pending.push(format!(
+                    "{} (S:{} R:{}) {}",
+                    rule.name,
+                    frame.sender,
+                    frame.receiver,
+                    String::from_utf8_lossy(&frame.data),
+                ));
+            }
+
+            sound |= rule.sound;
+        }
+
+        if sound && self.last_sound.is_none_or(|last| last.elapsed() >= SOUND_COOLDOWN) {
+            self.last_sound = Some(Instant::now());
+            beep();
+        }
+    }
+
+    /// filters of enabled highlighting rules, with their colors
+    pub fn highlights(&self) -> Vec<(CompiledFilter, Color32)> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.enabled && rule.highlight)
+            .filter_map(|rule| rule.filter.compile().ok().map(|filter| (filter, rule.color)))
+            .collect()
+    }
+
+    pub fn draw(&mut self, ui: &mut egui::Ui) {
+        ui.label("rules are checked against every received frame");
+
+        let mut remove = None;
+
+        for (i, rule) in self.rules.iter_mut().enumerate() {
+            ui.push_id(i, |ui| {
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut rule.enabled, "");
+                    ui.add(TextEdit::singleline(&mut rule.name).desired_width(150.0).hint_text("name"));
+
+                    if ui.button("🗑").on_hover_text("remove").clicked() {
+                        remove = Some(i);
+                    }
+                });
+
+                ui.label("when received:");
+                rule.filter.draw(ui);
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut rule.toast, "toast");
+                    ui.checkbox(&mut rule.sound, "sound");
+                    ui.checkbox(&mut rule.highlight, "highlight");
+                    ui.add_enabled_ui(rule.highlight, |ui| ui.color_edit_button_srgba(&mut rule.color));
+                });
+            });
+        }
+
+        if let Some(i) = remove {
+            self.rules.remove(i);
+        }
+
+        ui.separator();
+
+        if ui.button("Add rule").clicked() {
+            self.rules.push(NotifyRule {
+                enabled: true,
+                name: format!("rule {}", self.rules.len() + 1),
+                filter: Default::default(),
+                toast: true,
+                sound: false,
+                highlight: true,
+                color: Color32::from_rgb(255, 120, 60),
+            });
+        }
+    }
+}
+
+/// short tone on default output device, played in background
+fn beep() {
+    std::thread::spawn(|| {
+        let result = OutputStream::try_default()
+            .map_err(anyhow::Error::from)
+            .and_then(|(_stream, handle)| {
+                let sink = Sink::try_new(&handle)?;
+                sink.append(SineWave::new(880.0).take_duration(Duration::from_millis(150)).amplify(0.2));
+                sink.sleep_until_end();
+
+                Ok(())
+            });
+
+        if let Err(err) = result {
+            log::warn!("unable to play notification sound: {}", err);
+        }
+    });
+}
+
+/// shows notification rules window of `device`, if it is open
+pub fn show(ctx: &egui::Context, device: &mut Device) {
+    let mut open = device.notifier.open;
+
+    egui::Window::new(format!("Notifications - {}", device.title()))
+        .id(egui::Id::new(("notify", device.handle)))
+        .open(&mut open)
+        .default_size([600.0, 300.0])
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| device.notifier.draw(ui));
+        });
+
+    device.notifier.open = open;
+}
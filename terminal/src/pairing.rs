@@ -0,0 +1,56 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::DrawableFrame;
+
+/// Sent frame and its response, stored under id of both of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Exchange {
+    /// id of the other frame of the exchange
+    pub other: u64,
+    /// time between sending request and receiving response
+    pub rtt_us: u64,
+}
+
+/// pairs sent frames with their responses
+///
+/// Response is the first received frame with swapped addresses, following the request.
+/// If request is sent again before response arrives, the response is paired with the latest one.
+/// `sent` and `received` have to be ordered by time.
+pub fn pair(sent: &VecDeque<DrawableFrame>, received: &VecDeque<DrawableFrame>) -> HashMap<u64, Exchange> {
+    let mut exchanges = HashMap::new();
+    // unanswered request, keyed by (sender, receiver) its response will have
+    let mut pending: HashMap<(u8, u8), &DrawableFrame> = HashMap::new();
+
+    let mut sent = sent.iter().filter(|frame| frame.discarded.is_none()).peekable();
+    let mut received = received.iter().filter(|frame| frame.discarded.is_none()).peekable();
+
+    loop {
+        let request_first = match (sent.peek(), received.peek()) {
+            (Some(request), Some(response)) => request.timestamp_us <= response.timestamp_us,
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (None, None) => break,
+        };
+
+        if request_first {
+            let request = sent.next().unwrap();
+            pending.insert((request.inner.receiver, request.inner.sender), request);
+            continue;
+        }
+
+        let response = received.next().unwrap();
+        if let Some(request) = pending.remove(&(response.inner.sender, response.inner.receiver)) {
+            let rtt_us = response.timestamp_us.saturating_sub(request.timestamp_us);
+
+            exchanges.insert(request.id, Exchange { other: response.id, rtt_us });
+            exchanges.insert(response.id, Exchange { other: request.id, rtt_us });
+        }
+    }
+
+    exchanges
+}
+
+/// e.g. `12.345ms`
+pub fn format_rtt(rtt_us: u64) -> String {
+    format!("{}.{:03}ms", rtt_us / 1000, rtt_us % 1000)
+}
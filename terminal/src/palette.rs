@@ -0,0 +1,167 @@
+use eframe::{egui::{self, Key, TextEdit}, emath::Align2};
+
+use crate::{dock::DeviceTab, keybindings::{Action, Keybindings}};
+
+/// Something done to a device window, picked by name from the palette
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Send,
+    Clear,
+    Search,
+    ToggleWire,
+    Bookmark,
+    NextBookmark,
+    PreviousBookmark,
+    TogglePanel(DeviceTab),
+    ResetLayout,
+    Paste,
+    Responder,
+    Alerts,
+    Trigger,
+    Fuzz,
+    FirmwareUpdate,
+}
+
+impl Command {
+    /// commands of a read-only capture viewer
+    const VIEWER: [Command; 14] = [
+        Command::Clear,
+        Command::Search,
+        Command::ToggleWire,
+        Command::Bookmark,
+        Command::NextBookmark,
+        Command::PreviousBookmark,
+        Command::TogglePanel(DeviceTab::Inspector),
+        Command::TogglePanel(DeviceTab::Plot),
+        Command::TogglePanel(DeviceTab::Stats),
+        Command::TogglePanel(DeviceTab::Latency),
+        Command::TogglePanel(DeviceTab::Composer),
+        Command::TogglePanel(DeviceTab::Watches),
+        Command::TogglePanel(DeviceTab::Throughput),
+        Command::ResetLayout,
+    ];
+
+    /// commands only possible with a port
+    const PORT: [Command; 3] = [
+        Command::Alerts,
+        Command::Trigger,
+        Command::TogglePanel(DeviceTab::Console),
+    ];
+
+    /// commands writing to the port, not offered for monitored ones
+    const SEND: [Command; 5] = [
+        Command::Send,
+        Command::Paste,
+        Command::Responder,
+        Command::Fuzz,
+        Command::FirmwareUpdate,
+    ];
+
+    pub fn name(&self) -> String {
+        match self {
+            Command::Send => "Send input".into(),
+            Command::Clear => "Clear frame lists".into(),
+            Command::Search => "Search".into(),
+            Command::ToggleWire => "Toggle payload / wire view".into(),
+            Command::Bookmark => "Bookmark selected frame".into(),
+            Command::NextBookmark => "Go to next bookmark".into(),
+            Command::PreviousBookmark => "Go to previous bookmark".into(),
+            Command::TogglePanel(tab) => format!("Toggle {} panel", tab.name().to_lowercase()),
+            Command::ResetLayout => "Reset panel layout".into(),
+            Command::Paste => "Decode frame from clipboard".into(),
+            Command::Responder => "Open responder".into(),
+            Command::Alerts => "Open notification rules".into(),
+            Command::Trigger => "Open trigger capture".into(),
+            Command::Fuzz => "Open fuzzer".into(),
+            Command::FirmwareUpdate => "Open firmware update".into(),
+        }
+    }
+
+    /// action with the same effect, its shortcut is shown next to the command
+    fn action(&self) -> Option<Action> {
+        match self {
+            Command::Send => Some(Action::Send),
+            Command::Clear => Some(Action::Clear),
+            Command::Search => Some(Action::Search),
+            Command::Bookmark => Some(Action::Bookmark),
+            Command::NextBookmark => Some(Action::NextBookmark),
+            Command::PreviousBookmark => Some(Action::PreviousBookmark),
+            _ => None,
+        }
+    }
+}
+
+/// Command palette of a device window, commands are filtered by typed text
+#[derive(Debug, Default)]
+pub struct Palette {
+    pub open: bool,
+    query: String,
+}
+
+impl Palette {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.query.clear();
+    }
+
+    /// shows palette if it's open, returns picked command
+    ///
+    /// Enter picks the first matching command, Escape closes the palette.
+    pub fn show(&mut self, ctx: &egui::Context, id: egui::Id, keys: &Keybindings, has_port: bool, can_send: bool) -> Option<Command> {
+        if !self.open {
+            return None;
+        }
+
+        let query = self.query.to_lowercase();
+        let commands = Command::VIEWER
+            .iter()
+            .chain(if can_send { &Command::SEND[..] } else { &[] })
+            .chain(if has_port { &Command::PORT[..] } else { &[] })
+            .filter(|command| command.name().to_lowercase().contains(&query))
+            .copied()
+            .collect::<Vec<_>>();
+
+        let mut picked = None;
+
+        egui::Window::new("Command palette")
+            .id(id)
+            .title_bar(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_TOP, [0.0, 60.0])
+            .fixed_size([360.0, 0.0])
+            .show(ctx, |ui| {
+                let input = ui.add(TextEdit::singleline(&mut self.query)
+                    .desired_width(f32::INFINITY)
+                    .hint_text("type to filter commands"));
+                input.request_focus();
+
+                if ui.input(|i| i.key_pressed(Key::Enter)) {
+                    picked = commands.first().copied();
+                }
+
+                ui.separator();
+
+                for command in &commands {
+                    ui.horizontal(|ui| {
+                        if ui.selectable_label(false, command.name()).clicked() {
+                            picked = Some(*command);
+                        }
+
+                        if let Some(action) = command.action() {
+                            ui.weak(keys.format(ui.ctx(), action));
+                        }
+                    });
+                }
+
+                if commands.is_empty() {
+                    ui.weak("no matching command");
+                }
+            });
+
+        if picked.is_some() || ctx.input(|i| i.key_pressed(Key::Escape)) {
+            self.toggle();
+        }
+
+        picked
+    }
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use eframe::egui;
+use egui_number_buffer::NumberBuffer;
+use proto::{Frame, FrameBuilder};
+use proto_tools::capture::Direction;
+
+use crate::{Context, Device, InputMode, inspector};
+
+/// decodes frame in wire format from hex dump, e.g. copied from frame list
+pub fn decode(text: &str) -> anyhow::Result<Frame> {
+    let bytes = proto_tools::bytes::parse_hex(text)?;
+
+    FrameBuilder::new()
+        .push_buf(&bytes)
+        .into_iter()
+        .next()
+        .context("no complete frame in pasted bytes")?
+        .context("invalid pasted frame")
+}
+
+/// frame from hex dump in clipboard
+pub fn from_clipboard() -> anyhow::Result<Frame> {
+    let text = arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .context("unable to read clipboard")?;
+
+    decode(&text)
+}
+
+/// shows window with pasted frame of `device`, offering to send it
+pub fn show(ctx: &egui::Context, app_ctx: &Arc<Context>, device: &mut Device) {
+    let Some(frame) = device.pasted.clone() else {
+        return;
+    };
+
+    let mut open = true;
+    let mut close = false;
+
+    egui::Window::new(format!("Pasted frame - {}", device.title()))
+        .id(egui::Id::new(("pasted", device.handle)))
+        .open(&mut open)
+        .default_width(520.0)
+        .show(ctx, |ui| {
+            inspector::draw(ui, Direction::Tx, proto_tools::capture::now_us(), &frame);
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Send").clicked() {
+                    device.send(app_ctx, frame.clone());
+                    close = true;
+                }
+
+                if ui.button("Edit").on_hover_text("copy addresses and payload to the input, to change them before sending").clicked() {
+                    device.sender = NumberBuffer::new(&frame.sender.to_string());
+                    device.receiver = NumberBuffer::new(&frame.receiver.to_string());
+                    device.input_mode = InputMode::Hex;
+                    device.cmd_input = proto_tools::bytes::format_hex(&frame.data);
+                    close = true;
+                }
+            });
+        });
+
+    if !open || close {
+        device.pasted = None;
+    }
+}
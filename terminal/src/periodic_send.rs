@@ -0,0 +1,89 @@
+use std::{sync::{Arc, atomic::{AtomicBool, AtomicUsize, Ordering}}, time::Duration};
+
+use eframe::egui;
+use proto::Frame;
+use serial_com::{Cmd, DeviceHandle, Framing, SerialComError};
+use tokio::{sync::oneshot, time::MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+
+use crate::{Context, events::DeviceEvent};
+
+/// Frame sent repeatedly in background, until stopped or sending fails
+pub struct PeriodicSend {
+    pub period: Duration,
+    /// number of frames already sent
+    pub sent: AtomicUsize,
+    pub done: AtomicBool,
+    cancel: CancellationToken,
+}
+
+impl PeriodicSend {
+    pub fn start(ctx: &Arc<Context>, handle: DeviceHandle, frame: Frame, period: Duration) -> anyhow::Result<Arc<Self>> {
+        anyhow::ensure!(!period.is_zero(), "repeat period has to be at least 1 ms");
+
+        // fail early, instead of in background
+        let data = frame.serialize()?;
+
+        let progress = Arc::new(Self {
+            period,
+            sent: AtomicUsize::new(0),
+            done: AtomicBool::new(false),
+            cancel: CancellationToken::new(),
+        });
+
+        ctx.runtime.spawn(Self::run(ctx.clone(), handle, frame, data, progress.clone()));
+        Ok(progress)
+    }
+
+    pub fn stop(&self) {
+        self.cancel.cancel();
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    /// draws counter with stop button
+    pub fn draw(&self, ui: &mut egui::Ui) {
+        let sent = self.sent.load(Ordering::Relaxed);
+
+        if ui.button(format!("Stop ({})", sent))
+            .on_hover_text(format!("sending every {} ms, {} frames sent", self.period.as_millis(), sent))
+            .clicked()
+        {
+            self.stop();
+        }
+    }
+
+    async fn run(ctx: Arc<Context>, handle: DeviceHandle, frame: Frame, data: Vec<u8>, progress: Arc<Self>) {
+        let mut interval = tokio::time::interval(progress.period);
+        // don't try to catch up after a slow send
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = progress.cancel.cancelled() => break,
+                _ = interval.tick() => (),
+            }
+
+            let (result_tx, result) = oneshot::channel();
+            if ctx.cmd_tx.send(Cmd::SendData { handle, data: data.clone(), framing: Framing::Frame, result: result_tx }).await.is_err() {
+                break;
+            }
+
+            let result = result
+                .await
+                .unwrap_or(Err(SerialComError::WorkerStopped));
+
+            if ctx.report_error(result.map_err(Into::into)).is_none() {
+                break;
+            }
+
+            progress.sent.fetch_add(1, Ordering::Relaxed);
+            ctx.post(handle, DeviceEvent::Sent(Box::new(frame.clone().into())));
+        }
+
+        progress.done.store(true, Ordering::Relaxed);
+        ctx.egui_ctx.request_repaint();
+    }
+}
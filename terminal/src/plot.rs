@@ -0,0 +1,170 @@
+use std::collections::VecDeque;
+
+use eframe::egui::{self, ComboBox, DragValue, TextEdit};
+use egui_plot::{Line, Plot, PlotPoints};
+
+use crate::{DrawableFrame, watches::Watch};
+
+/// Numeric type of value extracted from payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    F32,
+    F64,
+}
+
+/// Chart of a value extracted from every received payload
+pub struct PayloadPlot {
+    /// position of the value in payload, in bytes
+    pub offset: usize,
+    pub value_type: ValueType,
+    pub big_endian: bool,
+    /// plot only frames from this sender, empty for any
+    pub sender: String,
+}
+
+impl ValueType {
+    pub const ALL: [ValueType; 8] = [
+        ValueType::U8,
+        ValueType::I8,
+        ValueType::U16,
+        ValueType::I16,
+        ValueType::U32,
+        ValueType::I32,
+        ValueType::F32,
+        ValueType::F64,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ValueType::U8 => "u8",
+            ValueType::I8 => "i8",
+            ValueType::U16 => "u16",
+            ValueType::I16 => "i16",
+            ValueType::U32 => "u32",
+            ValueType::I32 => "i32",
+            ValueType::F32 => "f32",
+            ValueType::F64 => "f64",
+        }
+    }
+
+    /// size in bytes
+    pub fn width(&self) -> usize {
+        match self {
+            ValueType::U8 | ValueType::I8 => 1,
+            ValueType::U16 | ValueType::I16 => 2,
+            ValueType::U32 | ValueType::I32 | ValueType::F32 => 4,
+            ValueType::F64 => 8,
+        }
+    }
+
+    /// decodes value from exactly `width()` bytes
+    pub fn decode(&self, bytes: &[u8], big_endian: bool) -> f64 {
+        macro_rules! decode {
+            ($ty:ty) => {{
+                let bytes = bytes.try_into().unwrap();
+                if big_endian { <$ty>::from_be_bytes(bytes) as f64 } else { <$ty>::from_le_bytes(bytes) as f64 }
+            }};
+        }
+
+        match self {
+            ValueType::U8 => decode!(u8),
+            ValueType::I8 => decode!(i8),
+            ValueType::U16 => decode!(u16),
+            ValueType::I16 => decode!(i16),
+            ValueType::U32 => decode!(u32),
+            ValueType::I32 => decode!(i32),
+            ValueType::F32 => decode!(f32),
+            ValueType::F64 => decode!(f64),
+        }
+    }
+}
+
+impl PayloadPlot {
+    /// value in `data`, `None` if payload is too short
+    pub fn extract(&self, data: &[u8]) -> Option<f64> {
+        let bytes = data.get(self.offset..self.offset + self.value_type.width())?;
+
+        Some(self.value_type.decode(bytes, self.big_endian))
+    }
+
+    /// points of plotted value, x is time in seconds since `start`
+    fn points(&self, frames: &VecDeque<DrawableFrame>, start: u64) -> Vec<[f64; 2]> {
+        let sender = parse_sender(&self.sender).ok().flatten();
+
+        frames.iter()
+            .filter(|frame| frame.discarded.is_none())
+            .filter(|frame| sender.is_none_or(|sender| frame.inner.sender == sender))
+            .filter_map(|frame| {
+                let time = frame.timestamp_us.saturating_sub(start) as f64 / 1e6;
+                self.extract(&frame.inner.data).map(|value| [time, value])
+            })
+            .collect()
+    }
+
+    /// `watches` marked as plotted are drawn along
+    pub fn draw(&mut self, ui: &mut egui::Ui, frames: &VecDeque<DrawableFrame>, watches: &[Watch]) {
+        ui.horizontal(|ui| {
+            ui.label("offset:");
+            ui.add(DragValue::new(&mut self.offset).clamp_range(0..=u16::MAX as usize));
+
+            ComboBox::from_id_source("value type")
+                .width(50.0)
+                .selected_text(self.value_type.name())
+                .show_ui(ui, |ui| {
+                    for ty in ValueType::ALL {
+                        ui.selectable_value(&mut self.value_type, ty, ty.name());
+                    }
+                });
+
+            ui.selectable_value(&mut self.big_endian, true, "BE");
+            ui.selectable_value(&mut self.big_endian, false, "LE");
+
+            ui.label("sender:");
+            let invalid = parse_sender(&self.sender).is_err();
+            ui.add(TextEdit::singleline(&mut self.sender)
+                .desired_width(30.0)
+                .hint_text("any")
+                .text_color_opt(invalid.then_some(ui.visuals().error_fg_color)));
+        });
+
+        // x is time in seconds since first received frame
+        let start = frames.front().map_or(0, |frame| frame.timestamp_us);
+        let points = self.points(frames, start);
+        ui.label(format!("{} values, time in seconds", points.len()));
+
+        Plot::new("payload plot")
+            .height(ui.available_height().max(200.0))
+            .show(ui, |plot_ui| {
+                plot_ui.line(Line::new(PlotPoints::from(points)).name(self.value_type.name()));
+
+                for watch in watches.iter().filter(|watch| watch.plotted) {
+                    plot_ui.line(Line::new(PlotPoints::from(watch.points(frames, start))).name(watch.label()));
+                }
+            });
+    }
+}
+
+/// sender address typed by user, `None` for any
+pub fn parse_sender(sender: &str) -> Result<Option<u8>, std::num::ParseIntError> {
+    match sender.trim() {
+        "" => Ok(None),
+        sender => sender.parse().map(Some),
+    }
+}
+
+impl Default for PayloadPlot {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            value_type: ValueType::U8,
+            big_endian: true,
+            sender: String::new(),
+        }
+    }
+}
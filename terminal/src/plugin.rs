@@ -0,0 +1,173 @@
+//! Decoder plugins, dynamic libraries turning frames into labels and fields
+//!
+//! Plugin is a `cdylib` placed in `<config dir>/terminal/plugins`, exporting:
+//!
+//! ```c
+//! // must return 1
+//! uint32_t proto_plugin_abi_version(void);
+//! // NUL terminated, shown in the UI
+//! const char* proto_plugin_name(void);
+//! // writes UTF-8 JSON `{"label": "...", "fields": [{"name": "...", "value": "..."}]}` to `out`,
+//! // returns number of bytes written, 0 if frame isn't recognized, negative on error
+//! intptr_t proto_plugin_decode(uint8_t sender, uint8_t receiver, const uint8_t* data, size_t len, uint8_t* out, size_t out_cap);
+//! ```
+
+use std::{ffi::{c_char, CStr}, fs, path::{Path, PathBuf}};
+
+use anyhow::Context as _;
+use libloading::{Library, Symbol};
+use proto::Frame;
+use serde::Deserialize;
+
+const ABI_VERSION: u32 = 1;
+/// decode results longer than this are treated as an error
+const OUT_CAPACITY: usize = 16 * 1024;
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type NameFn = unsafe extern "C" fn() -> *const c_char;
+type DecodeFn = unsafe extern "C" fn(u8, u8, *const u8, usize, *mut u8, usize) -> isize;
+
+/// Structured decode result of a plugin
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PluginDecode {
+    /// single line shown in frame lists instead of payload
+    pub label: String,
+    /// shown in the inspector
+    #[serde(default)]
+    pub fields: Vec<PluginField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PluginField {
+    pub name: String,
+    pub value: String,
+}
+
+pub struct Plugin {
+    pub name: String,
+    pub path: PathBuf,
+    decode: DecodeFn,
+    /// keeps `decode` valid, has to be dropped after it
+    _library: Library,
+}
+
+/// Plugins loaded at startup, with libraries that failed to load
+#[derive(Default)]
+pub struct Plugins {
+    pub loaded: Vec<Plugin>,
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+impl Plugin {
+    /// loads plugin library at `path`, checking its ABI version
+    ///
+    /// # Safety
+    /// Library initializers run on load, and exported functions have to match the documented signatures.
+    pub unsafe fn load(path: &Path) -> anyhow::Result<Self> {
+        let library = Library::new(path)?;
+
+        let abi_version: Symbol<AbiVersionFn> = library.get(b"proto_plugin_abi_version\0")?;
+        let abi_version = abi_version();
+        anyhow::ensure!(abi_version == ABI_VERSION, "unsupported ABI version {}, expected {}", abi_version, ABI_VERSION);
+
+        let name: Symbol<NameFn> = library.get(b"proto_plugin_name\0")?;
+        let name = name();
+        anyhow::ensure!(!name.is_null(), "plugin has no name");
+        let name = CStr::from_ptr(name).to_string_lossy().into_owned();
+
+        let decode: Symbol<DecodeFn> = library.get(b"proto_plugin_decode\0")?;
+        let decode = *decode;
+
+        Ok(Self { name, path: path.to_owned(), decode, _library: library })
+    }
+
+    /// result of the plugin, `None` if it doesn't recognize `frame`
+    pub fn decode(&self, frame: &Frame) -> anyhow::Result<Option<PluginDecode>> {
+        let mut out = vec![0u8; OUT_CAPACITY];
+
+        // SAFETY: signature was checked by ABI version, buffers outlive the call
+        let written = unsafe {
+            (self.decode)(frame.sender, frame.receiver, frame.data.as_ptr(), frame.data.len(), out.as_mut_ptr(), out.len())
+        };
+
+        match usize::try_from(written) {
+            Ok(0) => Ok(None),
+            Ok(len) if len <= out.len() => serde_json::from_slice(&out[..len])
+                .map(Some)
+                .context("invalid decode result"),
+            Ok(len) => anyhow::bail!("decode result of {} bytes overflows buffer", len),
+            Err(_) => anyhow::bail!("decoding failed with code {}", written),
+        }
+    }
+}
+
+impl Plugins {
+    pub fn dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("terminal").join("plugins"))
+    }
+
+    /// loads every library in plugin directory, missing directory means no plugins
+    pub fn load() -> Self {
+        let mut plugins = Self::default();
+
+        let Some(entries) = Self::dir().and_then(|dir| fs::read_dir(dir).ok()) else {
+            return plugins;
+        };
+
+        for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+            let is_library = path
+                .extension()
+                .is_some_and(|ext| ext == std::env::consts::DLL_EXTENSION);
+
+            if !is_library {
+                continue;
+            }
+
+            // SAFETY: user put the library in plugin directory to be loaded by the terminal
+            match unsafe { Plugin::load(&path) } {
+                Ok(plugin) => {
+                    log::info!("loaded plugin `{}` from {}", plugin.name, path.display());
+                    plugins.loaded.push(plugin);
+                },
+                Err(err) => {
+                    log::warn!("unable to load plugin {}: {:#}", path.display(), err);
+                    plugins.errors.push((path, format!("{:#}", err)));
+                },
+            }
+        }
+
+        plugins
+    }
+
+    /// result of the first plugin recognizing `frame`, with its name
+    ///
+    /// Failing plugins are skipped, their error is logged.
+    pub fn decode(&self, frame: &Frame) -> Option<(&str, PluginDecode)> {
+        self.loaded.iter().find_map(|plugin| match plugin.decode(frame) {
+            Ok(decoded) => decoded.map(|decoded| (plugin.name.as_str(), decoded)),
+            Err(err) => {
+                log::warn!("plugin `{}` failed: {:#}", plugin.name, err);
+                None
+            },
+        })
+    }
+
+    /// list of loaded plugins and load errors
+    pub fn draw(&self, ui: &mut eframe::egui::Ui) {
+        if let Some(dir) = Self::dir() {
+            ui.weak(format!("libraries in {} are loaded at startup", dir.display()));
+        }
+
+        for plugin in &self.loaded {
+            ui.label(&plugin.name).on_hover_text(plugin.path.display().to_string());
+        }
+
+        for (path, error) in &self.errors {
+            ui.colored_label(ui.visuals().error_fg_color, format!("{}: {}", path.display(), error));
+        }
+
+        if self.loaded.is_empty() && self.errors.is_empty() {
+            ui.weak("no plugins");
+        }
+    }
+}
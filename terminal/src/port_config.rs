@@ -0,0 +1,261 @@
+use std::fmt;
+
+use eframe::egui::{self, ComboBox, TextBuffer, TextEdit};
+use egui_number_buffer::NumberBuffer;
+use serde::{Deserialize, Serialize};
+use tokio_serial::{DataBits, FlowControl, Parity, SerialPortBuilder, SerialPortInfo, SerialPortType, StopBits};
+
+/// standard baud rates offered in the selector
+pub const BAUD_PRESETS: &[u32] = &[
+    9600, 19200, 38400, 57600, 115200, 230400, 460800, 921600, 1_000_000, 2_000_000,
+];
+
+/// Parameters serial port is opened with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PortConfig {
+    pub baud_rate: u32,
+    pub data_bits: DataBits,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+    pub flow_control: FlowControl,
+    /// listen only, nothing is ever written to the port, to sniff a bus between two other nodes
+    pub monitor: bool,
+}
+
+impl PortConfig {
+    pub fn builder(&self, path: &str) -> SerialPortBuilder {
+        tokio_serial::new(path, self.baud_rate)
+            .data_bits(self.data_bits)
+            .parity(self.parity)
+            .stop_bits(self.stop_bits)
+            .flow_control(self.flow_control)
+    }
+
+    /// draws selectors for data bits, parity, stop bits and flow control
+    pub fn draw_line(&mut self, ui: &mut egui::Ui) {
+        ComboBox::from_id_source("data bits")
+            .width(40.0)
+            .selected_text(data_bits_str(self.data_bits))
+            .show_ui(ui, |ui| {
+                for bits in [DataBits::Five, DataBits::Six, DataBits::Seven, DataBits::Eight] {
+                    ui.selectable_value(&mut self.data_bits, bits, data_bits_str(bits));
+                }
+            })
+            .response
+            .on_hover_text("data bits");
+
+        ComboBox::from_id_source("parity")
+            .width(40.0)
+            .selected_text(parity_str(self.parity))
+            .show_ui(ui, |ui| {
+                for parity in [Parity::None, Parity::Even, Parity::Odd] {
+                    ui.selectable_value(&mut self.parity, parity, parity_str(parity));
+                }
+            })
+            .response
+            .on_hover_text("parity (none, even, odd)");
+
+        ComboBox::from_id_source("stop bits")
+            .width(40.0)
+            .selected_text(stop_bits_str(self.stop_bits))
+            .show_ui(ui, |ui| {
+                for bits in [StopBits::One, StopBits::Two] {
+                    ui.selectable_value(&mut self.stop_bits, bits, stop_bits_str(bits));
+                }
+            })
+            .response
+            .on_hover_text("stop bits");
+
+        ComboBox::from_id_source("flow control")
+            .width(70.0)
+            .selected_text(flow_control_str(self.flow_control))
+            .show_ui(ui, |ui| {
+                for flow_control in [FlowControl::None, FlowControl::Hardware, FlowControl::Software] {
+                    ui.selectable_value(&mut self.flow_control, flow_control, flow_control_str(flow_control));
+                }
+            })
+            .response
+            .on_hover_text("flow control, device pauses transmission when its buffer is full, \
+                signalled by RTS/CTS lines or XON/XOFF bytes. With XON/XOFF these bytes are escaped in sent frames, \
+                firmware built before the escapes were added to proto_cpp rejects such frames");
+    }
+}
+
+/// Baud rate picker, one of `BAUD_PRESETS` or custom value
+pub struct BaudSelector {
+    /// `None` when custom value is used
+    preset: Option<u32>,
+    custom: NumberBuffer<7>,
+}
+
+impl BaudSelector {
+    pub fn new(baud_rate: u32) -> Self {
+        let mut selector = Self {
+            preset: None,
+            custom: NumberBuffer::new(""),
+        };
+        selector.set(baud_rate);
+
+        selector
+    }
+
+    pub fn set(&mut self, baud_rate: u32) {
+        if BAUD_PRESETS.contains(&baud_rate) {
+            self.preset = Some(baud_rate);
+        } else {
+            self.preset = None;
+            self.custom = NumberBuffer::new(&baud_rate.to_string());
+        }
+    }
+
+    /// selected baud rate, `None` if custom value is invalid
+    pub fn value(&self) -> Option<u32> {
+        match self.preset {
+            Some(preset) => Some(preset),
+            None => self.custom
+                .as_str()
+                .parse::<u32>()
+                .ok()
+                .filter(|&v| v > 0),
+        }
+    }
+
+    pub fn draw(&mut self, ui: &mut egui::Ui) {
+        let previous = self.preset;
+
+        ComboBox::from_id_source("baud rate")
+            .width(90.0)
+            .selected_text(self.preset.map_or_else(|| "custom".to_owned(), |v| v.to_string()))
+            .show_ui(ui, |ui| {
+                for &preset in BAUD_PRESETS {
+                    ui.selectable_value(&mut self.preset, Some(preset), preset.to_string());
+                }
+
+                ui.selectable_value(&mut self.preset, None, "custom");
+            });
+
+        // start editing from previously selected rate
+        if let (Some(previous), None) = (previous, self.preset) {
+            self.custom = NumberBuffer::new(&previous.to_string());
+        }
+
+        if self.preset.is_none() {
+            let invalid = self.value().is_none();
+            ui.add(TextEdit::singleline(&mut self.custom)
+                .desired_width(70.0)
+                .text_color_opt(invalid.then_some(ui.visuals().error_fg_color)))
+                .on_hover_text("custom baud rate");
+        }
+    }
+}
+
+impl Default for PortConfig {
+    /// 115200 8N1
+    fn default() -> Self {
+        Self {
+            baud_rate: 115200,
+            data_bits: DataBits::Eight,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+            flow_control: FlowControl::None,
+            monitor: false,
+        }
+    }
+}
+
+impl fmt::Display for PortConfig {
+    /// e.g. `115200 8E1` or `921600 8N1 RTS/CTS (monitor only)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}{}{}",
+            self.baud_rate,
+            data_bits_str(self.data_bits),
+            parity_str(self.parity),
+            stop_bits_str(self.stop_bits),
+        )?;
+
+        match self.flow_control {
+            FlowControl::None => (),
+            FlowControl::Hardware => write!(f, " RTS/CTS")?,
+            FlowControl::Software => write!(f, " XON/XOFF")?,
+        }
+
+        if self.monitor {
+            write!(f, " (monitor only)")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// one line description of USB adapter behind the port, e.g. `0483:374B STMicroelectronics STLink SN 0670FF`,
+/// `None` for other kinds of ports
+pub fn usb_description(info: &SerialPortInfo) -> Option<String> {
+    let SerialPortType::UsbPort(usb) = &info.port_type else {
+        return None;
+    };
+
+    let mut description = format!("{:04X}:{:04X}", usb.vid, usb.pid);
+    for part in [&usb.manufacturer, &usb.product].into_iter().flatten() {
+        description.push(' ');
+        description.push_str(part);
+    }
+
+    if let Some(serial_number) = usb.serial_number.as_ref() {
+        description.push_str(" SN ");
+        description.push_str(serial_number);
+    }
+
+    Some(description)
+}
+
+/// what alias of device at `port` is stored under, serial number of its USB adapter if it has one,
+/// so the alias follows the adapter to another port, port name otherwise
+pub fn alias_key(port: &str, ports: &[SerialPortInfo]) -> String {
+    let serial_number = ports
+        .iter()
+        .find(|info| info.port_name == port)
+        .and_then(|info| match &info.port_type {
+            SerialPortType::UsbPort(usb) => usb.serial_number.as_ref().map(|sn| (usb.vid, usb.pid, sn)),
+            _ => None,
+        });
+
+    match serial_number {
+        Some((vid, pid, serial_number)) => format!("usb:{:04X}:{:04X}:{}", vid, pid, serial_number),
+        None => port.to_owned(),
+    }
+}
+
+fn data_bits_str(bits: DataBits) -> &'static str {
+    match bits {
+        DataBits::Five => "5",
+        DataBits::Six => "6",
+        DataBits::Seven => "7",
+        DataBits::Eight => "8",
+    }
+}
+
+fn parity_str(parity: Parity) -> &'static str {
+    match parity {
+        Parity::None => "N",
+        Parity::Even => "E",
+        Parity::Odd => "O",
+    }
+}
+
+fn stop_bits_str(bits: StopBits) -> &'static str {
+    match bits {
+        StopBits::One => "1",
+        StopBits::Two => "2",
+    }
+}
+
+fn flow_control_str(flow_control: FlowControl) -> &'static str {
+    match flow_control {
+        FlowControl::None => "none",
+        FlowControl::Hardware => "RTS/CTS",
+        FlowControl::Software => "XON/XOFF",
+    }
+}
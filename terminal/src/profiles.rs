@@ -0,0 +1,70 @@
+use eframe::egui::{self, ComboBox, TextBuffer, TextEdit};
+use egui_number_buffer::NumberBuffer;
+use serde::{Deserialize, Serialize};
+
+/// Named sender address, so a bus node can be impersonated without remembering its address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SenderProfile {
+    pub name: String,
+    pub address: u8,
+}
+
+/// profile picker next to sender address, `id` keeps name of the profile being added,
+/// returns true if `profiles` changed
+pub fn draw(ui: &mut egui::Ui, id: egui::Id, profiles: &mut Vec<SenderProfile>, sender: &mut NumberBuffer<3>) -> bool {
+    let current = sender.as_str().parse::<u8>().ok();
+    let selected = profiles
+        .iter()
+        .find(|profile| Some(profile.address) == current)
+        .map_or("profile", |profile| profile.name.as_str())
+        .to_owned();
+
+    let mut changed = false;
+    let mut remove = None;
+
+    ComboBox::from_id_source(id)
+        .width(70.0)
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            for (i, profile) in profiles.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    let label = format!("{} ({})", profile.name, profile.address);
+                    if ui.selectable_label(Some(profile.address) == current, label).clicked() {
+                        *sender = NumberBuffer::new(&profile.address.to_string());
+                    }
+
+                    if ui.small_button("🗑").on_hover_text("remove profile").clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+
+            if !profiles.is_empty() {
+                ui.separator();
+            }
+
+            ui.horizontal(|ui| {
+                let mut name = ui.data_mut(|d| d.get_temp::<String>(id).unwrap_or_default());
+                ui.add(TextEdit::singleline(&mut name).desired_width(90.0).hint_text("name"));
+
+                let add = ui.add_enabled(current.is_some() && !name.trim().is_empty(), egui::Button::new("Save sender"))
+                    .on_hover_text("save current sender address as a profile");
+
+                if let (true, Some(address)) = (add.clicked(), current) {
+                    profiles.push(SenderProfile { name: std::mem::take(&mut name).trim().to_owned(), address });
+                    changed = true;
+                }
+
+                ui.data_mut(|d| d.insert_temp(id, name));
+            });
+        })
+        .response
+        .on_hover_text("sender address profiles, to act as different bus nodes");
+
+    if let Some(i) = remove {
+        profiles.remove(i);
+        changed = true;
+    }
+
+    changed
+}
@@ -0,0 +1,96 @@
+use std::{fs, path::{Path, PathBuf}};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{Device, dock::{self, DeviceTab}, port_config::PortConfig, settings::PortSettings, templates::Template};
+
+/// extension of project files
+pub const EXTENSION: &str = "tproj";
+
+/// Bench configuration, devices with everything needed to open them again as they were, and templates used with them
+///
+/// Saved as pretty printed JSON, so it can be kept in git next to firmware. Paths of schemas inside
+/// the directory of the project are relative to it, so the project can be checked out anywhere.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Project {
+    pub devices: Vec<ProjectDevice>,
+    pub templates: Vec<Template>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectDevice {
+    pub port: String,
+    #[serde(flatten)]
+    pub config: PortConfig,
+    pub addresses: PortSettings,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub alias: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<PathBuf>,
+    /// names of open optional panels, see `DeviceTab::name`
+    #[serde(default)]
+    pub panels: Vec<String>,
+    /// top left corner of device window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_pos: Option<[f32; 2]>,
+    #[serde(default)]
+    pub detached: bool,
+}
+
+impl Project {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("unable to read project {}", path.display()))?;
+        let mut project = serde_json::from_str::<Self>(&json)
+            .with_context(|| format!("invalid project {}", path.display()))?;
+
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for device in &mut project.devices {
+            device.schema = device.schema.take().map(|schema| dir.join(schema));
+        }
+
+        Ok(project)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let mut project = self.clone();
+
+        for device in &mut project.devices {
+            if let Some(relative) = device.schema.as_ref().and_then(|schema| schema.strip_prefix(dir).ok()) {
+                device.schema = Some(relative.to_path_buf());
+            }
+        }
+
+        fs::write(path, serde_json::to_string_pretty(&project)? + "\n")
+            .with_context(|| format!("unable to save project to {}", path.display()))
+    }
+}
+
+impl ProjectDevice {
+    pub fn new(device: &Device) -> Self {
+        Self {
+            port: device.name.clone(),
+            config: device.config,
+            addresses: device.port_settings().unwrap_or_default(),
+            alias: device.alias.clone(),
+            schema: device.schema_path.clone(),
+            panels: DeviceTab::OPTIONAL
+                .iter()
+                .filter(|tab| device.dock.find_tab(tab).is_some())
+                .map(|tab| tab.name().to_owned())
+                .collect(),
+            window_pos: device.window_pos.map(|pos| [pos.x, pos.y]),
+            detached: device.detached,
+        }
+    }
+
+    /// opens and closes panels of `device` as saved
+    pub fn apply_panels(&self, device: &mut Device) {
+        for tab in DeviceTab::OPTIONAL {
+            dock::set_open(&mut device.dock, tab, self.panels.iter().any(|name| name == tab.name()));
+        }
+    }
+}
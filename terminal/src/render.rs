@@ -0,0 +1,158 @@
+use base64::Engine;
+use eframe::egui::{self, ComboBox};
+use proto_tools::schema::Schema;
+
+use crate::{DrawableFrame, plot::ValueType, plugin::Plugins};
+
+/// How payload is shown in frame lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PayloadFormat {
+    /// text, invalid sequences are replaced
+    #[default]
+    Utf8,
+    Hex,
+    Base64,
+    /// sequence of numbers, trailing bytes not forming a whole value are shown as hex
+    Values {
+        value_type: ValueType,
+        big_endian: bool,
+    },
+}
+
+/// Everything payload text in frame lists comes from
+#[derive(Clone, Copy)]
+pub struct PayloadView<'a> {
+    pub format: PayloadFormat,
+    /// matching payloads are shown as decoded fields
+    pub schema: Option<&'a Schema>,
+    /// changes whenever `schema` is replaced, cached layouts are keyed by it
+    pub schema_generation: u64,
+    pub plugins: &'a Plugins,
+}
+
+impl PayloadView<'_> {
+    /// format picked for the frame wins, then device schema, plugins, and format of the list
+    pub fn text(&self, frame: &DrawableFrame) -> String {
+        if let Some(format) = frame.format.get() {
+            return format.format(&frame.inner.data);
+        }
+
+        if let Some(decoded) = self.schema.and_then(|schema| schema.decode(&frame.inner)) {
+            return decoded.summary();
+        }
+
+        if let Some((_, decoded)) = frame.plugin_decode(self.plugins) {
+            return decoded.label.clone();
+        }
+
+        self.format.format(&frame.inner.data)
+    }
+}
+
+impl PayloadFormat {
+    /// formats shown in menus, values wider than a byte in both byte orders
+    fn all() -> impl Iterator<Item = PayloadFormat> {
+        [PayloadFormat::Utf8, PayloadFormat::Hex, PayloadFormat::Base64]
+            .into_iter()
+            .chain(ValueType::ALL.into_iter().flat_map(|value_type| {
+                let orders: &[bool] = if value_type.width() == 1 { &[false] } else { &[false, true] };
+                orders.iter().map(move |&big_endian| PayloadFormat::Values { value_type, big_endian })
+            }))
+    }
+
+    /// e.g. `utf-8` or `u16 BE`
+    pub fn name(&self) -> String {
+        match self {
+            PayloadFormat::Utf8 => "utf-8".into(),
+            PayloadFormat::Hex => "hex".into(),
+            PayloadFormat::Base64 => "base64".into(),
+            // byte order doesn't matter for single bytes
+            PayloadFormat::Values { value_type, .. } if value_type.width() == 1 => value_type.name().into(),
+            PayloadFormat::Values { value_type, big_endian } => {
+                format!("{} {}", value_type.name(), if *big_endian { "BE" } else { "LE" })
+            },
+        }
+    }
+
+    pub fn format(&self, data: &[u8]) -> String {
+        match self {
+            PayloadFormat::Utf8 => String::from_utf8_lossy(data).into_owned(),
+            PayloadFormat::Hex => proto_tools::bytes::format_hex(data),
+            PayloadFormat::Base64 => base64::engine::general_purpose::STANDARD.encode(data),
+            PayloadFormat::Values { value_type, big_endian } => {
+                let chunks = data.chunks_exact(value_type.width());
+                let rest = chunks.remainder();
+
+                let mut values = chunks
+                    .map(|bytes| match (value_type, value_type.decode(bytes, *big_endian)) {
+                        // widened to f64 it would show digits that aren't there
+                        (ValueType::F32, value) => (value as f32).to_string(),
+                        (_, value) => value.to_string(),
+                    })
+                    .collect::<Vec<_>>();
+
+                if !rest.is_empty() {
+                    values.push(format!("+{}", proto_tools::bytes::format_hex(rest)));
+                }
+
+                values.join(" ")
+            },
+        }
+    }
+
+    /// selector of device wide format
+    pub fn draw(&mut self, ui: &mut egui::Ui) {
+        ComboBox::from_id_source("payload format")
+            .width(70.0)
+            .selected_text(self.name())
+            .show_ui(ui, |ui| {
+                for format in Self::all().filter(Self::in_selector) {
+                    // picking another type keeps byte order
+                    let format = match (format, *self) {
+                        (PayloadFormat::Values { value_type, .. }, PayloadFormat::Values { big_endian, .. }) => {
+                            PayloadFormat::Values { value_type, big_endian }
+                        },
+                        _ => format,
+                    };
+
+                    ui.selectable_value(self, format, format.name());
+                }
+            })
+            .response
+            .on_hover_text("how payloads are shown, can be changed for single frames from their context menu");
+
+        if let PayloadFormat::Values { value_type, big_endian } = self {
+            if value_type.width() > 1 {
+                ui.selectable_value(big_endian, true, "BE");
+                ui.selectable_value(big_endian, false, "LE");
+            }
+        }
+    }
+
+    /// context menu entries overriding format of a single frame, `None` follows the device
+    ///
+    /// Returns true if `format` was changed.
+    pub fn draw_menu(ui: &mut egui::Ui, format: &mut Option<PayloadFormat>) -> bool {
+        let mut changed = false;
+
+        ui.menu_button("Show payload as", |ui| {
+            changed |= ui.selectable_value(format, None, "device default").clicked();
+            ui.separator();
+
+            for option in Self::all() {
+                changed |= ui.selectable_value(format, Some(option), option.name()).clicked();
+            }
+
+            if changed {
+                ui.close_menu();
+            }
+        });
+
+        changed
+    }
+
+    /// byte order has its own buttons in device selector
+    fn in_selector(format: &PayloadFormat) -> bool {
+        !matches!(format, PayloadFormat::Values { big_endian: true, .. })
+    }
+}
@@ -0,0 +1,146 @@
+use std::{sync::Arc, time::Duration};
+
+use eframe::egui::{self, DragValue, TextEdit};
+use proto::Frame;
+use serial_com::DeviceHandle;
+
+use crate::{Context, Device, filter::FrameFilter, templates::{self, Template}};
+
+/// Rule replying to received frames matching `filter`
+///
+/// Reply is built from `reply` template, with `{input}` replaced by payload of the received frame.
+/// Addresses left unset in the template are taken from received frame, swapped.
+#[derive(Debug, Clone)]
+pub struct ResponderRule {
+    pub enabled: bool,
+    pub filter: FrameFilter,
+    pub reply: Template,
+    /// milliseconds between receiving frame and replying
+    pub delay_ms: u64,
+}
+
+/// Emulates device's peer, by replying to received frames
+#[derive(Debug, Default)]
+pub struct AutoResponder {
+    pub open: bool,
+    pub enabled: bool,
+    pub rules: Vec<ResponderRule>,
+    /// number of replies, for `{seq}` placeholder
+    seq: u64,
+}
+
+impl AutoResponder {
+    /// replies to `frame` from every matching rule, with delays they should be sent after
+    pub fn replies(&mut self, frame: &Frame) -> Vec<(Duration, anyhow::Result<Frame>)> {
+        if !self.enabled {
+            return Vec::new();
+        }
+
+        let mut replies = Vec::new();
+
+        for rule in self.rules.iter().filter(|rule| rule.enabled) {
+            // invalid filters are shown in the editor, and never match
+            if !rule.filter.compile().is_ok_and(|filter| filter.matches(frame)) {
+                continue;
+            }
+
+            replies.push((Duration::from_millis(rule.delay_ms), Self::reply(&rule.reply, frame, self.seq)));
+            self.seq += 1;
+        }
+
+        replies
+    }
+
+    fn reply(reply: &Template, frame: &Frame, seq: u64) -> anyhow::Result<Frame> {
+        let input = if reply.hex {
+            proto_tools::bytes::format_hex(&frame.data)
+        } else {
+            String::from_utf8_lossy(&frame.data).into_owned()
+        };
+
+        Ok(Frame {
+            sender: reply.sender.unwrap_or(frame.receiver),
+            receiver: reply.receiver.unwrap_or(frame.sender),
+            data: reply.expand(seq, &input)?,
+        })
+    }
+
+    pub fn draw(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.enabled, "reply to received frames");
+        ui.label("{input} in reply is replaced with received payload, unset addresses are swapped from received frame");
+
+        let mut remove = None;
+
+        for (i, rule) in self.rules.iter_mut().enumerate() {
+            ui.push_id(i, |ui| {
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut rule.enabled, format!("rule {}", i + 1));
+
+                    if ui.button("🗑").on_hover_text("remove").clicked() {
+                        remove = Some(i);
+                    }
+                });
+
+                ui.label("when received:");
+                rule.filter.draw(ui);
+
+                ui.horizontal(|ui| {
+                    ui.label("reply after");
+                    ui.add(DragValue::new(&mut rule.delay_ms).suffix(" ms"));
+
+                    ui.label("S:");
+                    templates::address(ui, &mut rule.reply.sender);
+                    ui.label("R:");
+                    templates::address(ui, &mut rule.reply.receiver);
+
+                    ui.checkbox(&mut rule.reply.hex, "hex");
+                    ui.add(TextEdit::singleline(&mut rule.reply.payload).desired_width(200.0));
+                });
+            });
+        }
+
+        if let Some(i) = remove {
+            self.rules.remove(i);
+        }
+
+        ui.separator();
+
+        if ui.button("Add rule").clicked() {
+            self.rules.push(ResponderRule {
+                enabled: true,
+                filter: Default::default(),
+                reply: Template::default(),
+                delay_ms: 0,
+            });
+        }
+    }
+}
+
+/// sends reply to `device` after `delay`
+pub async fn send_reply(ctx: Arc<Context>, handle: DeviceHandle, delay: Duration, reply: anyhow::Result<Frame>) {
+    tokio::time::sleep(delay).await;
+
+    let result = match reply {
+        Ok(frame) => ctx.send_frame(handle, frame).await,
+        Err(err) => Err(err.context("unable to build auto-responder reply")),
+    };
+
+    let _ = ctx.report_error(result);
+}
+
+/// shows auto-responder window of `device`, if it is open
+pub fn show(ctx: &egui::Context, device: &mut Device) {
+    let mut open = device.responder.open;
+
+    egui::Window::new(format!("Auto-responder - {}", device.title()))
+        .id(egui::Id::new(("responder", device.handle)))
+        .open(&mut open)
+        .default_size([600.0, 300.0])
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| device.responder.draw(ui));
+        });
+
+    device.responder.open = open;
+}
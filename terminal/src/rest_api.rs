@@ -0,0 +1,138 @@
+//! HTTP server letting test scripts drive published devices, while the session is watched in the GUI
+//!
+//! - `GET /devices` lists published devices, as JSON array of `DeviceInfo`
+//! - `POST /send` sends a frame, body like `{"device":"COM3","sender":123,"receiver":100,"data":"01A0FF"}`
+//! - `GET /frames` streams frames of published devices as JSON lines (see `BridgeEvent`),
+//!   `?device=COM3` limits it to one device
+//!
+//! Failed requests are answered with `{"error":"..."}`.
+
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+
+use anyhow::Context as _;
+use axum::{Json, Router, body::Body, extract::{Query, State}, http::{StatusCode, header}, response::{IntoResponse, Response}, routing::{get, post}};
+use serde::Deserialize;
+use tokio::{net::TcpListener, sync::broadcast::{self, error::RecvError}};
+use tokio_util::sync::CancellationToken;
+
+use crate::{Context, bridge::{self, BridgeEvent, Transmit}};
+
+#[derive(Clone)]
+struct ApiState {
+    ctx: Arc<Context>,
+    events: broadcast::Sender<BridgeEvent>,
+    cancel: CancellationToken,
+}
+
+#[derive(Debug, Deserialize)]
+struct FramesQuery {
+    /// port name, frames of all published devices if missing
+    device: Option<String>,
+}
+
+/// failed request, answered with `400 Bad Request`
+struct ApiError(anyhow::Error);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let error = serde_json::json!({ "error": format!("{:#}", self.0) });
+        (StatusCode::BAD_REQUEST, Json(error)).into_response()
+    }
+}
+
+/// Running server, stopped when dropped
+pub struct RestApi {
+    pub addr: SocketAddr,
+    cancel: CancellationToken,
+}
+
+impl RestApi {
+    pub fn start(ctx: &Arc<Context>, addr: &str, events: &broadcast::Sender<BridgeEvent>) -> anyhow::Result<Self> {
+        // bound right away so errors are reported, without waiting on the runtime from the UI thread
+        let listener = std::net::TcpListener::bind(addr)
+            .with_context(|| format!("unable to listen on {}", addr))?;
+        listener.set_nonblocking(true)?;
+
+        let cancel = CancellationToken::new();
+
+        let api = Self {
+            addr: listener.local_addr()?,
+            cancel: cancel.clone(),
+        };
+
+        let router = Router::new()
+            .route("/devices", get(devices))
+            .route("/send", post(send))
+            .route("/frames", get(frames))
+            .with_state(ApiState {
+                ctx: ctx.clone(),
+                events: events.clone(),
+                cancel: cancel.clone(),
+            });
+
+        log::info!("REST API listening on {}", api.addr);
+        ctx.runtime.spawn(async move {
+            // streams end when cancelled too, otherwise shutdown would wait for their clients
+            let shutdown = async move { cancel.cancelled().await };
+
+            let result = match TcpListener::from_std(listener) {
+                Ok(listener) => axum::serve(listener, router).with_graceful_shutdown(shutdown).await,
+                Err(err) => Err(err),
+            };
+
+            if let Err(err) = result {
+                log::warn!("REST API stopped: {}", err);
+            }
+        });
+
+        Ok(api)
+    }
+}
+
+impl Drop for RestApi {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+async fn devices(State(state): State<ApiState>) -> Json<Vec<bridge::DeviceInfo>> {
+    Json(bridge::published(&state.ctx))
+}
+
+async fn send(State(state): State<ApiState>, Json(transmit): Json<Transmit>) -> Result<StatusCode, ApiError> {
+    let frame = transmit.frame.frame().map_err(ApiError)?;
+    bridge::transmit(&state.ctx, &transmit.device, frame).await.map_err(ApiError)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn frames(State(state): State<ApiState>, Query(query): Query<FramesQuery>) -> impl IntoResponse {
+    let stream = futures_util::stream::unfold(state.events.subscribe(), move |mut events| {
+        let device = query.device.clone();
+        let cancel = state.cancel.clone();
+
+        async move {
+            loop {
+                let event = tokio::select! {
+                    _ = cancel.cancelled() => return None,
+                    event = events.recv() => event,
+                };
+
+                match event {
+                    Ok(event) => {
+                        if device.as_ref().is_some_and(|device| *device != event.device) {
+                            continue;
+                        }
+
+                        let line = serde_json::to_string(&event).ok()? + "\n";
+                        return Some((Ok::<_, Infallible>(line), events));
+                    },
+                    Err(RecvError::Lagged(missed)) => log::warn!("REST API client too slow, {} frames dropped", missed),
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }
+    });
+
+    ([(header::CONTENT_TYPE, "application/x-ndjson")], Body::from_stream(stream))
+}
@@ -0,0 +1,88 @@
+use eframe::egui::{self, TextEdit};
+
+/// Search box of a device window
+#[derive(Debug, Default, Clone)]
+pub struct Search {
+    pub query: String,
+    /// query is hex bytes instead of text
+    pub hex: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchAction {
+    Previous,
+    Next,
+}
+
+impl Search {
+    /// bytes to search for, `None` if query is empty
+    pub fn pattern(&self) -> anyhow::Result<Option<Vec<u8>>> {
+        if self.query.is_empty() {
+            return Ok(None);
+        }
+
+        if self.hex {
+            proto_tools::bytes::parse_hex(&self.query).map(Some)
+        } else {
+            Ok(Some(self.query.clone().into_bytes()))
+        }
+    }
+
+    /// draws search controls, `matches` is number of frames matching the query, `focus` moves keyboard focus to the query
+    pub fn draw(&mut self, ui: &mut egui::Ui, matches: usize, focus: bool) -> Option<SearchAction> {
+        let mut action = None;
+
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+
+            let invalid = self.pattern().is_err();
+            let resp = ui.add(TextEdit::singleline(&mut self.query)
+                .desired_width(200.0)
+                .text_color_opt(invalid.then_some(ui.visuals().error_fg_color)));
+
+            if focus {
+                resp.request_focus();
+            }
+
+            if resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                action = Some(SearchAction::Next);
+                resp.request_focus();
+            }
+
+            ui.checkbox(&mut self.hex, "hex");
+
+            if ui.button("<").on_hover_text("previous match").clicked() {
+                action = Some(SearchAction::Previous);
+            }
+
+            if ui.button(">").on_hover_text("next match").clicked() {
+                action = Some(SearchAction::Next);
+            }
+
+            if !self.query.is_empty() && !invalid {
+                ui.label(format!("{} matches", matches));
+            }
+        });
+
+        action
+    }
+}
+
+impl SearchAction {
+    /// picks match to jump to, `matches` are frame ids in ascending order
+    pub fn target(&self, matches: &[u64], current: Option<u64>) -> Option<u64> {
+        match (self, current) {
+            (SearchAction::Next, Some(current)) => matches
+                .iter()
+                .find(|&&id| id > current)
+                .or(matches.first()),
+            (SearchAction::Previous, Some(current)) => matches
+                .iter()
+                .rev()
+                .find(|&&id| id < current)
+                .or(matches.last()),
+            (SearchAction::Next, None) => matches.first(),
+            (SearchAction::Previous, None) => matches.last(),
+        }.copied()
+    }
+}
@@ -0,0 +1,61 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Context;
+use proto_tools::capture::Record;
+use serde::{Deserialize, Serialize};
+
+use crate::{port_config::PortConfig, settings::PortSettings};
+
+/// Devices open when app was closed, stored as JSON next to settings, and offered to restore on next launch
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub devices: Vec<DeviceSession>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceSession {
+    pub port: String,
+    #[serde(flatten)]
+    pub config: PortConfig,
+    pub addresses: PortSettings,
+    /// top left corner of device window
+    #[serde(default)]
+    pub window_pos: Option<[f32; 2]>,
+    /// sent and received frames, ordered by time
+    #[serde(default)]
+    pub frames: Vec<Record>,
+}
+
+impl Session {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("terminal").join("session.json"))
+    }
+
+    /// loads previous session, `None` if there is nothing to restore
+    pub fn load() -> Option<Self> {
+        let path = Self::path()?;
+        let json = fs::read_to_string(&path).ok()?;
+
+        match serde_json::from_str::<Self>(&json) {
+            Ok(session) if !session.devices.is_empty() => Some(session),
+            Ok(_) => None,
+            Err(err) => {
+                log::warn!("ignoring invalid session at {}: {}", path.display(), err);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path()
+            .context("unable to find config directory")?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(&path, serde_json::to_string(self)?)
+            .with_context(|| format!("unable to save session to {}", path.display()))
+    }
+}
@@ -0,0 +1,128 @@
+use std::{collections::HashMap, fs, path::{Path, PathBuf}};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{appearance::Theme, history::HistoryEntry, keybindings::Keybindings, port_config::PortConfig, profiles::SenderProfile, templates::Template};
+
+/// Settings persisted between runs, stored as JSON in platform's config directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// per port settings, keyed by port name
+    pub ports: HashMap<String, PortSettings>,
+    /// names given to devices, keyed by `port_config::alias_key`, so they follow USB adapters across ports
+    pub aliases: HashMap<String, String>,
+    /// baud rate port was last opened with, keyed by port name
+    pub baud_rates: HashMap<String, u32>,
+    /// frames sent with a single click, see `Template`
+    pub templates: Vec<Template>,
+    /// named sender addresses, shared by all devices
+    pub sender_profiles: Vec<SenderProfile>,
+    /// sent command inputs, keyed by port name, oldest first
+    pub history: HashMap<String, Vec<HistoryEntry>>,
+    /// shortcuts changed from their defaults
+    pub keybindings: Keybindings,
+    /// port selected when the last port was opened
+    pub last_port: String,
+    /// line parameters the last port was opened with
+    pub port_config: PortConfig,
+    /// scale of the whole UI
+    pub pixels_per_point: f32,
+    pub theme: Theme,
+    /// size of monospace text in frame lists, in points
+    pub frame_font_size: f32,
+    /// directory of the last log or export, file dialogs start there
+    pub log_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PortSettings {
+    /// sender address of frames sent from the terminal
+    pub sender: u8,
+    /// receiver address of frames sent from the terminal
+    pub receiver: u8,
+}
+
+impl Settings {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("terminal").join("settings.json"))
+    }
+
+    /// loads settings, falling back to defaults if they don't exist or are invalid
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .unwrap_or_else(|err| {
+                    log::warn!("ignoring invalid settings at {}: {}", path.display(), err);
+                    Self::default()
+                }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path()
+            .context("unable to find config directory")?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("unable to save settings to {}", path.display()))
+    }
+
+    /// file dialog for logs and exports, starting in directory of the last one
+    pub fn log_dialog(&self) -> rfd::FileDialog {
+        match self.log_dir.as_ref() {
+            Some(dir) => rfd::FileDialog::new().set_directory(dir),
+            None => rfd::FileDialog::new(),
+        }
+    }
+
+    /// remembers directory of log or export at `path`, for the next dialog
+    pub fn set_log_path(&mut self, path: &Path) -> anyhow::Result<()> {
+        let dir = path.parent().map(Path::to_path_buf);
+        if dir == self.log_dir {
+            return Ok(());
+        }
+
+        self.log_dir = dir;
+        self.save()
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            ports: Default::default(),
+            aliases: Default::default(),
+            baud_rates: Default::default(),
+            templates: Default::default(),
+            sender_profiles: Default::default(),
+            history: Default::default(),
+            keybindings: Default::default(),
+            last_port: Default::default(),
+            port_config: Default::default(),
+            pixels_per_point: 0.9,
+            theme: Default::default(),
+            frame_font_size: 14.0,
+            log_dir: None,
+        }
+    }
+}
+
+impl Default for PortSettings {
+    fn default() -> Self {
+        Self {
+            sender: 123,
+            receiver: 100,
+        }
+    }
+}
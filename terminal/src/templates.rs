@@ -0,0 +1,129 @@
+use anyhow::Context;
+use eframe::egui::{self, DragValue, TextEdit};
+use serde::{Deserialize, Serialize};
+
+/// Named frame, sent with a single click from any device window
+///
+/// Payload can contain placeholders, replaced when frame is sent:
+/// * `{seq}` - number of templates sent from the device so far (one byte in hex mode)
+/// * `{time}` - unix time in seconds (big endian u32 in hex mode)
+/// * `{input}` - current content of device's command input
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Template {
+    pub name: String,
+    /// `None` to use address entered in the device window
+    pub sender: Option<u8>,
+    /// `None` to use address entered in the device window
+    pub receiver: Option<u8>,
+    pub payload: String,
+    /// payload is hex bytes instead of text
+    #[serde(default)]
+    pub hex: bool,
+}
+
+impl Template {
+    /// payload with placeholders replaced
+    pub fn expand(&self, seq: u64, input: &str) -> anyhow::Result<Vec<u8>> {
+        let mut text = String::new();
+        let mut rest = self.payload.as_str();
+
+        while let Some(start) = rest.find('{') {
+            text.push_str(&rest[..start]);
+
+            let end = rest[start..]
+                .find('}')
+                .with_context(|| format!("unclosed placeholder in `{}`", self.payload))?
+                + start;
+
+            let value = match &rest[start + 1..end] {
+                "seq" if self.hex => format!("{:02X}", seq as u8),
+                "seq" => seq.to_string(),
+                "time" => {
+                    let secs = proto_tools::capture::now_us() / 1_000_000;
+                    if self.hex { format!("{:08X}", secs as u32) } else { secs.to_string() }
+                },
+                "input" => input.to_owned(),
+                other => anyhow::bail!("unknown placeholder `{{{}}}` in template `{}`", other, self.name),
+            };
+
+            text.push_str(&value);
+            rest = &rest[end + 1..];
+        }
+
+        text.push_str(rest);
+
+        if self.hex {
+            proto_tools::bytes::parse_hex(&text)
+        } else {
+            Ok(text.into_bytes())
+        }
+    }
+}
+
+impl Default for Template {
+    fn default() -> Self {
+        Self {
+            name: "new template".into(),
+            sender: None,
+            receiver: None,
+            payload: String::new(),
+            hex: false,
+        }
+    }
+}
+
+/// draws editable list of templates
+pub fn draw_editor(ui: &mut egui::Ui, templates: &mut Vec<Template>) {
+    ui.label("placeholders: {seq}, {time}, {input}");
+
+    let mut remove = None;
+
+    egui::Grid::new("templates")
+        .num_columns(6)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("name");
+            ui.label("sender");
+            ui.label("receiver");
+            ui.label("hex");
+            ui.label("payload");
+            ui.end_row();
+
+            for (i, template) in templates.iter_mut().enumerate() {
+                ui.add(TextEdit::singleline(&mut template.name).desired_width(100.0));
+                address(ui, &mut template.sender);
+                address(ui, &mut template.receiver);
+                ui.checkbox(&mut template.hex, "");
+                ui.add(TextEdit::singleline(&mut template.payload).desired_width(220.0));
+
+                if ui.button("🗑").on_hover_text("remove").clicked() {
+                    remove = Some(i);
+                }
+
+                ui.end_row();
+            }
+        });
+
+    if let Some(i) = remove {
+        templates.remove(i);
+    }
+
+    if ui.button("Add").clicked() {
+        templates.push(Template::default());
+    }
+}
+
+/// address override, unchecked means device's address is used
+pub fn address(ui: &mut egui::Ui, address: &mut Option<u8>) {
+    ui.horizontal(|ui| {
+        let mut fixed = address.is_some();
+        ui.checkbox(&mut fixed, "")
+            .on_hover_text("unchecked uses address entered in the device window");
+
+        match (fixed, address.as_mut()) {
+            (true, Some(value)) => { ui.add(DragValue::new(value)); },
+            (true, None) => *address = Some(0),
+            (false, _) => *address = None,
+        }
+    });
+}
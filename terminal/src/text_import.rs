@@ -0,0 +1,87 @@
+use eframe::egui::{self, ComboBox, TextEdit};
+use proto::FrameBuilder;
+use serial_com::DeviceHandle;
+
+use crate::DrawableFrame;
+
+/// Window scanning pasted hex dumps for frames
+#[derive(Debug, Default)]
+pub struct TextImport {
+    pub open: bool,
+    text: String,
+    /// device whose received list gets the frames, new viewer if `None`
+    target: Option<DeviceHandle>,
+}
+
+/// frames found in `bytes`, ones that failed to deserialize are kept as discarded
+fn scan(bytes: &[u8]) -> Vec<DrawableFrame> {
+    let timestamp_us = proto_tools::capture::now_us();
+
+    FrameBuilder::new()
+        .push_buf_raw(bytes)
+        .into_iter()
+        .map(|(raw, result)| match result {
+            Ok(frame) => DrawableFrame::new(frame, timestamp_us),
+            Err(err) => DrawableFrame::discarded(raw, err.to_string()),
+        })
+        .collect()
+}
+
+impl TextImport {
+    /// `targets` are devices frames can be added to, with their titles,
+    /// returns picked target and found frames when import is confirmed
+    pub fn show(&mut self, ctx: &egui::Context, targets: &[(DeviceHandle, String)]) -> Option<(Option<DeviceHandle>, Vec<DrawableFrame>)> {
+        let mut import = None;
+
+        if self.target.is_some_and(|target| !targets.iter().any(|(handle, _)| *handle == target)) {
+            self.target = None;
+        }
+
+        egui::Window::new("Import from text")
+            .open(&mut self.open)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                ui.weak("paste a hex dump, offsets, text columns and # comments are skipped");
+                ui.add(TextEdit::multiline(&mut self.text)
+                    .desired_width(f32::INFINITY)
+                    .desired_rows(10)
+                    .font(egui::TextStyle::Monospace)
+                    .hint_text("00000000: 7e01 0203 ..."));
+
+                let bytes = proto_tools::bytes::parse_dump(&self.text);
+                let frames = scan(&bytes);
+                let invalid = frames.iter().filter(|frame| frame.discarded.is_some()).count();
+
+                ui.label(format!("{} bytes, {} frames ({} invalid)", bytes.len(), frames.len(), invalid));
+
+                ui.horizontal(|ui| {
+                    ui.label("Add to:");
+
+                    let selected = self.target
+                        .and_then(|target| targets.iter().find(|(handle, _)| *handle == target))
+                        .map_or("new viewer", |(_, title)| title.as_str());
+
+                    ComboBox::from_id_source("import target")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.target, None, "new viewer");
+                            for (handle, title) in targets {
+                                ui.selectable_value(&mut self.target, Some(*handle), title);
+                            }
+                        })
+                        .response
+                        .on_hover_text("frames added to an open device can be sent again from their context menu");
+
+                    if ui.add_enabled(!frames.is_empty(), egui::Button::new("Import")).clicked() {
+                        import = Some((self.target, frames));
+                    }
+                });
+            });
+
+        if import.is_some() {
+            self.open = false;
+        }
+
+        import
+    }
+}
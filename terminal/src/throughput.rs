@@ -0,0 +1,75 @@
+use std::collections::VecDeque;
+
+use eframe::egui;
+use egui_plot::{Legend, Line, Plot, PlotPoints};
+
+use crate::DrawableFrame;
+
+/// most points of a line, buckets get wider for longer histories
+const MAX_BUCKETS: u64 = 2000;
+
+/// Frames or bytes per second over time, sent and received separately
+#[derive(Debug, Default)]
+pub struct ThroughputView {
+    /// shows bytes per second instead of frames
+    bytes: bool,
+}
+
+/// bytes frame took on the wire
+fn wire_len(frame: &DrawableFrame) -> usize {
+    match frame.discarded.as_ref() {
+        Some(discarded) => discarded.raw.len(),
+        None => frame.frame_length.unwrap_or_default(),
+    }
+}
+
+impl ThroughputView {
+    /// rate in every `width_us` long bucket since `start_us`, empty buckets are kept so stalls show up
+    fn points(&self, frames: &VecDeque<DrawableFrame>, start_us: u64, width_us: u64, buckets: usize) -> Vec<[f64; 2]> {
+        let mut sums = vec![0usize; buckets];
+
+        for frame in frames {
+            let bucket = (frame.timestamp_us.saturating_sub(start_us) / width_us) as usize;
+            sums[bucket.min(buckets - 1)] += if self.bytes { wire_len(frame) } else { 1 };
+        }
+
+        let width_secs = width_us as f64 / 1e6;
+        sums.into_iter()
+            .enumerate()
+            .map(|(i, sum)| [i as f64 * width_secs, sum as f64 / width_secs])
+            .collect()
+    }
+
+    pub fn draw(&mut self, ui: &mut egui::Ui, sent: &VecDeque<DrawableFrame>, received: &VecDeque<DrawableFrame>) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.bytes, false, "frames/s");
+            ui.selectable_value(&mut self.bytes, true, "bytes/s")
+                .on_hover_text("wire bytes, with escape sequences and delimiters");
+        });
+
+        let timestamps = || sent.iter().chain(received).map(|frame| frame.timestamp_us);
+        let (Some(start_us), Some(end_us)) = (timestamps().min(), timestamps().max()) else {
+            ui.weak("no frames");
+            return;
+        };
+
+        let width_us = ((end_us - start_us) / MAX_BUCKETS).max(1_000_000);
+        let buckets = ((end_us - start_us) / width_us) as usize + 1;
+
+        ui.label(format!("time in seconds, averaged over {:.1} s", width_us as f64 / 1e6));
+
+        let unit = if self.bytes { "bytes/s" } else { "frames/s" };
+        let tx = self.points(sent, start_us, width_us, buckets);
+        let rx = self.points(received, start_us, width_us, buckets);
+
+        Plot::new("throughput")
+            .height(ui.available_height().max(200.0))
+            .legend(Legend::default())
+            .y_axis_label(unit)
+            .include_y(0.0)
+            .show(ui, |plot_ui| {
+                plot_ui.line(Line::new(PlotPoints::from(tx)).name("sent"));
+                plot_ui.line(Line::new(PlotPoints::from(rx)).name("received"));
+            });
+    }
+}
@@ -0,0 +1,55 @@
+use std::fmt::Write as _;
+
+use proto_tools::capture::Direction;
+
+use crate::{Device, frame_list, render::PayloadView};
+
+/// sent and received frames of `device` as readable text, ordered by time, e.g. for bug reports
+///
+/// ```text
+/// 14:03:27.512 → S:1 R:2 PING
+/// 14:03:27.530 ← S:2 R:1 PONG
+/// ```
+pub fn transcript(device: &Device, payload: PayloadView) -> String {
+    let mut frames = device.sent
+        .iter()
+        .map(|frame| (Direction::Tx, frame))
+        .chain(device.received.iter().map(|frame| (Direction::Rx, frame)))
+        .collect::<Vec<_>>();
+    // stable, so frames with the same timestamp keep their order
+    frames.sort_by_key(|(_, frame)| frame.timestamp_us);
+
+    let mut out = format!("# {} ({} frames)\n", device.title(), frames.len());
+    if let Some((_, first)) = frames.first() {
+        let _ = writeln!(out, "# started {}", frame_list::format_date_time(first.timestamp_us));
+    }
+
+    for (direction, frame) in frames {
+        let arrow = match direction {
+            Direction::Tx => "→",
+            Direction::Rx => "←",
+        };
+
+        let _ = match frame.discarded.as_ref() {
+            Some(discarded) => writeln!(
+                out,
+                "{} {} invalid ({}): {}",
+                frame_list::format_time(frame.timestamp_us),
+                arrow,
+                discarded.reason,
+                proto_tools::bytes::format_hex(&discarded.raw),
+            ),
+            None => writeln!(
+                out,
+                "{} {} S:{} R:{} {}",
+                frame_list::format_time(frame.timestamp_us),
+                arrow,
+                frame.inner.sender,
+                frame.inner.receiver,
+                payload.text(frame),
+            ),
+        };
+    }
+
+    out
+}
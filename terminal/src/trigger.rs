@@ -0,0 +1,239 @@
+use std::{collections::VecDeque, fs::File, io::BufWriter, path::PathBuf, sync::Arc, time::{Duration, Instant}};
+
+use anyhow::Context as _;
+use eframe::egui::{self, DragValue};
+use proto_tools::capture::{CaptureWriter, Direction, Record};
+
+use crate::{Context, Device, DrawableFrame, filter::FrameFilter, settings::Settings};
+
+/// Records frames around the first received frame matching `filter` to a file, like a logic analyzer
+#[derive(Debug)]
+pub struct TriggerCapture {
+    pub open: bool,
+    /// received frame starting the recording
+    pub filter: FrameFilter,
+    /// frames kept from before the trigger
+    pub pre_frames: usize,
+    /// recording stops after this many frames following the trigger, 0 for no limit
+    pub post_frames: usize,
+    /// recording stops this long after the trigger, 0 for no limit
+    pub post_secs: f64,
+    state: State,
+}
+
+enum State {
+    Idle,
+    /// waiting for trigger, latest frames are kept
+    Armed {
+        path: PathBuf,
+        ring: VecDeque<Record>,
+    },
+    Recording {
+        path: PathBuf,
+        writer: CaptureWriter<BufWriter<File>>,
+        started: Instant,
+        /// frames written after the trigger
+        frames: usize,
+    },
+    /// file is complete
+    Done {
+        path: PathBuf,
+        frames: usize,
+    },
+}
+
+impl std::fmt::Debug for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            State::Idle => write!(f, "Idle"),
+            State::Armed { path, ring } => write!(f, "Armed({}, {} buffered)", path.display(), ring.len()),
+            State::Recording { path, frames, .. } => write!(f, "Recording({}, {} frames)", path.display(), frames),
+            State::Done { path, frames } => write!(f, "Done({}, {} frames)", path.display(), frames),
+        }
+    }
+}
+
+impl Default for TriggerCapture {
+    fn default() -> Self {
+        Self {
+            open: false,
+            filter: Default::default(),
+            pre_frames: 100,
+            post_frames: 1000,
+            post_secs: 10.0,
+            state: State::Idle,
+        }
+    }
+}
+
+impl TriggerCapture {
+    pub fn is_armed(&self) -> bool {
+        matches!(self.state, State::Armed { .. } | State::Recording { .. })
+    }
+
+    /// waits for trigger, recording to `path` (format is picked by extension)
+    pub fn arm(&mut self, path: PathBuf) -> anyhow::Result<()> {
+        self.filter.compile().context("invalid trigger")?;
+        self.state = State::Armed { path, ring: VecDeque::with_capacity(self.pre_frames) };
+
+        Ok(())
+    }
+
+    /// checks sent or received `frame`, recording is stopped if writing fails
+    pub fn observe(&mut self, direction: Direction, frame: &DrawableFrame) -> anyhow::Result<()> {
+        if frame.discarded.is_some() {
+            return Ok(());
+        }
+
+        let record = Record { timestamp_us: frame.timestamp_us, direction, frame: frame.inner.clone(), bookmark: None };
+        let result = self.push(record);
+        if result.is_err() {
+            self.state = State::Idle;
+        }
+
+        result.context("trigger capture stopped")
+    }
+
+    fn push(&mut self, record: Record) -> anyhow::Result<()> {
+        match &mut self.state {
+            State::Armed { path, ring } => {
+                let triggered = record.direction == Direction::Rx
+                    && self.filter.compile().is_ok_and(|filter| filter.matches(&record.frame));
+
+                if !triggered {
+                    if self.pre_frames > 0 {
+                        if ring.len() >= self.pre_frames {
+                            ring.pop_front();
+                        }
+                        ring.push_back(record);
+                    }
+
+                    return Ok(());
+                }
+
+                let mut writer = CaptureWriter::create(path, None)?;
+                for record in ring.drain(..).chain([record]) {
+                    writer.write(&record)?;
+                }
+                writer.flush()?;
+
+                log::info!("trigger capture started, writing to {}", path.display());
+                self.state = State::Recording { path: std::mem::take(path), writer, started: Instant::now(), frames: 1 };
+            },
+            State::Recording { writer, frames, .. } => {
+                writer.write(&record)?;
+                writer.flush()?;
+                *frames += 1;
+            },
+            State::Idle | State::Done { .. } => return Ok(()),
+        }
+
+        self.check_stop();
+        Ok(())
+    }
+
+    /// ends recording once frame or time limit is reached
+    fn check_stop(&mut self) {
+        let State::Recording { path, started, frames, .. } = &mut self.state else {
+            return;
+        };
+
+        let by_frames = self.post_frames > 0 && *frames > self.post_frames;
+        let by_time = self.post_secs > 0.0 && started.elapsed() >= Duration::from_secs_f64(self.post_secs);
+
+        if by_frames || by_time {
+            // writer is flushed after every frame, dropping it closes the file
+            self.state = State::Done { path: std::mem::take(path), frames: *frames };
+        }
+    }
+
+    fn draw(&mut self, ui: &mut egui::Ui, app_ctx: &Arc<Context>, device: &Device, settings: &Settings) {
+        let armed = self.is_armed();
+
+        ui.add_enabled_ui(!armed, |ui| {
+            ui.label("start when received:");
+            self.filter.draw(ui);
+
+            ui.horizontal(|ui| {
+                ui.label("keep");
+                ui.add(DragValue::new(&mut self.pre_frames).clamp_range(0..=100_000));
+                ui.label("frames before trigger");
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("stop after");
+                ui.add(DragValue::new(&mut self.post_frames).clamp_range(0..=10_000_000));
+                ui.label("frames or");
+                ui.add(DragValue::new(&mut self.post_secs).clamp_range(0.0..=86_400.0).speed(0.1).suffix(" s"));
+            })
+            .response
+            .on_hover_text("whichever comes first, 0 for no limit");
+        });
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            match &self.state {
+                State::Idle => {
+                    ui.weak("not armed");
+                },
+                State::Armed { ring, .. } => {
+                    ui.spinner();
+                    ui.label(format!("waiting for trigger, {} frames buffered", ring.len()));
+                },
+                State::Recording { path, started, frames, .. } => {
+                    ui.spinner();
+                    ui.label(format!("recording to {}: {} frames, {:.1} s", path.display(), frames, started.elapsed().as_secs_f64()));
+                },
+                State::Done { path, frames } => {
+                    ui.label(format!("saved {} frames after trigger to {}", frames, path.display()));
+                },
+            }
+        });
+
+        if armed {
+            if ui.button("Stop").clicked() {
+                self.state = match std::mem::replace(&mut self.state, State::Idle) {
+                    State::Recording { path, frames, .. } => State::Done { path, frames },
+                    _ => State::Idle,
+                };
+            }
+
+            return;
+        }
+
+        if ui.button("Arm…").on_hover_text("choose file, then wait for trigger").clicked() {
+            let path = device.save_dialog(settings, "pcapng")
+                .add_filter("pcapng", &["pcapng"])
+                .add_filter("CSV", &["csv"])
+                .add_filter("JSON lines", &["jsonl"])
+                .save_file();
+
+            if let Some(path) = path {
+                let _ = app_ctx.report_error(self.arm(path));
+            }
+        }
+    }
+}
+
+/// shows trigger capture window of `device`, if it is open, and stops recording after its time limit
+pub fn show(ctx: &egui::Context, app_ctx: &Arc<Context>, device: &mut Device, settings: &Settings) {
+    let mut trigger = std::mem::take(&mut device.trigger);
+    trigger.check_stop();
+
+    // time limit has to be checked even if no frames arrive
+    if matches!(trigger.state, State::Recording { .. }) && trigger.post_secs > 0.0 {
+        ctx.request_repaint_after(Duration::from_millis(250));
+    }
+
+    let mut open = trigger.open;
+
+    egui::Window::new(format!("Trigger capture - {}", device.title()))
+        .id(egui::Id::new(("trigger", device.handle)))
+        .open(&mut open)
+        .default_width(520.0)
+        .show(ctx, |ui| trigger.draw(ui, app_ctx, device, settings));
+
+    trigger.open = open;
+    device.trigger = trigger;
+}
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use eframe::egui;
+use proto_tools::capture::Direction;
+
+use crate::{Context, Device, console::RawConsole, filter::FrameFilter, frame_list::{ColorMode, FrameList, TimeMode}, pairing, render::{PayloadFormat, PayloadView}};
+
+/// What a view of a device shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewKind {
+    /// one of the frame lists, with its own filter and format
+    Frames,
+    /// received bytes as text
+    Raw,
+}
+
+/// Additional window onto an open device, e.g. raw bytes next to frames, or frames of a single sender.
+///
+/// Frame views draw lists of the device, so frames aren't copied, raw views get received bytes
+/// from the device as they arrive, since they split them into lines on their own.
+pub struct DeviceView {
+    /// unique among views of the device, keeps window of the view when others are closed
+    id: u64,
+    pub kind: ViewKind,
+    pub direction: Direction,
+    pub filter: FrameFilter,
+    pub time_mode: TimeMode,
+    pub show_wire: bool,
+    pub payload_format: PayloadFormat,
+    pub show_discarded: bool,
+    /// bytes received since the view was opened
+    console: RawConsole,
+}
+
+impl DeviceView {
+    pub fn new(kind: ViewKind, views: &[DeviceView]) -> Self {
+        Self {
+            id: views.iter().map(|view| view.id + 1).max().unwrap_or(0),
+            kind,
+            direction: Direction::Rx,
+            filter: FrameFilter::default(),
+            time_mode: TimeMode::default(),
+            show_wire: false,
+            payload_format: PayloadFormat::default(),
+            show_discarded: true,
+            console: RawConsole::default(),
+        }
+    }
+
+    /// passes bytes read from the device to raw views
+    pub fn push(&mut self, timestamp_us: u64, data: &[u8]) {
+        if self.kind == ViewKind::Raw {
+            self.console.push(timestamp_us, data);
+        }
+    }
+
+    fn title(&self, device: &Device) -> String {
+        match (self.kind, self.direction) {
+            (ViewKind::Raw, _) => format!("{} - raw", device.title()),
+            (ViewKind::Frames, Direction::Rx) => format!("{} - received", device.title()),
+            (ViewKind::Frames, Direction::Tx) => format!("{} - sent", device.title()),
+        }
+    }
+
+    fn draw_frames(&mut self, ui: &mut egui::Ui, ctx: &Arc<Context>, device: &mut Device) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.direction, Direction::Rx, "received");
+            ui.selectable_value(&mut self.direction, Direction::Tx, "sent");
+
+            ui.separator();
+            ui.selectable_value(&mut self.time_mode, TimeMode::Absolute, "absolute");
+            ui.selectable_value(&mut self.time_mode, TimeMode::Delta, "delta");
+
+            ui.separator();
+            ui.selectable_value(&mut self.show_wire, false, "payload");
+            ui.selectable_value(&mut self.show_wire, true, "wire");
+            ui.add_enabled_ui(!self.show_wire, |ui| self.payload_format.draw(ui));
+
+            ui.separator();
+            ui.checkbox(&mut self.show_discarded, "show discarded");
+        });
+
+        let filter = egui::CollapsingHeader::new(if self.filter.is_empty() { "Filter" } else { "Filter (active)" })
+            .id_source("filter")
+            .show(ui, |ui| self.filter.draw(ui))
+            .body_returned
+            .flatten()
+            .or_else(|| self.filter.compile().ok())
+            .unwrap_or_default();
+
+        let exchanges = pairing::pair(&device.sent, &device.received);
+        let highlights = device.notifier.highlights();
+        let list = FrameList {
+            filter: &filter,
+            pattern: None,
+            time_mode: self.time_mode,
+            show_discarded: self.show_discarded,
+            collapse_repeats: device.collapse_repeats,
+            auto_scroll: device.auto_scroll,
+            scroll_to: None,
+            color_mode: ColorMode::Off,
+            exchanges: &exchanges,
+            can_send: device.can_send(),
+            wire: self.show_wire,
+            payload: PayloadView {
+                format: self.payload_format,
+                schema: device.schema.as_deref(),
+                schema_generation: device.schema_generation,
+                plugins: &ctx.plugins,
+            },
+            highlights: &highlights,
+        };
+
+        let frames = match self.direction {
+            Direction::Tx => &device.sent,
+            Direction::Rx => &device.received,
+        };
+
+        // selection is shared with the device window, so its inspector shows frames picked here
+        let mut resend = None;
+        list.draw(ui, self.direction, frames, ui.available_width(), &mut device.selection, &mut resend);
+
+        if let Some(frame) = resend {
+            device.send(ctx, frame);
+        }
+    }
+}
+
+/// menu opening new views of `device`
+pub fn draw_menu(ui: &mut egui::Ui, device: &mut Device) {
+    if ui.button("Frames").on_hover_text("frame list with its own filter and format").clicked() {
+        device.views.push(DeviceView::new(ViewKind::Frames, &device.views));
+        ui.close_menu();
+    }
+
+    // captures keep only frames
+    if device.capture.is_none() && ui.button("Raw").on_hover_text("received bytes as text, including ones outside of frames").clicked() {
+        device.views.push(DeviceView::new(ViewKind::Raw, &device.views));
+        ui.close_menu();
+    }
+}
+
+/// shows windows of all views of `device`, closed ones are removed
+pub fn show(ctx: &egui::Context, app_ctx: &Arc<Context>, device: &mut Device) {
+    // views need the rest of the device, so they are taken out of it while drawn
+    let mut views = std::mem::take(&mut device.views);
+
+    views.retain_mut(|view| {
+        let mut open = true;
+
+        egui::Window::new(view.title(device))
+            .id(egui::Id::new(("view", device.handle, view.id)))
+            .open(&mut open)
+            .default_size([500.0, 400.0])
+            .show(ctx, |ui| match view.kind {
+                ViewKind::Frames => view.draw_frames(ui, app_ctx, device),
+                ViewKind::Raw => view.console.draw(ui),
+            });
+
+        open
+    });
+
+    device.views = views;
+}
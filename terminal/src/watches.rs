@@ -0,0 +1,170 @@
+use std::collections::VecDeque;
+
+use eframe::egui::{self, ComboBox, DragValue, RichText, TextEdit};
+
+use crate::{DrawableFrame, frame_list, plot::{self, ValueType}};
+
+/// Value read from received frames, the latest one is shown
+#[derive(Debug, Clone)]
+pub struct Watch {
+    pub name: String,
+    /// position of the value in payload, in bytes
+    pub offset: usize,
+    pub value_type: ValueType,
+    pub big_endian: bool,
+    /// frames from this sender only, empty for any
+    pub sender: String,
+    /// also drawn in plot panel
+    pub plotted: bool,
+}
+
+impl Default for Watch {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            offset: 0,
+            value_type: ValueType::U8,
+            big_endian: true,
+            sender: String::new(),
+            plotted: false,
+        }
+    }
+}
+
+impl Watch {
+    /// value in `frame`, `None` if it's from another sender or too short
+    pub fn extract(&self, frame: &DrawableFrame) -> Option<f64> {
+        let sender = plot::parse_sender(&self.sender).ok()?;
+        if frame.discarded.is_some() || sender.is_some_and(|sender| frame.inner.sender != sender) {
+            return None;
+        }
+
+        let bytes = frame.inner.data.get(self.offset..self.offset + self.value_type.width())?;
+
+        Some(self.value_type.decode(bytes, self.big_endian))
+    }
+
+    /// latest value in `frames`, with timestamp of its frame
+    fn latest(&self, frames: &VecDeque<DrawableFrame>) -> Option<(f64, u64)> {
+        frames
+            .iter()
+            .rev()
+            .find_map(|frame| self.extract(frame).map(|value| (value, frame.timestamp_us)))
+    }
+
+    /// points of the value, x is time in seconds since `start_us`
+    pub fn points(&self, frames: &VecDeque<DrawableFrame>, start_us: u64) -> Vec<[f64; 2]> {
+        frames
+            .iter()
+            .filter_map(|frame| {
+                let time = frame.timestamp_us.saturating_sub(start_us) as f64 / 1e6;
+                self.extract(frame).map(|value| [time, value])
+            })
+            .collect()
+    }
+
+    /// name, or where the value is when it has none, e.g. `u16 BE @4 from 7`
+    pub fn label(&self) -> String {
+        if !self.name.is_empty() {
+            return self.name.clone();
+        }
+
+        let order = match (self.value_type.width(), self.big_endian) {
+            (1, _) => "",
+            (_, true) => " BE",
+            (_, false) => " LE",
+        };
+
+        match self.sender.trim() {
+            "" => format!("{}{} @{}", self.value_type.name(), order, self.offset),
+            sender => format!("{}{} @{} from {}", self.value_type.name(), order, self.offset, sender),
+        }
+    }
+
+    fn format(&self, value: f64) -> String {
+        match self.value_type {
+            // widened to f64 it would show digits that aren't there
+            ValueType::F32 => (value as f32).to_string(),
+            _ => value.to_string(),
+        }
+    }
+
+    /// inputs picking where the value is
+    fn draw_source(&mut self, ui: &mut egui::Ui, id: usize) {
+        ui.label("offset:");
+        ui.add(DragValue::new(&mut self.offset).clamp_range(0..=u16::MAX as usize));
+
+        ComboBox::from_id_source(("watch type", id))
+            .width(50.0)
+            .selected_text(self.value_type.name())
+            .show_ui(ui, |ui| {
+                for ty in ValueType::ALL {
+                    ui.selectable_value(&mut self.value_type, ty, ty.name());
+                }
+            });
+
+        if self.value_type.width() > 1 {
+            ui.selectable_value(&mut self.big_endian, true, "BE");
+            ui.selectable_value(&mut self.big_endian, false, "LE");
+        }
+
+        ui.label("sender:");
+        let invalid = plot::parse_sender(&self.sender).is_err();
+        ui.add(TextEdit::singleline(&mut self.sender)
+            .desired_width(30.0)
+            .hint_text("any")
+            .text_color_opt(invalid.then_some(ui.visuals().error_fg_color)));
+    }
+}
+
+/// Values of received frames followed live, without opening frames one by one
+#[derive(Debug, Default)]
+pub struct Watches {
+    pub list: Vec<Watch>,
+}
+
+impl Watches {
+    pub fn draw(&mut self, ui: &mut egui::Ui, received: &VecDeque<DrawableFrame>) {
+        let mut remove = None;
+
+        egui::Grid::new("watches")
+            .num_columns(5)
+            .striped(true)
+            .show(ui, |ui| {
+                for (i, watch) in self.list.iter_mut().enumerate() {
+                    let label = watch.label();
+                    ui.add(TextEdit::singleline(&mut watch.name)
+                        .desired_width(90.0)
+                        .hint_text(label));
+
+                    match watch.latest(received) {
+                        Some((value, timestamp_us)) => {
+                            ui.label(RichText::new(watch.format(value)).monospace().strong())
+                                .on_hover_text(format!("received at {}", frame_list::format_time(timestamp_us)));
+                        },
+                        None => {
+                            ui.weak("-");
+                        },
+                    }
+
+                    ui.horizontal(|ui| watch.draw_source(ui, i));
+
+                    ui.checkbox(&mut watch.plotted, "plot")
+                        .on_hover_text("draw values in plot panel");
+
+                    if ui.small_button("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+
+        if let Some(i) = remove {
+            self.list.remove(i);
+        }
+
+        if ui.button("Add watch").clicked() {
+            self.list.push(Watch::default());
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! WebSocket server giving other tools (e.g. browser dashboards) access to live devices
+//!
+//! Every frame of a published device is sent to all clients as JSON text message (see `BridgeEvent`).
+//! Clients can send frames with text messages like `{"device":"COM3","sender":123,"receiver":100,"data":"01A0FF"}`.
+
+use std::{net::SocketAddr, sync::Arc};
+
+use anyhow::Context as _;
+use futures_util::{SinkExt, StreamExt};
+use tokio::{net::{TcpListener, TcpStream}, sync::broadcast::{self, error::RecvError}};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_util::sync::CancellationToken;
+
+use crate::{Context, bridge::{self, BridgeEvent, Transmit}};
+
+/// Running server, stopped when dropped
+pub struct WsBridge {
+    pub addr: SocketAddr,
+    cancel: CancellationToken,
+}
+
+impl WsBridge {
+    pub fn start(ctx: &Arc<Context>, addr: &str, events: &broadcast::Sender<BridgeEvent>) -> anyhow::Result<Self> {
+        // bound right away so errors are reported, without waiting on the runtime from the UI thread
+        let listener = std::net::TcpListener::bind(addr)
+            .with_context(|| format!("unable to listen on {}", addr))?;
+        listener.set_nonblocking(true)?;
+
+        let cancel = CancellationToken::new();
+
+        let bridge = Self {
+            addr: listener.local_addr()?,
+            cancel: cancel.clone(),
+        };
+
+        log::info!("WebSocket bridge listening on {}", bridge.addr);
+        ctx.runtime.spawn(Self::accept(ctx.clone(), listener, events.clone(), cancel));
+
+        Ok(bridge)
+    }
+
+    async fn accept(ctx: Arc<Context>, listener: std::net::TcpListener, events: broadcast::Sender<BridgeEvent>, cancel: CancellationToken) {
+        let listener = match TcpListener::from_std(listener) {
+            Ok(listener) => listener,
+            Err(err) => {
+                log::warn!("WebSocket bridge stopped: {}", err);
+                return;
+            },
+        };
+
+        loop {
+            let (stream, peer) = tokio::select! {
+                _ = cancel.cancelled() => return,
+                result = listener.accept() => match result {
+                    Ok(client) => client,
+                    Err(err) => {
+                        log::warn!("unable to accept WebSocket client: {}", err);
+                        continue;
+                    },
+                },
+            };
+
+            let ctx = ctx.clone();
+            let events = events.subscribe();
+            let cancel = cancel.child_token();
+
+            tokio::spawn(async move {
+                log::info!("WebSocket client {} connected", peer);
+
+                if let Err(err) = Self::client(&ctx, stream, events, cancel).await {
+                    log::warn!("WebSocket client {}: {:#}", peer, err);
+                }
+
+                log::info!("WebSocket client {} disconnected", peer);
+            });
+        }
+    }
+
+    async fn client(
+        ctx: &Arc<Context>,
+        stream: TcpStream,
+        mut events: broadcast::Receiver<BridgeEvent>,
+        cancel: CancellationToken,
+    ) -> anyhow::Result<()> {
+        let (mut sink, mut source) = tokio_tungstenite::accept_async(stream)
+            .await?
+            .split();
+
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => return Ok(()),
+
+                event = events.recv() => match event {
+                    Ok(event) => sink.send(Message::Text(serde_json::to_string(&event)?)).await?,
+                    Err(RecvError::Lagged(missed)) => log::warn!("WebSocket client too slow, {} frames dropped", missed),
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+
+                message = source.next() => match message.transpose()? {
+                    Some(Message::Text(text)) => {
+                        // report failure to the client, instead of disconnecting it
+                        if let Err(err) = Self::transmit(ctx, &text).await {
+                            let error = serde_json::json!({ "error": format!("{:#}", err) });
+                            sink.send(Message::Text(error.to_string())).await?;
+                        }
+                    },
+                    Some(Message::Close(_)) | None => return Ok(()),
+                    Some(_) => (),
+                },
+            }
+        }
+    }
+
+    async fn transmit(ctx: &Arc<Context>, text: &str) -> anyhow::Result<()> {
+        let transmit: Transmit = serde_json::from_str(text)?;
+
+        bridge::transmit(ctx, &transmit.device, transmit.frame.frame()?).await
+    }
+}
+
+impl Drop for WsBridge {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}